@@ -1,20 +1,41 @@
 // SeeSea Self-Contained Installer - CLI Module
 // 模块名称: cli
 // 职责范围: 处理命令行参数解析
-// 已实现功能: Args结构体定义
-// 使用依赖: clap
-// 主要接口: Args结构体
+// 已实现功能: Args结构体定义、配置覆盖层参数、打包压缩参数解析、签名验证策略解析、带单位的大小解析、加密口令读取
+// 使用依赖: clap, rpassword, layering, packager
+// 主要接口: Args结构体, PackPreset, Args::config_overrides, Args::pack_options, Args::unpack_options, Args::signature_policy, Args::passphrase, parse_size
 // 注意事项: 用于命令行参数解析
 
-use clap::Parser;
+use crate::layering::ConfigOverrides;
+use crate::packager::{self, PackOptions, Passphrase, SignaturePolicy, UnpackOptions, signing};
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use std::path::Path;
+
+/// 打包压缩预设
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackPreset {
+    /// 开发构建：快速压缩
+    Dev,
+    /// 发布构建：最高压缩比
+    Release,
+}
 
 /// 命令行参数结构体
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
-    /// 安装配置文件路径
-    #[arg(short, long, default_value = "install.toml")]
-    pub config: String,
+    /// 安装配置文件路径，未指定时优先使用安装程序中嵌入的配置，其次为 install.toml
+    #[arg(short, long)]
+    pub config: Option<String>,
+    
+    /// 配置环境，在配置文件之上合并同目录的 <文件名>.<环境>.toml，例如 --env production 使用 install.production.toml
+    #[arg(long)]
+    pub env: Option<String>,
+    
+    /// 覆盖配置值（可重复指定，最后合并），例如 --set install_options.silent=true
+    #[arg(long, value_name = "KEY=VALUE")]
+    pub set: Vec<String>,
     
     /// 安装目录
     #[arg(short, long)]
@@ -24,10 +45,13 @@ pub struct Args {
     #[arg(short, long)]
     pub debug: bool,
     
-    /// 执行的命令: install, uninstall, repair, update
+    /// 执行的命令: install, uninstall, repair, update, pack, unpack, list, verify, convert, bundle, delta, keygen, config, validate
     #[arg(default_value = "install")]
     pub command: String,
     
+    /// 安装包文件（unpack、list、verify命令），默认为 payload.sspk；convert命令中为转换的输入文件
+    pub archive: Option<String>,
+    
     /// 仅检查更新，不执行更新
     #[arg(long)]
     pub check: bool,
@@ -39,4 +63,222 @@ pub struct Args {
     /// 强制更新，忽略版本检查
     #[arg(long)]
     pub force: bool,
+    
+    /// 打包的源目录（pack、bundle命令），delta命令中为新版本目录
+    #[arg(long, default_value = "building")]
+    pub source: String,
+    
+    /// 输出文件，pack命令默认为 payload.sspk，bundle命令默认为 seesea-setup，delta命令默认为 update.sspd；convert命令中按扩展名决定导入或导出的格式
+    #[arg(long)]
+    pub output: Option<String>,
+    
+    /// 旧版本目录，生成补丁包时与源目录比较（delta命令）
+    #[arg(long)]
+    pub base: Option<String>,
+    
+    /// 使用补丁包原地更新已安装的目录，而不是重新解压完整安装包（update命令）
+    #[arg(long)]
+    pub delta: Option<String>,
+    
+    /// 自解压安装程序使用的程序本体，默认为当前可执行文件（bundle命令）
+    #[arg(long)]
+    pub stub: Option<String>,
+    
+    /// 压缩预设: dev（快速）或 release（最高压缩比）
+    #[arg(long, value_enum)]
+    pub preset: Option<PackPreset>,
+    
+    /// zstd压缩级别，覆盖预设
+    #[arg(long, allow_negative_numbers = true)]
+    pub level: Option<i32>,
+    
+    /// zstd工作线程数，0表示单线程，覆盖预设
+    #[arg(long)]
+    pub threads: Option<u32>,
+    
+    /// 长距离匹配窗口（log2字节，10-27），覆盖预设
+    #[arg(long)]
+    pub long_window: Option<u32>,
+    
+    /// 不在zstd帧中写入校验和
+    #[arg(long)]
+    pub no_checksum: bool,
+    
+    /// 不合并内容相同的文件
+    #[arg(long)]
+    pub no_dedupe: bool,
+    
+    /// 对安装包签名的Ed25519密钥文件（pack、bundle命令）
+    #[arg(long)]
+    pub signing_key: Option<String>,
+    
+    /// 跳过匹配的路径（gitignore语法，可重复指定），在 .installerignore 之后生效
+    #[arg(long)]
+    pub exclude: Vec<String>,
+    
+    /// 重新包含匹配的路径（gitignore语法，可重复指定），覆盖排除规则
+    #[arg(long)]
+    pub include: Vec<String>,
+    
+    /// 不读取源目录中的 .installerignore
+    #[arg(long)]
+    pub no_ignore_file: bool,
+    
+    /// 可重现打包：条目按路径排序，统一修改时间（SOURCE_DATE_EPOCH）和权限，在单线程中压缩
+    #[arg(long)]
+    pub deterministic: bool,
+    
+    /// 把安装包拆分为不超过该大小的分卷（pack命令），可带单位 K/M/G（1024进制）或 KB/MB/GB（1000进制），例如 4GB
+    #[arg(long, value_parser = parse_size)]
+    pub volume_size: Option<u64>,
+    
+    /// 使用口令加密安装包（pack、bundle命令及convert导入），口令依次取自 --passphrase-file、SEESEA_PASSPHRASE 环境变量或终端输入
+    #[arg(long)]
+    pub encrypt: bool,
+    
+    /// 从文件读取加密口令，去掉末尾的换行符；读取加密的安装包时同样使用
+    #[arg(long)]
+    pub passphrase_file: Option<String>,
+    
+    /// 允许安装、解压和校验未签名的安装包；未指定 --public-key 时签名不经验证
+    #[arg(long)]
+    pub allow_unsigned: bool,
+    
+    /// 受信任的Ed25519公钥（十六进制，可重复指定），unpack、verify命令要求由其中之一做出的有效签名
+    #[arg(long)]
+    pub public_key: Vec<String>,
+    
+    /// 只解压指定的条目或目录（可重复指定，unpack命令）
+    #[arg(long)]
+    pub entry: Vec<String>,
+    
+    /// 以JSON格式输出（list命令）
+    #[arg(long)]
+    pub json: bool,
+}
+
+impl Args {
+    /// 安装配置文件路径，未指定时为 install.toml
+    pub fn config_path(&self) -> &str {
+        self.config.as_deref().unwrap_or("install.toml")
+    }
+    
+    /// 配置覆盖层: --env 指定的环境覆盖文件和 --set 覆盖值
+    pub fn config_overrides(&self) -> ConfigOverrides {
+        ConfigOverrides { environment: self.env.clone(), set: self.set.clone() }
+    }
+    
+    /// 安装包文件路径，未指定时为 payload.sspk
+    pub fn archive_path(&self) -> &str {
+        self.archive.as_deref().unwrap_or("payload.sspk")
+    }
+    
+    /// 根据 --public-key 和 --allow-unsigned 生成签名验证策略
+    ///
+    /// 未指定公钥时没有可信任的签名者，除非指定 --allow-unsigned，否则解压和校验都会失败。
+    pub fn signature_policy(&self) -> Result<SignaturePolicy> {
+        let keys = self.public_key.iter().map(|key| signing::parse_public_key(key)).collect::<Result<Vec<_>>>()?;
+        Ok(if self.allow_unsigned {
+            SignaturePolicy::AllowUnsigned(keys)
+        } else {
+            SignaturePolicy::Require(keys)
+        })
+    }
+    
+    /// 读取加密口令: 依次使用 --passphrase-file、SEESEA_PASSPHRASE 环境变量和终端输入，confirm 时要求输入两次
+    pub fn passphrase(&self, confirm: bool) -> Result<Passphrase> {
+        if let Some(path) = &self.passphrase_file {
+            return Passphrase::from_file(Path::new(path));
+        }
+        if let Some(passphrase) = Passphrase::from_env()? {
+            return Ok(passphrase);
+        }
+        
+        let prompt = |message: &str| {
+            rpassword::prompt_password(message).map_err(|e| {
+                anyhow::anyhow!(
+                    "Failed to read passphrase from the terminal ({e}); use --passphrase-file or {}",
+                    packager::encryption::PASSPHRASE_ENV
+                )
+            })
+        };
+        let passphrase = Passphrase::new(prompt("Passphrase: ")?)?;
+        if confirm && Passphrase::new(prompt("Confirm passphrase: ")?)? != passphrase {
+            anyhow::bail!("Passphrases do not match");
+        }
+        Ok(passphrase)
+    }
+    
+    /// 生成读取安装包的解压参数: 签名验证策略，安装包经过加密时还读取口令
+    pub fn unpack_options(&self, archive: &Path) -> Result<UnpackOptions> {
+        let passphrase = if packager::is_encrypted(archive)? { Some(self.passphrase(false)?) } else { None };
+        Ok(UnpackOptions { signature: self.signature_policy()?, passphrase, ..Default::default() })
+    }
+    
+    /// 根据预设和单独指定的参数生成打包压缩参数
+    pub fn pack_options(&self) -> Result<PackOptions> {
+        let mut options = match self.preset {
+            Some(PackPreset::Dev) => PackOptions::dev(),
+            Some(PackPreset::Release) => PackOptions::release(),
+            None => PackOptions::default(),
+        };
+        
+        if let Some(level) = self.level {
+            options.level = level;
+        }
+        if let Some(threads) = self.threads {
+            options.threads = threads;
+        }
+        if let Some(long_window) = self.long_window {
+            options.long_window = Some(long_window);
+        }
+        if self.no_checksum {
+            options.checksum = false;
+        }
+        if self.no_dedupe {
+            options.deduplicate = false;
+        }
+        if self.no_ignore_file {
+            options.use_ignore_file = false;
+        }
+        options.exclude.extend(self.exclude.iter().cloned());
+        options.include.extend(self.include.iter().cloned());
+        if self.deterministic {
+            options.deterministic = true;
+            options.source_date_epoch = packager::options::source_date_epoch()?;
+        }
+        if let Some(volume_size) = self.volume_size {
+            options.volume_size = Some(volume_size);
+        }
+        if let Some(path) = &self.signing_key {
+            options.signing_key = Some(signing::load_signing_key(Path::new(path))?);
+        }
+        if self.encrypt {
+            options.passphrase = Some(self.passphrase(true)?);
+        }
+        
+        options.validate()?;
+        Ok(options)
+    }
+}
+
+/// 解析带单位的大小: 无单位为字节，K/M/G/T 与 KiB/MiB/GiB/TiB 为1024进制，KB/MB/GB/TB 为1000进制
+pub fn parse_size(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number.parse().map_err(|_| format!("invalid size: {text:?}"))?;
+    let multiplier: u64 = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KIB" => 1 << 10,
+        "M" | "MIB" => 1 << 20,
+        "G" | "GIB" => 1 << 30,
+        "T" | "TIB" => 1 << 40,
+        "KB" => 1_000,
+        "MB" => 1_000_000,
+        "GB" => 1_000_000_000,
+        "TB" => 1_000_000_000_000,
+        _ => return Err(format!("invalid size unit in {text:?}: use K, M, G, T, KB, MB, GB or TB")),
+    };
+    number.checked_mul(multiplier).ok_or_else(|| format!("size is too large: {text:?}"))
 }
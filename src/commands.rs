@@ -0,0 +1,271 @@
+// SeeSea Self-Contained Installer - Commands Module
+// 模块名称: commands
+// 职责范围: 执行不需要安装器实例的安装包命令和配置命令
+// 已实现功能: pack、unpack、list（文本和JSON输出）、verify、convert、bundle、delta、keygen、config、validate命令，分层加载安装配置
+// 使用依赖: anyhow, log, serde_json, std::env, std::fs, std::io, cli, config, layering, packager, progress_bar, validation
+// 主要接口: run_packager_command, write_listing, load_installer_config
+// 注意事项: 命令结果写入调用方传入的输出流，pack和unpack的进度条绘制在标准错误输出；校验失败时返回错误，由main以非零退出码结束
+
+use crate::cli::Args;
+use crate::config;
+use crate::layering::{self, LayeredConfig};
+use crate::packager::{self, ArchiveEntry, CompressionMethod, EntryKind, ForeignFormat, PackOptions, UnpackOptions, checksum, signing};
+use crate::progress_bar::ProgressBar;
+use crate::validation;
+use anyhow::Result;
+use serde_json::json;
+use log::info;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// 执行安装包命令，不是安装包命令时返回None
+pub fn run_packager_command<W: Write>(args: &Args, out: &mut W) -> Option<Result<()>> {
+    let result = match args.command.as_str() {
+        "pack" => pack(args, out),
+        "unpack" => unpack(args, out),
+        "list" => list(args, out),
+        "verify" => verify(args, out),
+        "convert" => convert(args, out),
+        "bundle" => bundle(args, out),
+        "delta" => delta(args, out),
+        "keygen" => keygen(args, out),
+        "config" => show_config(args, out),
+        "validate" => validate(args, out),
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// 打包源目录
+fn pack<W: Write>(args: &Args, out: &mut W) -> Result<()> {
+    let bar = ProgressBar::new("Packing");
+    let options = PackOptions { progress: bar.handler(), ..args.pack_options()? };
+    let output = args.output.as_deref().unwrap_or("payload.sspk");
+    let summary = packager::pack_directory_with(Path::new(&args.source), Path::new(output), &options)?;
+    bar.finish();
+    for path in &summary.skipped {
+        writeln!(out, "Skipped: {path}")?;
+    }
+    for volume in &summary.volumes {
+        writeln!(out, "Volume: {}", volume.display())?;
+    }
+    writeln!(out, "Packaged {} entries from {} to {output} ({} skipped)", summary.entries, args.source, summary.skipped.len())?;
+    Ok(())
+}
+
+/// 解压安装包，指定 --entry 时只解压这些条目
+fn unpack<W: Write>(args: &Args, out: &mut W) -> Result<()> {
+    let archive = Path::new(args.archive_path());
+    let output = args.output.as_deref().unwrap_or(".");
+    let options = args.unpack_options(archive)?;
+    let bar = ProgressBar::new("Unpacking");
+    let options = UnpackOptions { progress: bar.handler(), ..options };
+    if args.entry.is_empty() {
+        packager::unpack_directory_with(archive, Path::new(output), &options)?;
+        bar.finish();
+        writeln!(out, "Unpacked {} to {output}", archive.display())?;
+    } else {
+        let paths: Vec<&str> = args.entry.iter().map(String::as_str).collect();
+        let count = packager::unpack_paths(archive, Path::new(output), &paths, &options)?;
+        bar.finish();
+        writeln!(out, "Unpacked {count} entries from {} to {output}", archive.display())?;
+    }
+    Ok(())
+}
+
+/// 列出安装包中的条目
+fn list<W: Write>(args: &Args, out: &mut W) -> Result<()> {
+    let archive = Path::new(args.archive_path());
+    let entries = packager::read_manifest_with(archive, &args.unpack_options(archive)?)?;
+    write_listing(&entries, args.json, out)
+}
+
+/// 以文本表格或JSON数组输出条目清单
+pub fn write_listing<W: Write>(entries: &[ArchiveEntry], as_json: bool, out: &mut W) -> Result<()> {
+    if as_json {
+        let listing: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "path": entry.path,
+                    "kind": kind_name(entry.kind),
+                    "method": method_name(entry.method),
+                    "mode": entry.mode,
+                    "mtime": entry.mtime,
+                    "size": entry.size,
+                    "compressed_size": entry.compressed_size,
+                    "offset": entry.offset,
+                    "sha256": checksum::to_hex(&entry.digest),
+                })
+            })
+            .collect();
+        serde_json::to_writer_pretty(&mut *out, &listing)?;
+        writeln!(out)?;
+        return Ok(());
+    }
+
+    writeln!(out, "{:<10} {:>12} {:>12} {:<6} Path", "Mode", "Size", "Compressed", "Method")?;
+    for entry in entries {
+        let kind = match entry.kind {
+            EntryKind::File => '-',
+            EntryKind::Directory => 'd',
+            EntryKind::Symlink => 'l',
+        };
+        let (path, method) = match entry.kind {
+            EntryKind::Directory => (format!("{}/", entry.path), "-"),
+            _ => (entry.path.clone(), method_name(entry.method)),
+        };
+        writeln!(
+            out,
+            "{kind}{:<9o} {:>12} {:>12} {method:<6} {path}",
+            entry.mode & 0o7777,
+            entry.size,
+            entry.compressed_size
+        )?;
+    }
+    let total: u64 = entries.iter().map(|entry| entry.size).sum();
+    writeln!(out, "{} entries, {total} bytes", entries.len())?;
+    Ok(())
+}
+
+/// 完整校验安装包，失败时返回错误
+fn verify<W: Write>(args: &Args, out: &mut W) -> Result<()> {
+    let archive = args.archive_path();
+    let options = args.unpack_options(Path::new(archive))?;
+    let report = packager::verify_archive_with(Path::new(archive), &options)
+        .map_err(|e| anyhow::anyhow!("Verification of {archive} failed: {e}"))?;
+
+    match report.signer {
+        Some(key) if !args.public_key.is_empty() => writeln!(out, "Signature: valid, signed by {}", checksum::to_hex(&key))?,
+        Some(key) => writeln!(out, "Signature: present but not checked (signed by {}, no --public-key given)", checksum::to_hex(&key))?,
+        None => writeln!(out, "Signature: none")?,
+    }
+    writeln!(out, "OK: {archive} ({} entries verified)", report.entries)?;
+    Ok(())
+}
+
+/// 在原生安装包与zip、tar.zst之间转换：输入为zip或tar.zst时导入，否则导出为输出文件的格式
+fn convert<W: Write>(args: &Args, out: &mut W) -> Result<()> {
+    let input = Path::new(args.archive_path());
+    let Some(output) = args.output.as_deref() else {
+        anyhow::bail!("The convert command requires --output <file>");
+    };
+    let options = args.pack_options()?;
+    if ForeignFormat::from_path(input).is_some() {
+        let summary = packager::import_archive(input, Path::new(output), &options)?;
+        writeln!(out, "Imported {} entries from {} to {output}", summary.entries, input.display())?;
+    } else {
+        let count = packager::export_archive(input, Path::new(output), &options, &args.unpack_options(input)?)?;
+        writeln!(out, "Exported {count} entries from {} to {output}", input.display())?;
+    }
+    Ok(())
+}
+
+/// 生成自解压安装程序：程序本体 + 安装包 + install.toml
+fn bundle<W: Write>(args: &Args, out: &mut W) -> Result<()> {
+    let options = args.pack_options()?;
+    let stub = match &args.stub {
+        Some(stub) => PathBuf::from(stub),
+        None => env::current_exe()?,
+    };
+    let default_output = format!("seesea-setup{}", env::consts::EXE_SUFFIX);
+    let output = args.output.as_deref().unwrap_or(&default_output);
+    
+    // 嵌入合并 include、环境覆盖文件和 --set 之后的配置
+    let config_file = Path::new(args.config_path());
+    let layered = layering::load_layered_config(config_file, &args.config_overrides())?;
+    let output_path = Path::new(output);
+    let file_name = output_path.file_name().ok_or_else(|| anyhow::anyhow!("Invalid output file: {output}"))?;
+    let merged = output_path.with_file_name(format!(".{}.toml-partial", file_name.to_string_lossy()));
+    let result = fs::write(&merged, layered.to_toml()?)
+        .map_err(anyhow::Error::from)
+        .and_then(|_| packager::bundle_installer(&stub, Path::new(&args.source), Some(&merged), output_path, &options));
+    let _ = fs::remove_file(&merged);
+    result?;
+    writeln!(out, "Bundled {} and {} into {output}", args.source, config_file.display())?;
+    Ok(())
+}
+
+/// 加载安装配置：命令行指定的配置文件优先，其次为安装程序中嵌入的配置，最后查找 install.toml；
+/// 在其上合并 --env 环境覆盖文件和 --set 覆盖值
+pub fn load_installer_config(args: &Args) -> Result<LayeredConfig> {
+    let overrides = args.config_overrides();
+    if args.config.is_none()
+        && let Some(contents) = packager::embed::read_embedded_config(&env::current_exe()?)?
+    {
+        info!("Using config embedded in the installer");
+        return layering::parse_layered_config(&contents, "<embedded config>", &overrides);
+    }
+    layering::load_layered_config(&config::find_config_file(args.config_path())?, &overrides)
+}
+
+/// 输出合并后的每个配置值及其来源
+fn show_config<W: Write>(args: &Args, out: &mut W) -> Result<()> {
+    let layered = load_installer_config(args)?;
+    for (key, source) in &layered.sources {
+        if let Some(value) = layered.value(key) {
+            writeln!(out, "{key} = {value}  # {source}")?;
+        }
+    }
+    Ok(())
+}
+
+/// 检查配置文件，输出全部错误和警告及其位置，有错误时返回错误
+fn validate<W: Write>(args: &Args, out: &mut W) -> Result<()> {
+    let config_file = config::find_config_file(args.config_path())?;
+    let layers = layering::load_layers(&config_file, &args.config_overrides())?;
+    // 组件文件相对于配置文件所在目录
+    let base_dir = config_file.parent().unwrap_or(Path::new(""));
+    let report = validation::validate_layers(&layers, Some(base_dir));
+    
+    for diagnostic in &report.diagnostics {
+        writeln!(out, "{}", diagnostic.render())?;
+    }
+    let (errors, warnings) = (report.error_count(), report.warning_count());
+    writeln!(out, "{}: {errors} error(s), {warnings} warning(s)", config_file.display())?;
+    if report.has_errors() {
+        anyhow::bail!("Config validation failed with {errors} error(s)");
+    }
+    Ok(())
+}
+
+/// 生成从旧版本目录升级到源目录的补丁包
+fn delta<W: Write>(args: &Args, out: &mut W) -> Result<()> {
+    let Some(base) = &args.base else {
+        anyhow::bail!("The delta command requires --base <old version directory>");
+    };
+    let options = args.pack_options()?;
+    let output = args.output.as_deref().unwrap_or("update.sspd");
+    packager::create_delta(Path::new(base), Path::new(&args.source), Path::new(output), &options)?;
+    writeln!(out, "Created delta from {base} to {} as {output}", args.source)?;
+    Ok(())
+}
+
+/// 生成安装包签名密钥
+fn keygen<W: Write>(args: &Args, out: &mut W) -> Result<()> {
+    let output = args.output.as_deref().unwrap_or("signing.key");
+    let key = signing::generate_signing_key()?;
+    signing::save_signing_key(Path::new(output), &key)?;
+    writeln!(out, "Signing key written to {output}")?;
+    writeln!(out, "Public key: {}", signing::public_key_hex(&key.verifying_key()))?;
+    Ok(())
+}
+
+/// 条目类型名称
+fn kind_name(kind: EntryKind) -> &'static str {
+    match kind {
+        EntryKind::File => "file",
+        EntryKind::Directory => "directory",
+        EntryKind::Symlink => "symlink",
+    }
+}
+
+/// 压缩方式名称
+fn method_name(method: CompressionMethod) -> &'static str {
+    match method {
+        CompressionMethod::Zstd => "zstd",
+        CompressionMethod::Stored => "stored",
+    }
+}
@@ -6,16 +6,17 @@
 // 2. 实现配置文件加载
 // 3. 实现配置验证
 // 4. 实现默认配置生成
-// 已实现功能: 配置结构定义、配置文件加载
-// 使用依赖: toml, serde, anyhow, std::fs
-// 主要接口: load_config, Config struct
-// 注意事项: 配置文件使用TOML格式，支持平台特定配置
+// 已实现功能: 配置结构定义、配置文件加载（支持 include，见 layering 模块）、平台特定安装选项合并、配置验证（一次报告全部错误，见 validation 模块）
+// 使用依赖: layering, validation, toml, serde, serde_ignored, anyhow, log, std::path
+// 主要接口: load_config, find_config_file, parse_config, config_from_table, Config struct, Config::platform_install_options
+// 注意事项: 配置文件使用TOML格式，支持平台特定配置；只有项目名称、版本和默认安装目录是必填项，其余选项都有默认值
 
+use crate::layering::{ConfigOverrides, load_layered_config, merge_tables, parse_layered_config};
+use crate::validation::{CheckMode, Diagnostic, Severity};
 use serde::{Deserialize, Serialize};
-use std::fs::File;
-use std::io::Read;
+use std::path::{Path, PathBuf};
 use anyhow::Result;
-use log::debug;
+use log::{debug, warn};
 
 /// 项目信息配置
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -40,14 +41,19 @@ pub struct InstallOptions {
     /// 默认安装目录
     pub default_dir: String,
     /// 是否创建桌面快捷方式
+    #[serde(default)]
     pub create_desktop_shortcut: bool,
     /// 是否创建开始菜单快捷方式
+    #[serde(default)]
     pub create_start_menu_shortcut: bool,
     /// 是否添加到PATH环境变量
+    #[serde(default)]
     pub add_to_path: bool,
-    /// 是否创建卸载程序
+    /// 是否创建卸载程序，默认创建
+    #[serde(default = "default_true")]
     pub create_uninstaller: bool,
     /// 是否启用静默安装
+    #[serde(default)]
     pub silent: bool,
     /// 默认安装语言
     pub language: Option<String>,
@@ -64,21 +70,26 @@ pub struct InstallOptions {
     /// 后卸载脚本
     pub post_uninstall_script: Option<String>,
     /// 是否创建系统服务
+    #[serde(default)]
     pub create_service: bool,
     /// 服务名称
     pub service_name: Option<String>,
     /// 服务描述
     pub service_description: Option<String>,
     /// 是否自动检查更新
+    #[serde(default)]
     pub auto_check_updates: bool,
     /// 更新通道（stable, beta, alpha）
     pub update_channel: Option<String>,
     /// 更新时需要保留的配置文件列表
     pub preserve_configs: Option<Vec<String>>,
-    /// 是否启用自动备份
+    /// 是否启用自动备份，默认启用
+    #[serde(default = "default_true")]
     pub backup_enabled: bool,
     /// 备份保留数量
     pub backup_retention: Option<u32>,
+    /// 验证安装包签名的Ed25519公钥（十六进制）
+    pub payload_public_key: Option<String>,
 }
 
 /// 自定义命令配置
@@ -91,10 +102,12 @@ pub struct CommandConfig {
     /// 命令执行的程序路径
     pub program: String,
     /// 命令参数
+    #[serde(default)]
     pub args: Vec<String>,
     /// 工作目录
     pub working_dir: Option<String>,
     /// 是否在后台执行
+    #[serde(default)]
     pub background: bool,
     /// 环境变量
     pub env: Option<Vec<(String, String)>>,
@@ -111,6 +124,7 @@ pub struct CommandConfig {
     /// 成功退出码
     pub success_codes: Option<Vec<i32>>,
     /// 是否忽略错误
+    #[serde(default)]
     pub ignore_errors: bool,
 }
 
@@ -121,7 +135,8 @@ pub struct DependencyConfig {
     pub name: String,
     /// 依赖版本
     pub version: String,
-    /// 依赖类型: runtime, development, optional
+    /// 依赖类型: runtime, development, optional，默认为 runtime
+    #[serde(default = "default_dependency_kind")]
     pub kind: String,
     /// 安装命令
     pub install_command: Option<String>,
@@ -134,14 +149,24 @@ pub struct DependencyConfig {
     /// 依赖的组件
     pub components: Option<Vec<String>>,
     /// 是否可选
+    #[serde(default)]
     pub optional: bool,
     /// 依赖的其他依赖
     pub depends_on: Option<Vec<String>>,
 }
 
 /// 平台特定配置
+///
+/// `[platform.linux]`、`[platform.macos]`、`[platform.windows]` 表可以覆盖 `[install_options]` 中的任意选项，
+/// 旧版的 `linux_default_dir` 等平铺键仍然有效，两者同时存在时以平台表为准
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PlatformConfig {
+    /// Linux平台覆盖的安装选项
+    pub linux: Option<toml::Table>,
+    /// macOS平台覆盖的安装选项
+    pub macos: Option<toml::Table>,
+    /// Windows平台覆盖的安装选项
+    pub windows: Option<toml::Table>,
     /// Windows平台默认安装目录
     pub windows_default_dir: Option<String>,
     /// Linux平台默认安装目录
@@ -165,7 +190,8 @@ pub struct ComponentConfig {
     pub description: Option<String>,
     /// 组件版本
     pub version: Option<String>,
-    /// 组件是否默认安装
+    /// 组件是否默认安装，默认安装
+    #[serde(default = "default_true")]
     pub default: bool,
     /// 组件的文件列表
     pub files: Option<Vec<String>>,
@@ -196,6 +222,7 @@ pub struct Config {
     /// 平台特定配置
     pub platform: Option<PlatformConfig>,
     /// 自定义命令列表
+    #[serde(default)]
     pub commands: Vec<CommandConfig>,
     /// 依赖列表
     pub dependencies: Option<Vec<DependencyConfig>>,
@@ -209,8 +236,57 @@ pub struct Config {
     pub installer_name: Option<String>,
 }
 
-/// 加载配置文件
+impl Config {
+    /// 合并平台特定配置后的安装选项，platform 为 linux、macos 或 windows
+    pub fn platform_install_options(&self, platform: &str) -> Result<InstallOptions> {
+        let Some(platform_config) = &self.platform else {
+            return Ok(self.install_options.clone());
+        };
+        let (overrides, legacy_default_dir) = match platform {
+            "linux" => (&platform_config.linux, &platform_config.linux_default_dir),
+            "macos" => (&platform_config.macos, &platform_config.macos_default_dir),
+            "windows" => (&platform_config.windows, &platform_config.windows_default_dir),
+            _ => anyhow::bail!("Unknown platform: {platform}"),
+        };
+        
+        let mut merged = toml::Table::try_from(&self.install_options)?;
+        if let Some(default_dir) = legacy_default_dir {
+            debug!("Using {platform} specific default_dir: {default_dir}");
+            merged.insert("default_dir".to_string(), toml::Value::String(default_dir.clone()));
+        }
+        if let Some(overrides) = overrides {
+            debug!("Merging [platform.{platform}] over global install options");
+            merge_tables(&mut merged, overrides, "", &mut |_| {});
+        }
+        
+        // 只允许覆盖已有的安装选项，避免拼写错误被静默忽略
+        let mut unknown = Vec::new();
+        let install_options = serde_ignored::deserialize(toml::Value::Table(merged), |path| unknown.push(path.to_string()))
+            .map_err(|e| anyhow::anyhow!("Invalid install options in [platform.{platform}]: {e}"))?;
+        if !unknown.is_empty() {
+            anyhow::bail!("Unknown install options in [platform.{platform}]: {}", unknown.join(", "));
+        }
+        Ok(install_options)
+    }
+}
+
+/// 未在配置文件中指定时为 true 的选项
+fn default_true() -> bool {
+    true
+}
+
+/// 未在配置文件中指定时的依赖类型
+fn default_dependency_kind() -> String {
+    "runtime".to_string()
+}
+
+/// 加载配置文件，先合并 include 指令引用的文件
 pub fn load_config(config_path: &str) -> Result<Config> {
+    Ok(load_layered_config(&find_config_file(config_path)?, &ConfigOverrides::default())?.config)
+}
+
+/// 查找配置文件
+pub fn find_config_file(config_path: &str) -> Result<PathBuf> {
     debug!("Loading config from: {config_path}");
     
     // 尝试多种路径查找配置文件
@@ -220,10 +296,10 @@ pub fn load_config(config_path: &str) -> Result<Config> {
     paths_to_try.push(format!("../../{config_path}"));
     
     // 添加当前可执行文件所在目录的查找
-    if let Ok(exe_path) = std::env::current_exe() {
-        if let Some(exe_dir) = exe_path.parent() {
-            paths_to_try.push(exe_dir.join(config_path).to_string_lossy().to_string());
-        }
+    if let Ok(exe_path) = std::env::current_exe()
+        && let Some(exe_dir) = exe_path.parent()
+    {
+        paths_to_try.push(exe_dir.join(config_path).to_string_lossy().to_string());
     }
     
     // 添加系统安装目录的查找
@@ -234,129 +310,63 @@ pub fn load_config(config_path: &str) -> Result<Config> {
     paths_to_try.push("C:\\Program Files\\SeeSea-Installer\\install.toml".to_string());
     paths_to_try.push("/Applications/SeeSea-Installer/install.toml".to_string());
     
-    let mut file = None;
-    let mut used_path = String::new();
-    
     for path in &paths_to_try {
         debug!("Trying config path: {path}");
-        if let Ok(f) = File::open(path) {
-            file = Some(f);
-            used_path = path.clone();
-            break;
+        if Path::new(path).is_file() {
+            debug!("Found config file at: {path}");
+            return Ok(PathBuf::from(path));
         }
     }
     
-    let mut file = file.ok_or_else(|| anyhow::anyhow!("Could not find config file at any of the tried paths: {paths_to_try:?}"))?;
-    
-    debug!("Found config file at: {used_path}");
-    
-    // 读取文件内容
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
-    
-    // 解析TOML配置
-    let config: Config = toml::from_str(&contents)?;
+    anyhow::bail!("Could not find config file at any of the tried paths: {paths_to_try:?}")
+}
+
+/// 从TOML文本解析并验证配置，用于嵌入在安装程序中的配置
+pub fn parse_config(contents: &str) -> Result<Config> {
+    Ok(parse_layered_config(contents, "<config text>", &ConfigOverrides::default())?.config)
+}
+
+/// 将合并后的TOML表转换为配置并验证
+pub fn config_from_table(table: toml::Table) -> Result<Config> {
+    let (config, mut diagnostics) = crate::validation::deserialize_config(table, CheckMode::Load);
+    let Some(config) = config else {
+        anyhow::bail!("{}", join_errors(&diagnostics));
+    };
     
     debug!("Config loaded successfully: {config:?}");
     
-    // 验证配置
-    validate_config(&config)?;
+    // 验证配置，组件文件相对于打包时的工作目录，加载时不检查；新加入的依赖关系、平台名称和变量引用检查只记录警告
+    diagnostics.extend(crate::validation::check_config(&config, None, CheckMode::Load));
+    validate_config(&diagnostics)?;
     
     Ok(config)
 }
 
-/// 验证配置
-fn validate_config(config: &Config) -> Result<()> {
-    // 验证项目名称和版本
-    if config.project.name.is_empty() {
-        anyhow::bail!("Project name cannot be empty");
-    }
-    
-    if config.project.version.is_empty() {
-        anyhow::bail!("Project version cannot be empty");
-    }
-    
-    // 验证安装选项
-    if config.install_options.default_dir.is_empty() {
-        anyhow::bail!("Default install directory cannot be empty");
-    }
-    
-    // 验证命令配置
-    for (index, command) in config.commands.iter().enumerate() {
-        if command.name.is_empty() {
-            anyhow::bail!("Command name cannot be empty at index {index}");
-        }
-        
-        if command.program.is_empty() {
-            anyhow::bail!("Command program cannot be empty for command '{0}'", command.name);
-        }
-        
-        // 验证命令超时时间
-        if let Some(timeout) = command.timeout {
-            if timeout == 0 {
-                anyhow::bail!("Command timeout cannot be zero for command '{0}'", command.name);
-            }
-        }
-        
-        // 验证命令重试次数
-        if let Some(retries) = command.retries {
-            if retries > 10 {
-                anyhow::bail!("Command retries cannot exceed 10 for command '{0}'", command.name);
-            }
-        }
-    }
-    
-    // 验证组件配置
-    if let Some(components) = &config.components {
-        // 检查组件名称唯一性
-        let mut component_names = std::collections::HashSet::new();
-        for component in components {
-            if !component_names.insert(&component.name) {
-                anyhow::bail!("Duplicate component name: {0}", component.name);
-            }
-            
-            // 检查组件依赖是否存在
-            if let Some(depends_on) = &component.depends_on {
-                for dep in depends_on {
-                    let dep_exists = components.iter().any(|c| c.name == *dep);
-                    if !dep_exists {
-                        anyhow::bail!("Component {0} depends on non-existent component: {1}", component.name, dep);
-                    }
-                }
-            }
+/// 报告验证结果: 警告只记录日志，有错误时返回全部错误；问题的位置见 validate 命令
+fn validate_config(diagnostics: &[Diagnostic]) -> Result<()> {
+    for diagnostic in diagnostics {
+        if diagnostic.severity == Severity::Warning {
+            warn!("Config: {diagnostic}");
         }
     }
     
-    // 验证插件配置
-    if let Some(plugins) = &config.plugins {
-        // 检查插件名称唯一性
-        let mut plugin_names = std::collections::HashSet::new();
-        for plugin in plugins {
-            if !plugin_names.insert(&plugin.name) {
-                anyhow::bail!("Duplicate plugin name: {0}", plugin.name);
-            }
-            
-            // 检查插件路径是否存在
-            if !std::path::Path::new(&plugin.path).exists() {
-                anyhow::bail!("Plugin path does not exist: {0}", plugin.path);
-            }
-        }
-    }
-    
-    // 验证依赖配置
-    if let Some(dependencies) = &config.dependencies {
-        // 检查依赖名称唯一性
-        let mut dependency_names = std::collections::HashSet::new();
-        for dependency in dependencies {
-            if !dependency_names.insert(&dependency.name) {
-                anyhow::bail!("Duplicate dependency name: {0}", dependency.name);
-            }
-        }
+    if diagnostics.iter().any(|d| d.severity == Severity::Error) {
+        anyhow::bail!("{}", join_errors(diagnostics));
     }
     
     Ok(())
 }
 
+/// 将错误合并为一条消息，每行一个
+fn join_errors(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// 生成默认配置
 #[allow(dead_code)]
 pub fn generate_default_config() -> Config {
@@ -391,6 +401,7 @@ pub fn generate_default_config() -> Config {
             preserve_configs: None,
             backup_enabled: true,
             backup_retention: Some(5),
+            payload_public_key: None,
         },
         platform: None,
         commands: Vec::new(),
@@ -8,14 +8,18 @@
 // 4. 实现修复逻辑
 // 5. 实现平台特定功能调用
 // 已实现功能: Installer结构体定义、基础安装流程
-// 使用依赖: config, platform, utils, anyhow, log, std::fs, std::path
+// 使用依赖: config, interpolation, platform, utils, packager, progress_bar, anyhow, log, std::fs, std::path
 // 主要接口: Installer::new, install, uninstall, repair
 // 注意事项: 支持Windows、Linux、macOS平台，使用平台特定实现
 
 use crate::config::{Config, InstallOptions, ComponentConfig};
+use crate::interpolation::Variables;
 use crate::platform::PlatformImpl;
 use crate::utils::{create_directory, execute_command, copy_files};
 use crate::version::{Version, get_current_version, save_version, check_update, get_latest_version_from_github};
+use crate::packager::delta::DeltaOp;
+use crate::packager::{self, EntryKind, Passphrase, SignaturePolicy, UnpackOptions, embed, signing};
+use crate::progress_bar::ProgressBar;
 use crate::Args;
 use anyhow::Result;
 use log::{info, debug, warn};
@@ -24,6 +28,9 @@ use std::path::{Path, PathBuf};
 use std::env;
 use std::collections::HashMap;
 
+/// 构建时嵌入的安装包签名公钥（十六进制）
+const BUILTIN_PUBLIC_KEY: Option<&str> = option_env!("SEESEA_PAYLOAD_PUBLIC_KEY");
+
 /// 组件状态
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
@@ -65,6 +72,8 @@ pub struct Installer {
     pub created_shortcuts: Vec<PathBuf>,
     /// 是否已创建卸载程序，用于回滚
     pub created_uninstaller: bool,
+    /// 嵌入安装包的解密口令，第一次需要时读取，之后不再重复询问
+    pub passphrase: Option<Passphrase>,
 }
 
 
@@ -74,18 +83,24 @@ impl Installer {
     pub fn new(config: Config, args: &Args) -> Result<Self> {
         debug!("Creating installer instance");
         
-        // 确定安装目录
-        let install_dir = match &args.install_dir {
-            Some(dir) => PathBuf::from(dir),
-            None => PathBuf::from(&config.install_options.default_dir),
-        };
-        
         // 获取平台特定实现
         let platform = PlatformImpl::new()?;
         
         // 获取平台特定安装选项
         let install_options = platform.get_install_options(&config)?;
         
+        // 确定安装目录，未指定时使用合并平台配置后的默认目录
+        let install_dir = match &args.install_dir {
+            Some(dir) => PathBuf::from(dir),
+            None => PathBuf::from(Variables::new(&config, None).expand(&install_options.default_dir)?),
+        };
+        
+        // 安装目录确定后展开当前平台安装选项和命令中的变量，其他平台的覆盖表不展开
+        let variables = Variables::new(&config, Some(&install_dir));
+        let install_options = variables.expand_install_options(&install_options)?;
+        let mut config = config;
+        config.commands = variables.expand_commands(&config.commands)?;
+        
         // 创建临时目录
         let temp_dir = std::env::temp_dir().join("seesea-installer");
         create_directory(&temp_dir)?;
@@ -126,6 +141,7 @@ impl Installer {
             installed_components,
             created_shortcuts,
             created_uninstaller,
+            passphrase: None,
         })
     }
     
@@ -185,6 +201,9 @@ impl Installer {
     
     /// 内部安装方法，包含实际安装逻辑
     fn install_internal(&mut self) -> Result<()> {
+        // 0. 验证安装包签名
+        self.verify_payload()?;
+        
         // 1. 执行预安装脚本
         if let Some(pre_script) = &self.install_options.pre_install_script {
             info!("Running pre-install script");
@@ -270,20 +289,20 @@ impl Installer {
         // 3. 删除已安装的文件
         info!("Rolling back installed files");
         for file_path in &self.installed_files {
-            if file_path.exists() {
-                if let Err(e) = std::fs::remove_file(file_path) {
-                    warn!("Failed to remove file: {file_path:?}, error: {e:?}");
-                }
+            if file_path.exists()
+                && let Err(e) = std::fs::remove_file(file_path)
+            {
+                warn!("Failed to remove file: {file_path:?}, error: {e:?}");
             }
         }
         self.installed_files.clear();
         
         // 4. 删除安装目录
         info!("Rolling back install directory");
-        if self.install_dir.exists() {
-            if let Err(e) = std::fs::remove_dir_all(&self.install_dir) {
-                warn!("Failed to remove install directory: {install_dir:?}, error: {e:?}", install_dir = self.install_dir);
-            }
+        if self.install_dir.exists()
+            && let Err(e) = std::fs::remove_dir_all(&self.install_dir)
+        {
+            warn!("Failed to remove install directory: {install_dir:?}, error: {e:?}", install_dir = self.install_dir);
         }
         
         info!("Rollback completed");
@@ -524,6 +543,11 @@ impl Installer {
         info!("Starting internal update process");
         debug!("New version: {new_version:?}");
         
+        // 0. 验证安装包签名；使用补丁包更新时在应用补丁前验证补丁包
+        if self.args.delta.is_none() {
+            self.verify_payload()?;
+        }
+        
         // 1. 执行预安装脚本
         if let Some(pre_script) = &self.install_options.pre_install_script {
             info!("Running pre-install script");
@@ -535,20 +559,25 @@ impl Installer {
         self.platform.check_system_requirements(&self.config)?;
         
         // 3. 安装依赖
-        if let Some(deps) = &self.config.dependencies {
-            if !deps.is_empty() {
-                info!("Installing dependencies");
-                self.install_dependencies()?;
-            }
+        if let Some(deps) = &self.config.dependencies
+            && !deps.is_empty()
+        {
+            info!("Installing dependencies");
+            self.install_dependencies()?;
         }
         
         // 4. 安装组件
         info!("Installing components");
         self.install_components()?;
         
-        // 5. 复制安装文件
-        info!("Copying installation files");
-        self.copy_install_files()?;
+        // 5. 复制安装文件，指定补丁包时只应用变化的部分
+        if let Some(delta) = self.args.delta.clone() {
+            info!("Applying delta {delta}");
+            self.apply_delta_files(Path::new(&delta))?;
+        } else {
+            info!("Copying installation files");
+            self.copy_install_files()?;
+        }
         
         // 6. 更新快捷方式
         if self.install_options.create_desktop_shortcut {
@@ -723,9 +752,12 @@ impl Installer {
             anyhow::bail!("Install directory does not exist: {install_dir:?}", install_dir = self.install_dir);
         }
         
-        // 2. 重新复制安装文件
-        info!("Re-copying installation files");
-        self.copy_install_files()?;
+        // 验证安装包签名
+        self.verify_payload()?;
+        
+        // 2. 恢复缺失或损坏的安装文件
+        info!("Restoring damaged installation files");
+        self.repair_install_files()?;
         
         // 3. 重新创建快捷方式
         info!("Re-creating shortcuts");
@@ -747,68 +779,96 @@ impl Installer {
         Ok(())
     }
     
-    /// 复制安装文件
+    /// 安装包签名验证策略
+    ///
+    /// 受信任的公钥来自构建时嵌入安装程序的公钥（SEESEA_PAYLOAD_PUBLIC_KEY）和配置中固定的公钥；
+    /// 除非指定 --allow-unsigned，否则拒绝未签名的安装包。
+    fn signature_policy(&self) -> Result<SignaturePolicy> {
+        let mut trusted = Vec::new();
+        if let Some(key) = BUILTIN_PUBLIC_KEY {
+            trusted.push(signing::parse_public_key(key)?);
+        }
+        if let Some(key) = &self.install_options.payload_public_key {
+            trusted.push(signing::parse_public_key(key)?);
+        }
+        
+        Ok(if self.args.allow_unsigned {
+            SignaturePolicy::AllowUnsigned(trusted)
+        } else {
+            SignaturePolicy::Require(trusted)
+        })
+    }
+    
+    /// 读取嵌入安装包的解压参数: 签名验证策略，安装包经过加密时还读取口令
+    fn payload_options(&mut self, exe_path: &Path) -> Result<UnpackOptions> {
+        if self.passphrase.is_none() && packager::is_encrypted(exe_path)? {
+            info!("Payload is encrypted");
+            self.passphrase = Some(self.args.passphrase(false)?);
+        }
+        Ok(UnpackOptions { signature: self.signature_policy()?, passphrase: self.passphrase.clone(), ..Default::default() })
+    }
+    
+    /// 在解压任何文件或执行任何命令之前验证嵌入安装包的签名，加密的安装包同时验证口令
+    fn verify_payload(&mut self) -> Result<()> {
+        let exe_path = env::current_exe()?;
+        if embed::find_embedded(&exe_path)?.is_none() {
+            anyhow::bail!("No embedded payload found in {exe_path:?}; build the installer with the bundle command");
+        }
+        
+        info!("Verifying payload signature");
+        let options = self.payload_options(&exe_path)?;
+        packager::verify_signature_with(&exe_path, &options)
+    }
+    
+    /// 从安装程序自身嵌入的安装包中解压安装文件
     fn copy_install_files(&mut self) -> Result<()> {
-        // 获取当前可执行文件目录
         let exe_path = env::current_exe()?;
-        let exe_dir = exe_path.parent().ok_or_else(|| anyhow::anyhow!("Failed to get executable directory"))?;
-        
-        // 尝试多种路径查找building目录
-        let mut building_paths = Vec::new();
-        
-        // 当前可执行文件所在目录的building子目录
-        building_paths.push(exe_dir.join("building"));
-        
-        // 当前目录
-        building_paths.push(PathBuf::from("building"));
-        
-        // 上级目录
-        building_paths.push(PathBuf::from("../building"));
-        building_paths.push(PathBuf::from("../../building"));
-        
-        // 系统安装目录
-        building_paths.push(PathBuf::from("/opt/seesea-installer/building"));
-        building_paths.push(PathBuf::from("C:\\Program Files\\SeeSea-Installer\\building"));
-        building_paths.push(PathBuf::from("/Applications/SeeSea-Installer/building"));
-        
-        // Linux deb包特定目录结构：building在lib/seesea-installer目录下
-        building_paths.push(exe_dir.join("../lib/seesea-installer/building"));
-        building_paths.push(PathBuf::from("/usr/lib/seesea-installer/building"));
-        building_paths.push(PathBuf::from("/lib/seesea-installer/building"));
-        // 添加更多可能的路径
-        building_paths.push(PathBuf::from("/usr/local/lib/seesea-installer/building"));
-        building_paths.push(PathBuf::from("/opt/seesea-installer/building"));
-        
-        // 查找存在的building目录
-        let mut found_building_dir = None;
-        for path in &building_paths {
-            if path.exists() && path.is_dir() {
-                found_building_dir = Some(path);
-                break;
-            }
+        if embed::find_embedded(&exe_path)?.is_none() {
+            anyhow::bail!("No embedded payload found in {exe_path:?}; build the installer with the bundle command");
         }
         
-        if let Some(building_dir) = found_building_dir {
-            debug!("Copying files from {building_dir:?} to {install_dir:?}", install_dir = self.install_dir);
-            
-            // 遍历building目录下的所有文件
-            for entry in std::fs::read_dir(building_dir)? {
-                let entry = entry?;
-                let src_path = entry.path();
-                if src_path.is_file() {
-                    let dest_path = self.install_dir.join(src_path.file_name().unwrap());
-                    
-                    // 复制文件
-                    std::fs::copy(&src_path, &dest_path)?;
-                    
-                    // 添加到已安装文件列表
-                    self.installed_files.push(dest_path.clone());
-                    debug!("Copied file: {src_path:?} -> {dest_path:?}");
-                }
+        debug!("Extracting embedded payload from {exe_path:?} to {install_dir:?}", install_dir = self.install_dir);
+        let options = self.payload_options(&exe_path)?;
+        let manifest = packager::read_manifest_with(&exe_path, &options)?;
+        let bar = ProgressBar::new("Extracting");
+        let options = UnpackOptions { progress: bar.handler(), ..options };
+        packager::unpack_directory_with(&exe_path, &self.install_dir, &options)?;
+        bar.finish();
+        
+        // 添加到已安装文件列表
+        for entry in manifest.iter().filter(|entry| entry.kind != EntryKind::Directory) {
+            self.installed_files.push(self.install_dir.join(&entry.path));
+        }
+        
+        Ok(())
+    }
+    
+    /// 将补丁包原地应用到已安装的目录
+    fn apply_delta_files(&mut self, delta_file: &Path) -> Result<()> {
+        packager::apply_delta(delta_file, &self.install_dir, &self.signature_policy()?)?;
+        
+        // 添加到已安装文件列表
+        let delta = packager::delta::DeltaArchive::open(delta_file)?;
+        for entry in delta.entries().iter().filter(|entry| entry.op != DeltaOp::Delete) {
+            if entry.entry.kind != EntryKind::Directory {
+                self.installed_files.push(self.install_dir.join(&entry.entry.path));
             }
-        } else {
-            warn!("Building directory not found at any of the tried paths: {building_paths:?}");
-            anyhow::bail!("Building directory not found");
+        }
+        
+        Ok(())
+    }
+    
+    /// 对照安装程序自身嵌入的安装包，只恢复缺失或损坏的文件
+    fn repair_install_files(&mut self) -> Result<()> {
+        let exe_path = env::current_exe()?;
+        if embed::find_embedded(&exe_path)?.is_none() {
+            anyhow::bail!("No embedded payload found in {exe_path:?}; build the installer with the bundle command");
+        }
+        
+        let options = self.payload_options(&exe_path)?;
+        let repaired = packager::repair_directory(&exe_path, &self.install_dir, &options)?;
+        for path in &repaired {
+            info!("Restored damaged file: {path}");
         }
         
         Ok(())
@@ -0,0 +1,232 @@
+// SeeSea Self-Contained Installer - Interpolation Module
+// 模块名称: interpolation
+// 职责范围: 展开配置值中的 ${...} 变量
+// 已实现功能: 变量解析、安装目录/项目信息/用户目录/临时目录/环境变量/平台变量展开、配置中变量名检查
+// 使用依赖: config, anyhow, serde, toml, std::collections, std::env, std::path
+// 主要接口: Variables::new, Variables::expand, Variables::expand_install_options, Variables::expand_commands, check_variables
+// 注意事项: 安装目录确定后才能展开 ${install_dir}，因此默认安装目录中不能引用它；
+//           未知变量和未设置的环境变量都是错误，$${ 表示字面量 ${；
+//           安装时只展开当前平台合并后的安装选项中的路径和脚本以及命令的程序、参数和工作目录，
+//           其他平台的覆盖表和项目描述等值不展开，其中只在其他平台设置的环境变量不影响当前平台
+
+use crate::config::{CommandConfig, Config, InstallOptions};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::env;
+use std::path::Path;
+
+/// 除 ${env:VAR} 外可以引用的变量
+pub const KNOWN_VARIABLES: &[&str] = &[
+    "install_dir",
+    "project.name",
+    "project.version",
+    "home",
+    "temp_dir",
+    "os",
+    "arch",
+    "family",
+    "exe_suffix",
+];
+
+/// 环境变量引用的前缀
+const ENV_PREFIX: &str = "env:";
+
+/// 配置值中的一段: 字面文本或变量引用
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Variable(&'a str),
+}
+
+/// 将文本拆分为字面文本和变量引用
+fn parse(text: &str) -> Result<Vec<Segment<'_>>> {
+    let mut segments = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        let after = &rest[start + 1..];
+        if let Some(escaped) = after.strip_prefix("${") {
+            // $${ 转义为字面量 ${
+            segments.push(Segment::Text(&rest[..start + 1]));
+            segments.push(Segment::Text("{"));
+            rest = escaped;
+        } else if let Some(body) = after.strip_prefix('{') {
+            let end = body
+                .find('}')
+                .ok_or_else(|| anyhow::anyhow!("Unterminated variable reference in: {text}"))?;
+            segments.push(Segment::Text(&rest[..start]));
+            let name = body[..end].trim();
+            if name.is_empty() {
+                anyhow::bail!("Empty variable reference in: {text}");
+            }
+            segments.push(Segment::Variable(name));
+            rest = &body[end + 1..];
+        } else {
+            segments.push(Segment::Text(&rest[..start + 1]));
+            rest = after;
+        }
+    }
+    segments.push(Segment::Text(rest));
+    segments.retain(|segment| *segment != Segment::Text(""));
+    Ok(segments)
+}
+
+/// 检查变量名是否可以引用
+fn check_name(name: &str) -> Result<()> {
+    if let Some(var) = name.strip_prefix(ENV_PREFIX) {
+        if var.is_empty() {
+            anyhow::bail!("Empty environment variable name in ${{{name}}}");
+        }
+        return Ok(());
+    }
+    if !KNOWN_VARIABLES.contains(&name) {
+        anyhow::bail!("Unknown variable ${{{name}}}, expected one of: {}, or env:NAME", KNOWN_VARIABLES.join(", "));
+    }
+    Ok(())
+}
+
+/// 可以在配置值中引用的变量
+#[derive(Debug, Clone)]
+pub struct Variables {
+    values: HashMap<String, String>,
+}
+
+impl Variables {
+    /// 根据配置和安装目录创建变量表，安装目录尚未确定时传入 None
+    pub fn new(config: &Config, install_dir: Option<&Path>) -> Self {
+        let mut values = HashMap::new();
+        if let Some(install_dir) = install_dir {
+            values.insert("install_dir".to_string(), install_dir.to_string_lossy().to_string());
+        }
+        values.insert("project.name".to_string(), config.project.name.clone());
+        values.insert("project.version".to_string(), config.project.version.clone());
+        if let Some(home) = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+            values.insert("home".to_string(), home.to_string_lossy().to_string());
+        }
+        values.insert("temp_dir".to_string(), env::temp_dir().to_string_lossy().to_string());
+        values.insert("os".to_string(), env::consts::OS.to_string());
+        values.insert("arch".to_string(), env::consts::ARCH.to_string());
+        values.insert("family".to_string(), env::consts::FAMILY.to_string());
+        values.insert("exe_suffix".to_string(), env::consts::EXE_SUFFIX.to_string());
+        Self { values }
+    }
+
+    /// 变量的值，未知或当前不可用的变量返回错误
+    pub fn get(&self, name: &str) -> Result<String> {
+        check_name(name)?;
+        if let Some(var) = name.strip_prefix(ENV_PREFIX) {
+            return env::var(var).map_err(|_| anyhow::anyhow!("Environment variable {var} referenced by ${{{name}}} is not set"));
+        }
+        match self.values.get(name) {
+            Some(value) => Ok(value.clone()),
+            None if name == "install_dir" => anyhow::bail!("Variable ${{install_dir}} is not available before the install directory is known"),
+            None => anyhow::bail!("Variable ${{{name}}} is not available on this system"),
+        }
+    }
+
+    /// 展开文本中的变量
+    pub fn expand(&self, text: &str) -> Result<String> {
+        let mut expanded = String::with_capacity(text.len());
+        for segment in parse(text)? {
+            match segment {
+                Segment::Text(text) => expanded.push_str(text),
+                Segment::Variable(name) => expanded.push_str(&self.get(name)?),
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// 展开当前平台合并后的安装选项中的安装目录、脚本和保留的配置文件路径
+    pub fn expand_install_options(&self, options: &InstallOptions) -> Result<InstallOptions> {
+        let mut options = options.clone();
+        self.expand_value("install_options.default_dir", &mut options.default_dir)?;
+        let scripts = [
+            ("pre_install_script", &mut options.pre_install_script),
+            ("post_install_script", &mut options.post_install_script),
+            ("pre_uninstall_script", &mut options.pre_uninstall_script),
+            ("post_uninstall_script", &mut options.post_uninstall_script),
+        ];
+        for (name, script) in scripts {
+            if let Some(script) = script {
+                self.expand_value(&format!("install_options.{name}"), script)?;
+            }
+        }
+        for (index, path) in options.preserve_configs.iter_mut().flatten().enumerate() {
+            self.expand_value(&format!("install_options.preserve_configs[{index}]"), path)?;
+        }
+        Ok(options)
+    }
+
+    /// 展开命令的程序、参数和工作目录
+    pub fn expand_commands(&self, commands: &[CommandConfig]) -> Result<Vec<CommandConfig>> {
+        let mut commands = commands.to_vec();
+        for (index, command) in commands.iter_mut().enumerate() {
+            self.expand_value(&format!("commands[{index}].program"), &mut command.program)?;
+            for (arg_index, arg) in command.args.iter_mut().enumerate() {
+                self.expand_value(&format!("commands[{index}].args[{arg_index}]"), arg)?;
+            }
+            if let Some(working_dir) = &mut command.working_dir {
+                self.expand_value(&format!("commands[{index}].working_dir"), working_dir)?;
+            }
+        }
+        Ok(commands)
+    }
+
+    /// 原地展开一个值，错误信息中给出值所在的键
+    fn expand_value(&self, key: &str, text: &mut String) -> Result<()> {
+        *text = self.expand(text).map_err(|e| anyhow::anyhow!("{key}: {e}"))?;
+        Ok(())
+    }
+}
+
+/// 检查配置中引用的变量名，在加载配置时发现拼写错误，不展开变量；返回每个问题所在的键和说明
+pub fn check_variables(config: &Config) -> Result<Vec<(String, String)>> {
+    let mut problems = Vec::new();
+    let mut tree = toml::Value::try_from(config)?;
+    visit_strings(&mut tree, &mut String::new(), &mut |key, text| {
+        let segments = match parse(text) {
+            Ok(segments) => segments,
+            Err(e) => {
+                problems.push((key.to_string(), e.to_string()));
+                return Ok(());
+            }
+        };
+        for segment in segments {
+            if let Segment::Variable(name) = segment {
+                if let Err(e) = check_name(name) {
+                    problems.push((key.to_string(), e.to_string()));
+                } else if name == "install_dir" && key.ends_with("default_dir") {
+                    problems.push((key.to_string(), "the default install directory cannot refer to ${install_dir}".to_string()));
+                }
+            }
+        }
+        Ok(())
+    })?;
+    Ok(problems)
+}
+
+/// 遍历配置树中的字符串值，key 为值所在的键，例如 commands[0].args[1]
+fn visit_strings(value: &mut toml::Value, key: &mut String, visit: &mut impl FnMut(&str, &mut String) -> Result<()>) -> Result<()> {
+    let len = key.len();
+    match value {
+        toml::Value::String(text) => visit(key, text)?,
+        toml::Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                key.push_str(&format!("[{index}]"));
+                visit_strings(item, key, visit)?;
+                key.truncate(len);
+            }
+        }
+        toml::Value::Table(table) => {
+            for (name, item) in table.iter_mut() {
+                if !key.is_empty() {
+                    key.push('.');
+                }
+                key.push_str(name);
+                visit_strings(item, key, visit)?;
+                key.truncate(len);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
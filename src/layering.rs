@@ -0,0 +1,246 @@
+// SeeSea Self-Contained Installer - Config Layering Module
+// 模块名称: layering
+// 职责范围: 按顺序合并多层安装配置，并记录每个值来自哪一层
+// 已实现功能: include 指令、环境覆盖文件、--set 命令行覆盖、深度合并、值来源记录、合并结果导出为TOML
+// 使用依赖: config, anyhow, log, toml, std::collections, std::fs, std::path
+// 主要接口: ConfigOverrides, ConfigLayers, LayeredConfig, load_layers, load_layered_config, parse_layered_config, merge_tables
+// 注意事项: 合并顺序（后者覆盖前者）: 被包含的文件（按列出顺序，递归展开） < 配置文件本身 < 环境覆盖文件 < --set；
+//           两边都是表时递归合并，其他值（包括数组和 [[commands]] 这样的表数组）整体替换；
+//           include 中的路径相对于包含它的文件，嵌入在安装程序中的配置没有所在目录，因此不能使用 include
+
+use crate::config::{self, Config};
+use anyhow::{Context, Result};
+use log::debug;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 引用其他配置文件的顶层键
+const INCLUDE_KEY: &str = "include";
+
+/// 命令行覆盖值的来源名称
+const SET_SOURCE: &str = "--set";
+
+/// 在配置文件之上叠加的覆盖层
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOverrides {
+    /// 环境名称，例如 production，加载配置文件旁的 <文件名>.<环境>.toml
+    pub environment: Option<String>,
+    /// KEY=VALUE 形式的覆盖值，KEY 为以点分隔的表路径，VALUE 按TOML值解析，解析失败时作为字符串
+    pub set: Vec<String>,
+}
+
+/// 合并后的配置
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    /// 验证后的配置
+    pub config: Config,
+    /// 合并后的TOML表，不含 include 指令
+    pub table: toml::Table,
+    /// 每个值所在的键（例如 install_options.silent）到来源（文件路径或 --set）的映射
+    pub sources: BTreeMap<String, String>,
+}
+
+impl LayeredConfig {
+    /// 合并后键对应的值
+    pub fn value(&self, key: &str) -> Option<&toml::Value> {
+        lookup(&self.table, &key.split('.').collect::<Vec<_>>())
+    }
+
+    /// 合并后的配置文本，用于嵌入到安装程序中
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string(&self.table)?)
+    }
+}
+
+/// 合并后尚未验证的配置层
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLayers {
+    /// 合并后的TOML表，不含 include 指令
+    pub table: toml::Table,
+    /// 每个值所在的键到来源的映射
+    pub sources: BTreeMap<String, String>,
+}
+
+impl ConfigLayers {
+    /// 将一层合并到已有的层之上，记录被替换的值的新来源
+    fn merge(&mut self, table: &toml::Table, source: &str) {
+        let sources = &mut self.sources;
+        merge_tables(&mut self.table, table, "", &mut |key| {
+            // 整体替换的值下原有的键不再存在
+            sources.retain(|existing, _| !existing.starts_with(&format!("{key}.")));
+            sources.insert(key.to_string(), source.to_string());
+        });
+    }
+
+    /// 读取配置文件，先合并它包含的文件；stack 为正在读取的文件，用于发现循环包含
+    fn load_file(&mut self, path: &Path, stack: &mut Vec<PathBuf>) -> Result<()> {
+        let canonical = fs::canonicalize(path).with_context(|| format!("Failed to read config file {}", path.display()))?;
+        if stack.contains(&canonical) {
+            let chain = stack.iter().chain([&canonical]).map(|path| path.display().to_string()).collect::<Vec<_>>();
+            anyhow::bail!("Config include cycle: {}", chain.join(" -> "));
+        }
+
+        debug!("Loading config layer: {}", path.display());
+        let contents = fs::read_to_string(path).with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let mut table: toml::Table = toml::from_str(&contents).with_context(|| format!("Invalid config file {}", path.display()))?;
+        let includes = take_includes(&mut table).with_context(|| format!("Invalid config file {}", path.display()))?;
+
+        stack.push(canonical);
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        for include in includes {
+            self.load_file(&base_dir.join(include), stack)?;
+        }
+        stack.pop();
+
+        self.merge(&table, &path.display().to_string());
+        Ok(())
+    }
+
+    /// 应用 --set 覆盖值
+    fn apply_set(&mut self, overrides: &ConfigOverrides) -> Result<()> {
+        for assignment in &overrides.set {
+            let table = parse_assignment(assignment, &self.table)?;
+            self.merge(&table, SET_SOURCE);
+        }
+        Ok(())
+    }
+
+    /// 验证合并结果
+    fn into_layered(self) -> Result<LayeredConfig> {
+        let config = config::config_from_table(self.table.clone())?;
+        Ok(LayeredConfig { config, table: self.table, sources: self.sources })
+    }
+}
+
+/// 加载配置文件及其包含的文件、环境覆盖文件和 --set 覆盖值
+pub fn load_layered_config(config_file: &Path, overrides: &ConfigOverrides) -> Result<LayeredConfig> {
+    load_layers(config_file, overrides)?.into_layered()
+}
+
+/// 按顺序合并配置层，不验证结果，用于收集全部验证问题
+pub fn load_layers(config_file: &Path, overrides: &ConfigOverrides) -> Result<ConfigLayers> {
+    let mut layers = ConfigLayers::default();
+    layers.load_file(config_file, &mut Vec::new())?;
+
+    if let Some(environment) = &overrides.environment {
+        let overlay = environment_file(config_file, environment)?;
+        if !overlay.is_file() {
+            anyhow::bail!("Config overlay for environment '{environment}' not found: {}", overlay.display());
+        }
+        layers.load_file(&overlay, &mut Vec::new())?;
+    }
+
+    layers.apply_set(overrides)?;
+    Ok(layers)
+}
+
+/// 解析配置文本（例如嵌入在安装程序中的配置）并应用 --set 覆盖值，source 为记录的来源名称
+pub fn parse_layered_config(contents: &str, source: &str, overrides: &ConfigOverrides) -> Result<LayeredConfig> {
+    if overrides.environment.is_some() {
+        anyhow::bail!("Config environments require a config file; pass it with --config");
+    }
+    let table: toml::Table = toml::from_str(contents)?;
+    if table.contains_key(INCLUDE_KEY) {
+        anyhow::bail!("'{INCLUDE_KEY}' is only supported in config files, not in {source}");
+    }
+    let mut layers = ConfigLayers::default();
+    layers.merge(&table, source);
+    layers.apply_set(overrides)?;
+    layers.into_layered()
+}
+
+/// 环境覆盖文件路径: install.toml 的 production 环境为同目录下的 install.production.toml
+fn environment_file(config_file: &Path, environment: &str) -> Result<PathBuf> {
+    if environment.is_empty() || environment.contains(['/', '\\']) {
+        anyhow::bail!("Invalid config environment name: {environment:?}");
+    }
+    let stem = config_file
+        .file_stem()
+        .ok_or_else(|| anyhow::anyhow!("Invalid config file path: {}", config_file.display()))?;
+    let mut name = stem.to_os_string();
+    name.push(format!(".{environment}"));
+    if let Some(extension) = config_file.extension() {
+        name.push(".");
+        name.push(extension);
+    }
+    Ok(config_file.with_file_name(name))
+}
+
+/// 取出并移除 include 指令
+fn take_includes(table: &mut toml::Table) -> Result<Vec<String>> {
+    let Some(value) = table.remove(INCLUDE_KEY) else {
+        return Ok(Vec::new());
+    };
+    let invalid = || anyhow::anyhow!("'{INCLUDE_KEY}' must be an array of file paths");
+    value
+        .as_array()
+        .ok_or_else(invalid)?
+        .iter()
+        .map(|path| path.as_str().map(str::to_string).ok_or_else(invalid))
+        .collect()
+}
+
+/// 解析 KEY=VALUE 覆盖值为只包含该键的表，current 为已合并的配置
+fn parse_assignment(assignment: &str, current: &toml::Table) -> Result<toml::Table> {
+    let (key, value) = assignment
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("Invalid --set value {assignment:?}, expected KEY=VALUE"))?;
+    let key = key.trim();
+    let parts = key.split('.').collect::<Vec<_>>();
+    if parts.iter().any(|part| part.is_empty()) {
+        anyhow::bail!("Invalid --set key {key:?}, expected a dotted path such as install_options.silent");
+    }
+    if parts[0] == INCLUDE_KEY {
+        anyhow::bail!("'{INCLUDE_KEY}' cannot be set with --set");
+    }
+
+    // 按TOML值解析（true、5、["a", "b"]、"quoted"），否则作为字符串；
+    // 原值为字符串时不加引号的值同样作为字符串，例如 project.version=2
+    let value = value.trim();
+    let is_string = lookup(current, &parts).is_some_and(toml::Value::is_str);
+    let value = toml::from_str::<toml::Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .filter(|parsed| parsed.is_str() || !is_string)
+        .unwrap_or_else(|| toml::Value::String(value.to_string()));
+
+    let mut table = toml::Table::new();
+    table.insert(parts[parts.len() - 1].to_string(), value);
+    for part in parts[..parts.len() - 1].iter().rev() {
+        let mut parent = toml::Table::new();
+        parent.insert(part.to_string(), toml::Value::Table(table));
+        table = parent;
+    }
+    Ok(table)
+}
+
+/// 按以点分隔的键查找值
+fn lookup<'a>(table: &'a toml::Table, parts: &[&str]) -> Option<&'a toml::Value> {
+    let (last, parents) = parts.split_last()?;
+    let mut table = table;
+    for part in parents {
+        table = table.get(*part)?.as_table()?;
+    }
+    table.get(*last)
+}
+
+/// 将覆盖表深度合并到基础表: 两边都是表时递归合并，其他值直接替换；
+/// 每替换一个值调用一次 replaced，参数为以点分隔的完整键
+pub fn merge_tables(base: &mut toml::Table, overrides: &toml::Table, prefix: &str, replaced: &mut impl FnMut(&str)) {
+    for (key, value) in overrides {
+        let path = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+        match (base.get_mut(key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(overrides)) => merge_tables(base, overrides, &path, replaced),
+            (None, toml::Value::Table(overrides)) => {
+                let mut table = toml::Table::new();
+                merge_tables(&mut table, overrides, &path, replaced);
+                base.insert(key.clone(), toml::Value::Table(table));
+            }
+            _ => {
+                replaced(&path);
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
@@ -7,17 +7,22 @@
 // 注意事项: 用于集成测试和外部调用
 
 pub mod cli;
+pub mod commands;
 pub mod config;
 pub mod installer;
+pub mod interpolation;
+pub mod layering;
 pub mod packager;
 pub mod platform;
+pub mod progress_bar;
 pub mod utils;
+pub mod validation;
 pub mod version;
 
 // 重新导出主要类型和函数
 pub use cli::Args;
 pub use config::{Config, InstallOptions, load_config, generate_default_config};
 pub use installer::Installer;
-pub use packager::{pack_directory, unpack_directory, compress_file, decompress_file};
+pub use packager::{pack_directory, pack_directory_with, unpack_directory, unpack_directory_with, unpack_paths, repair_directory, verify_signature, verify_signature_with, verify_archive, verify_archive_with, bundle_installer, read_manifest, read_manifest_with, is_encrypted, create_delta, apply_delta, import_archive, export_archive, compress_file, compress_file_with, decompress_file, decompress_file_with, PackOptions, UnpackOptions, SignaturePolicy, Passphrase, PackagerError};
 pub use platform::PlatformImpl;
 pub use version::{Version, get_current_version, save_version, check_update};
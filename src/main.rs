@@ -19,38 +19,43 @@ use clap::Parser;
 use anyhow::Result;
 use log::{info, error};
 
-mod config;
-mod cli;
-mod installer;
-mod platform;
-mod utils;
-mod version;
+use seesea_installer::cli::Args;
+use seesea_installer::config::Config;
+use seesea_installer::{commands, installer};
 
-use crate::cli::Args;
+/// 加载安装配置：命令行指定的配置文件优先，其次为安装程序中嵌入的配置，最后查找 install.toml
+fn load_installer_config(args: &Args) -> Result<Config> {
+    Ok(commands::load_installer_config(args)?.config)
+}
 
 fn main() -> Result<()> {
     // 初始化日志
     env_logger::init();
     
+    // 解析命令行参数
+    let args = Args::parse();
+    
+    // 安装包命令不需要安装配置，输出只包含命令结果（list --json 可直接被其他工具解析）
+    if let Some(result) = commands::run_packager_command(&args, &mut std::io::stdout()) {
+        return result;
+    }
+    
     // 打印欢迎信息
     println!("SeeSea Installer v{}", env!("CARGO_PKG_VERSION"));
     println!("Starting installation process...");
     println!("Press Ctrl+C to cancel.");
     println!();
     
-    // 解析命令行参数
-    let args = Args::parse();
-    
     println!("Command: {}", args.command);
-    println!("Config file: {}", args.config);
+    println!("Config file: {}", args.config_path());
     println!();
     
     info!("Starting SeeSea Installer v{}", env!("CARGO_PKG_VERSION"));
     info!("Command: {}", args.command);
-    info!("Config file: {}", args.config);
+    info!("Config file: {}", args.config_path());
     
     // 简单测试模式：如果配置文件不存在，只显示控制台窗口
-    if let Err(e) = config::load_config(&args.config) {
+    if let Err(e) = load_installer_config(&args) {
         println!("警告: 无法加载配置文件: {e}");
         println!("进入测试模式，仅显示控制台窗口...");
         
@@ -61,7 +66,7 @@ fn main() -> Result<()> {
     }
     
     // 加载配置
-    let config = config::load_config(&args.config)?;
+    let config = load_installer_config(&args)?;
     
     // 创建安装器实例
     let mut installer = installer::Installer::new(config, &args)?;
@@ -0,0 +1,99 @@
+// SeeSea Self-Contained Installer - Packager Checksum Module
+// 模块名称: packager::checksum
+// 职责范围: 计算和校验安装包中的SHA-256摘要
+// 已实现功能: 数据摘要计算、边读边算/边写边算的流包装器、十六进制格式化
+// 使用依赖: sha2, std::io
+// 主要接口: Digest, digest_bytes, to_hex, HashingReader, HashingWriter
+// 注意事项: 包装器只对经过它的字节计算摘要，调用方负责决定摘要覆盖的范围
+
+use sha2::{Digest as _, Sha256};
+use std::io::{Read, Write};
+
+/// 摘要长度（字节）
+pub const DIGEST_LEN: usize = 32;
+
+/// SHA-256摘要
+pub type Digest = [u8; DIGEST_LEN];
+
+/// 计算数据的SHA-256摘要
+pub fn digest_bytes(data: &[u8]) -> Digest {
+    Sha256::digest(data).into()
+}
+
+/// 将摘要格式化为十六进制字符串
+pub fn to_hex(digest: &Digest) -> String {
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// 写入时同步计算摘要并统计字节数的包装器
+pub struct HashingWriter<W: Write> {
+    inner: W,
+    hasher: Sha256,
+    written: u64,
+}
+
+impl<W: Write> HashingWriter<W> {
+    /// 包装写入流
+    pub fn new(inner: W) -> Self {
+        Self { inner, hasher: Sha256::new(), written: 0 }
+    }
+
+    /// 目前为止写入的字节数
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// 获取目前为止写入数据的摘要
+    pub fn digest(&self) -> Digest {
+        self.hasher.clone().finalize().into()
+    }
+
+    /// 取回内部写入流
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 读取时同步计算摘要的包装器
+pub struct HashingReader<R: Read> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> HashingReader<R> {
+    /// 包装读取流
+    pub fn new(inner: R) -> Self {
+        Self { inner, hasher: Sha256::new() }
+    }
+
+    /// 获取目前为止读取数据的摘要
+    pub fn digest(&self) -> Digest {
+        self.hasher.clone().finalize().into()
+    }
+
+    /// 取回内部读取流
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.update(&buf[..read]);
+        Ok(read)
+    }
+}
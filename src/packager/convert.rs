@@ -0,0 +1,327 @@
+// SeeSea Self-Contained Installer - Packager Convert Module
+// 模块名称: packager::convert
+// 职责范围: 在原生安装包格式与 .zip / .tar.zst 之间转换
+// 已实现功能: 导入zip和tar.zst为原生安装包、导出原生安装包为zip和tar.zst，保留路径、权限、修改时间和符号链接
+// 使用依赖: anyhow, chrono, log, tar, zip, zstd, std::fs, std::io, std::path, encryption
+// 主要接口: ForeignFormat, import_archive, export_archive
+// 注意事项: 导入时路径和符号链接目标按解压时的规则校验，不安全的条目直接拒绝；
+//           zip的修改时间为2秒精度的UTC时间，只能表示1980至2107年，超出范围时记为1980-01-01；
+//           tar中的硬链接导入为共享数据帧的条目，设备文件等特殊条目被跳过；
+//           导入不应用忽略规则，也不对内容重复但不是硬链接的文件去重
+
+use super::encryption::PayloadWriter;
+use super::format::{ArchiveEntry, CompressionMethod, EntryKind, MAX_PATH_LEN};
+use super::options::{self, PackOptions, UnpackOptions};
+use super::writer::ArchiveWriter;
+use super::{PackSummary, open_reader, sanitize};
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate, Timelike};
+use log::{info, warn};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use zstd::stream::Decoder;
+
+/// Unix文件类型位
+const S_IFMT: u32 = 0o170000;
+const S_IFDIR: u32 = 0o040000;
+const S_IFLNK: u32 = 0o120000;
+
+/// tar数据块大小
+const TAR_BLOCK_LEN: u64 = 512;
+
+/// 可与原生格式互相转换的外部格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForeignFormat {
+    /// .zip
+    Zip,
+    /// .tar.zst / .tzst
+    TarZst,
+}
+
+impl ForeignFormat {
+    /// 按文件扩展名识别外部格式，不是外部格式时返回None
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".zip") {
+            Some(Self::Zip)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(Self::TarZst)
+        } else {
+            None
+        }
+    }
+}
+
+/// 将zip或tar.zst文件导入为原生安装包，格式按输入文件扩展名识别
+pub fn import_archive(input_file: &Path, output_file: &Path, options: &PackOptions) -> Result<PackSummary> {
+    let Some(format) = ForeignFormat::from_path(input_file) else {
+        anyhow::bail!("Unsupported import format: {input_file:?} (expected .zip, .tar.zst or .tzst)");
+    };
+    info!("Importing {input_file:?} into {output_file:?}");
+    let options = &options.with_resolved_epoch()?;
+
+    let output = PayloadWriter::new(BufWriter::new(File::create(output_file)?), options.passphrase.as_ref())?;
+    let mut writer = ArchiveWriter::with_options(output, options.clone())?;
+    match format {
+        ForeignFormat::Zip => import_zip(input_file, &mut writer, options)?,
+        ForeignFormat::TarZst => import_tar_zst(input_file, &mut writer, options)?,
+    }
+
+    let summary = PackSummary { entries: writer.entries().len(), deduplicated: writer.deduplicated(), ..Default::default() };
+    writer.finish()?.finish()?.into_inner()?.sync_all()?;
+    info!("Successfully imported {} entries to {output_file:?}", summary.entries);
+    Ok(summary)
+}
+
+/// 将原生安装包导出为zip或tar.zst文件，格式按输出文件扩展名识别，返回导出的条目数
+///
+/// 导出前按 unpack_options 中的签名策略校验签名和整包摘要，每个条目的数据在写出时校验摘要；
+/// 加密的安装包使用 unpack_options 中的口令解密。tar.zst的压缩参数取自 options。
+pub fn export_archive(input_file: &Path, output_file: &Path, options: &PackOptions, unpack_options: &UnpackOptions) -> Result<usize> {
+    let Some(format) = ForeignFormat::from_path(output_file) else {
+        anyhow::bail!("Unsupported export format: {output_file:?} (expected .zip, .tar.zst or .tzst)");
+    };
+    info!("Exporting {input_file:?} to {output_file:?}");
+
+    let mut reader = open_reader(input_file, unpack_options.passphrase.as_ref())?;
+    reader.verify(&unpack_options.signature)?;
+    let entries = reader.entries().to_vec();
+    let output = BufWriter::new(File::create(output_file)?);
+
+    match format {
+        ForeignFormat::Zip => {
+            let mut zip = zip::ZipWriter::new(output);
+            for entry in &entries {
+                let file_options = zip::write::FileOptions::default()
+                    .unix_permissions(entry.mode)
+                    .last_modified_time(zip_time(entry.mtime))
+                    .large_file(entry.size >= u32::MAX as u64);
+                match entry.kind {
+                    EntryKind::Directory => zip.add_directory(entry.path.as_str(), file_options)?,
+                    EntryKind::Symlink => {
+                        let mut target = Vec::new();
+                        reader.read_entry(entry, &mut target)?;
+                        zip.add_symlink(entry.path.as_str(), String::from_utf8(target)?, file_options)?;
+                    }
+                    EntryKind::File => {
+                        // 已压缩的文件在zip中同样原样存储
+                        let method = match entry.method {
+                            CompressionMethod::Stored => zip::CompressionMethod::Stored,
+                            CompressionMethod::Zstd => zip::CompressionMethod::Deflated,
+                        };
+                        zip.start_file(entry.path.as_str(), file_options.compression_method(method))?;
+                        reader.read_entry(entry, &mut zip)?;
+                    }
+                }
+            }
+            zip.finish()?.into_inner()?.sync_all()?;
+        }
+        ForeignFormat::TarZst => {
+            let mut tar = tar::Builder::new(options.encoder(output)?);
+            for entry in &entries {
+                let mut header = tar::Header::new_gnu();
+                header.set_mode(entry.mode & 0o7777);
+                header.set_mtime(entry.mtime.max(0) as u64);
+                match entry.kind {
+                    EntryKind::Directory => {
+                        header.set_entry_type(tar::EntryType::Directory);
+                        header.set_size(0);
+                        tar.append_data(&mut header, format!("{}/", entry.path), io::empty())?;
+                    }
+                    EntryKind::Symlink => {
+                        let mut target = Vec::new();
+                        reader.read_entry(entry, &mut target)?;
+                        header.set_entry_type(tar::EntryType::Symlink);
+                        header.set_size(0);
+                        tar.append_link(&mut header, &entry.path, String::from_utf8(target)?)?;
+                    }
+                    EntryKind::File => {
+                        // 先写入带大小的文件头，再把数据流式写在其后并补齐数据块，不整体读入内存
+                        header.set_entry_type(tar::EntryType::Regular);
+                        header.set_size(entry.size);
+                        tar.append_data(&mut header, &entry.path, io::empty())?;
+                        reader.read_entry(entry, tar.get_mut())?;
+                        let padding = (TAR_BLOCK_LEN - entry.size % TAR_BLOCK_LEN) % TAR_BLOCK_LEN;
+                        tar.get_mut().write_all(&[0u8; TAR_BLOCK_LEN as usize][..padding as usize])?;
+                    }
+                }
+            }
+            tar.into_inner()?.finish()?.into_inner()?.sync_all()?;
+        }
+    }
+
+    info!("Successfully exported {} entries to {output_file:?}", entries.len());
+    Ok(entries.len())
+}
+
+/// 逐个导入zip条目
+fn import_zip<W: Write>(input_file: &Path, writer: &mut ArchiveWriter<W>, options: &PackOptions) -> Result<()> {
+    let mut zip = zip::ZipArchive::new(BufReader::new(File::open(input_file)?))?;
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index)?;
+        let Some(path) = foreign_path(file.name())? else {
+            continue;
+        };
+        let unix_mode = file.unix_mode();
+        let kind = match unix_mode.map(|mode| mode & S_IFMT) {
+            Some(S_IFLNK) => EntryKind::Symlink,
+            Some(S_IFDIR) => EntryKind::Directory,
+            _ if file.is_dir() => EntryKind::Directory,
+            _ => EntryKind::File,
+        };
+        let mut entry = ArchiveEntry {
+            path,
+            kind,
+            mode: unix_mode.map_or_else(|| default_mode(kind), |mode| mode & 0o7777),
+            mtime: zip_timestamp(&file.last_modified()),
+            size: if kind == EntryKind::Directory { 0 } else { file.size() },
+            ..Default::default()
+        };
+        options.normalize_entry(&mut entry);
+        add_foreign_entry(writer, entry, options, &mut file)?;
+    }
+    Ok(())
+}
+
+/// 逐个导入tar.zst条目，数据边解压边写入
+fn import_tar_zst<W: Write>(input_file: &Path, writer: &mut ArchiveWriter<W>, options: &PackOptions) -> Result<()> {
+    let mut tar = tar::Archive::new(Decoder::new(File::open(input_file)?)?);
+    for item in tar.entries()? {
+        let mut item = item?;
+        let Some(path) = foreign_path(std::str::from_utf8(&item.path_bytes())?)? else {
+            continue;
+        };
+        let entry_type = item.header().entry_type();
+        let kind = match entry_type {
+            tar::EntryType::Regular | tar::EntryType::Continuous => EntryKind::File,
+            tar::EntryType::Directory => EntryKind::Directory,
+            tar::EntryType::Symlink => EntryKind::Symlink,
+            tar::EntryType::Link => {
+                link_entry(writer, &mut item, path, options)?;
+                continue;
+            }
+            tar::EntryType::XGlobalHeader => continue,
+            _ => {
+                warn!("Skipping special file: {path}");
+                continue;
+            }
+        };
+
+        let mut entry = ArchiveEntry {
+            path,
+            kind,
+            mode: item.header().mode()? & 0o7777,
+            mtime: item.header().mtime()? as i64,
+            size: if kind == EntryKind::File { item.size() } else { 0 },
+            ..Default::default()
+        };
+        options.normalize_entry(&mut entry);
+        if kind == EntryKind::Symlink {
+            let target = link_name(&item)?;
+            entry.size = target.len() as u64;
+            add_foreign_entry(writer, entry, options, target.as_bytes())?;
+        } else {
+            add_foreign_entry(writer, entry, options, &mut item)?;
+        }
+    }
+    Ok(())
+}
+
+/// 将tar硬链接导入为引用目标文件数据帧的条目，目标必须是之前导入的文件
+fn link_entry<W: Write, R: Read>(writer: &mut ArchiveWriter<W>, item: &mut tar::Entry<R>, path: String, options: &PackOptions) -> Result<()> {
+    let target = link_name(item)?;
+    let Some(original) = writer.entries().iter().find(|entry| entry.path == target && entry.kind == EntryKind::File) else {
+        anyhow::bail!("Hard link {path} refers to {target}, which is not an earlier file in the archive");
+    };
+    let digest = original.digest;
+    let mut entry = ArchiveEntry {
+        path,
+        kind: EntryKind::File,
+        method: original.method,
+        mode: item.header().mode()? & 0o7777,
+        mtime: item.header().mtime()? as i64,
+        size: original.size,
+        ..Default::default()
+    };
+    options.normalize_entry(&mut entry);
+    writer.add_deduplicated(entry, &digest, io::empty())
+}
+
+/// 读取tar链接条目的目标
+fn link_name<R: Read>(item: &tar::Entry<R>) -> Result<String> {
+    let Some(target) = item.link_name_bytes() else {
+        anyhow::bail!("Link entry has no target");
+    };
+    Ok(String::from_utf8(target.into_owned())?)
+}
+
+/// 校验并写入导入的条目，文件按开头的采样数据选择压缩方式
+fn add_foreign_entry<W: Write, R: Read>(writer: &mut ArchiveWriter<W>, mut entry: ArchiveEntry, options: &PackOptions, mut data: R) -> Result<()> {
+    match entry.kind {
+        EntryKind::Directory => writer.add_entry(entry, io::empty()),
+        EntryKind::Symlink => {
+            // 符号链接目标的长度由导入的文件决定，多读一个字节以发现超长的目标
+            let mut target = Vec::new();
+            data.take(MAX_PATH_LEN as u64 + 1).read_to_end(&mut target)?;
+            if target.len() > MAX_PATH_LEN {
+                anyhow::bail!("Symlink target of {} exceeds limit of {MAX_PATH_LEN} bytes", entry.path);
+            }
+            let target = String::from_utf8(target)?;
+            sanitize::validate_symlink_target(&entry.path, &target)?;
+            writer.add_entry(entry, target.as_bytes())
+        }
+        EntryKind::File => {
+            let mut sample = Vec::with_capacity(options::ENTROPY_SAMPLE_LEN);
+            Read::by_ref(&mut data).take(options::ENTROPY_SAMPLE_LEN as u64).read_to_end(&mut sample)?;
+            entry.method = options.choose_method(&entry.path, &sample);
+            writer.add_entry(entry, sample.as_slice().chain(data))
+        }
+    }
+}
+
+/// 将外部格式中的条目名转换为安装包路径，去掉开头的"./"和结尾的'/'，根目录本身返回None
+fn foreign_path(name: &str) -> Result<Option<String>> {
+    let mut path = name.trim_end_matches('/');
+    while let Some(rest) = path.strip_prefix("./") {
+        path = rest.trim_start_matches('/');
+    }
+    if path.is_empty() || path == "." {
+        return Ok(None);
+    }
+    sanitize::validate_entry_path(path)?;
+    Ok(Some(path.to_string()))
+}
+
+/// 没有记录Unix权限的条目使用的默认权限
+fn default_mode(kind: EntryKind) -> u32 {
+    match kind {
+        EntryKind::File => 0o644,
+        EntryKind::Directory => 0o755,
+        EntryKind::Symlink => 0o777,
+    }
+}
+
+/// Unix时间戳转换为zip时间，超出zip可表示的范围时使用1980-01-01
+fn zip_time(mtime: i64) -> zip::DateTime {
+    chrono::DateTime::from_timestamp(mtime, 0)
+        .and_then(|time| {
+            zip::DateTime::from_date_and_time(
+                u16::try_from(time.year()).ok()?,
+                time.month() as u8,
+                time.day() as u8,
+                time.hour() as u8,
+                time.minute() as u8,
+                time.second() as u8,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+/// zip时间转换为Unix时间戳
+fn zip_timestamp(time: &zip::DateTime) -> i64 {
+    NaiveDate::from_ymd_opt(time.year().into(), time.month().into(), time.day().into())
+        .and_then(|date| date.and_hms_opt(time.hour().into(), time.minute().into(), time.second().into()))
+        .map_or(0, |time| time.and_utc().timestamp())
+}
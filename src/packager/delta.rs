@@ -0,0 +1,619 @@
+// SeeSea Self-Contained Installer - Packager Delta Module
+// 模块名称: packager::delta
+// 职责范围: 生成两个版本目录之间的增量补丁包，并将其原地应用到已安装的目录
+// 已实现功能: 比较新旧目录树、未变化文件按摘要引用（含移动）、变化文件以旧文件为参考前缀生成zstd二进制差分、
+//             删除列表、应用前校验旧版本文件摘要、新数据先解压到暂存目录校验后再替换
+// 使用依赖: anyhow, log, zstd, std::fs, std::io, std::path, checksum, error, format, metadata, options, reader, sanitize, signing
+// 主要接口: create_delta, apply_delta, DeltaArchive, DeltaEntry, DeltaOp
+// 注意事项: 生成和应用差分时旧文件整体读入内存作为参考前缀（与 zstd --patch-from 相同），
+//           因此只对新旧版本都不超过 PackOptions::patch_size_limit 的文件生成差分，更大的变化文件作为新增条目完整存储；
+//           涉及的旧文件全部校验通过、新数据全部解压校验通过之后才开始修改目录
+//           补丁包的文件头、文件尾和签名块与安装包共用format和reader模块的读取函数，数据错误同样归类为 PackagerError
+//
+// 补丁包布局（结构与安装包容器相同，魔数不同）:
+//   [未压缩]  DELTA_MAGIC(8) | DELTA_VERSION(u16) | flags(u16)
+//   [数据帧]  新增的文件和符号链接为普通zstd帧，差分文件为以旧文件内容为参考前缀的zstd帧
+//   [操作表]  一个zstd帧: entry_count(u64) | 操作条目 * entry_count
+//   [未压缩]  文件尾，格式与安装包相同，archive_digest覆盖文件开头到操作表结束的全部字节
+//   [签名块]  flags带FLAG_SIGNED时存在，格式见signing模块
+//
+// 操作条目: op(u8) | TOC条目 | base_path_len(u32) | base_path | base_size(u64) | base_digest(32)
+// TOC条目记录新版本的路径、类型、元数据和数据帧位置，删除操作记录旧版本的条目。
+// base_path 非空时，应用前旧版本目录中的该文件必须与 base_size、base_digest 一致。
+
+use super::checksum::{DIGEST_LEN, Digest, HashingReader, HashingWriter};
+use super::error::{FrameDecoder, PackagerError};
+use super::format::{self, ArchiveEntry, EntryKind, FLAG_SIGNED, FOOTER_LEN, Footer, HEADER_LEN, MAX_PATH_LEN};
+use super::options::{MAX_PATCH_SIZE, MIN_WINDOW_LOG, PackOptions};
+use super::signing::{ArchiveSignature, SignaturePolicy};
+use super::{metadata, reader, sanitize};
+use anyhow::Result;
+use log::{debug, info, warn};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use zstd::stream::{Decoder, Encoder};
+
+/// 补丁包文件头魔数
+pub const DELTA_MAGIC: [u8; 8] = *b"SEESEADL";
+
+/// 当前写入的补丁包格式版本
+pub const DELTA_VERSION: u16 = 1;
+
+/// 差分帧窗口的最大值（log2字节），与zstd在64位平台上的上限一致
+const MAX_PATCH_WINDOW_LOG: u32 = 31;
+
+/// 应用补丁时在目标目录中使用的暂存目录
+const STAGING_DIR: &str = ".sspk-delta-staging";
+
+/// 补丁操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeltaOp {
+    /// 内容未变化，引用旧版本中的文件（可以位于其他路径）
+    Keep,
+    /// 以旧版本中同路径文件为参考的二进制差分
+    Patch,
+    /// 新增条目，数据帧为完整内容
+    Add,
+    /// 删除旧版本中的条目
+    Delete,
+}
+
+impl DeltaOp {
+    /// 转换为存储的操作标记
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::Keep => 0,
+            Self::Patch => 1,
+            Self::Add => 2,
+            Self::Delete => 3,
+        }
+    }
+
+    /// 从存储的操作标记解析
+    pub fn from_u8(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::Keep),
+            1 => Ok(Self::Patch),
+            2 => Ok(Self::Add),
+            3 => Ok(Self::Delete),
+            _ => Err(PackagerError::Corrupted(format!("Unknown delta operation: {value}")).into()),
+        }
+    }
+}
+
+/// 操作表条目
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeltaEntry {
+    /// 操作
+    pub op: DeltaOp,
+    /// 新版本的条目，删除操作为旧版本的条目
+    pub entry: ArchiveEntry,
+    /// 应用前需要校验的旧版本文件路径，空字符串表示不涉及旧文件
+    pub base_path: String,
+    /// 旧版本文件大小
+    pub base_size: u64,
+    /// 旧版本文件摘要
+    pub base_digest: Digest,
+}
+
+impl DeltaEntry {
+    /// 不涉及旧文件的操作
+    fn new(op: DeltaOp, entry: ArchiveEntry) -> Self {
+        Self { op, entry, base_path: String::new(), base_size: 0, base_digest: [0u8; DIGEST_LEN] }
+    }
+
+    /// 条目是否带有数据帧
+    pub fn has_data(&self) -> bool {
+        matches!(self.op, DeltaOp::Patch | DeltaOp::Add) && self.entry.kind != EntryKind::Directory
+    }
+
+    /// 将操作条目写入流
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.base_path.len() > MAX_PATH_LEN {
+            anyhow::bail!("Archive path too long: {}", self.base_path);
+        }
+
+        writer.write_all(&[self.op.to_u8()])?;
+        self.entry.write_to(writer)?;
+        writer.write_all(&(self.base_path.len() as u32).to_le_bytes())?;
+        writer.write_all(self.base_path.as_bytes())?;
+        writer.write_all(&self.base_size.to_le_bytes())?;
+        writer.write_all(&self.base_digest)?;
+        Ok(())
+    }
+
+    /// 从流中读取操作条目
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut op = [0u8; 1];
+        format::read_exact(reader, &mut op)?;
+        let op = DeltaOp::from_u8(op[0])?;
+        let entry = ArchiveEntry::read_from(reader)?;
+
+        let path_len = format::read_u32(reader)? as usize;
+        if path_len > MAX_PATH_LEN {
+            return Err(PackagerError::Corrupted(format!("Archive path length {path_len} exceeds limit of {MAX_PATH_LEN} bytes")).into());
+        }
+        let mut path_buf = vec![0u8; path_len];
+        format::read_exact(reader, &mut path_buf)?;
+        let base_path = String::from_utf8(path_buf)?;
+        let base_size = format::read_u64(reader)?;
+        let base_digest = format::read_digest(reader)?;
+
+        Ok(Self { op, entry, base_path, base_size, base_digest })
+    }
+}
+
+/// 生成从 base_dir 升级到 new_dir 的补丁包
+pub fn create_delta(base_dir: &Path, new_dir: &Path, output_file: &Path, options: &PackOptions) -> Result<()> {
+    info!("Creating delta from {base_dir:?} to {new_dir:?} as {output_file:?}");
+    options.validate()?;
+    if options.passphrase.is_some() {
+        anyhow::bail!("Delta patches cannot be encrypted");
+    }
+    let options = &options.with_resolved_epoch()?;
+
+    // 旧版本的全部条目；文件按路径和按内容索引
+    let (base_entries, _) = super::collect_entries(base_dir, options)?;
+    let mut base_files: HashMap<String, (PathBuf, u64, Digest)> = HashMap::new();
+    let mut base_by_digest: HashMap<Digest, String> = HashMap::new();
+    for (entry, source_path) in base_entries.iter().filter(|(entry, _)| entry.kind == EntryKind::File) {
+        let digest = super::file_digest(source_path)?;
+        base_by_digest.entry(digest).or_insert_with(|| entry.path.clone());
+        base_files.insert(entry.path.clone(), (source_path.clone(), entry.size, digest));
+    }
+
+    let mut writer = HashingWriter::new(BufWriter::new(File::create(output_file)?));
+    let flags = if options.signing_key.is_some() { FLAG_SIGNED } else { 0 };
+    writer.write_all(&DELTA_MAGIC)?;
+    writer.write_all(&DELTA_VERSION.to_le_bytes())?;
+    writer.write_all(&flags.to_le_bytes())?;
+
+    let mut entries = Vec::new();
+    let mut new_kinds = HashMap::new();
+    let (new_entries, skipped) = super::collect_entries(new_dir, options)?;
+    for path in &skipped {
+        info!("Skipped by ignore rules: {path}");
+    }
+    for (mut entry, source_path) in new_entries {
+        new_kinds.insert(entry.path.clone(), entry.kind);
+        let delta_entry = match entry.kind {
+            EntryKind::Directory => DeltaEntry::new(DeltaOp::Add, entry),
+            EntryKind::Symlink => {
+                let target = metadata::symlink_target(&source_path)?;
+                entry.size = target.len() as u64;
+                entry.digest = write_frame(&mut writer, &mut entry, target.as_bytes(), options, None)?;
+                DeltaEntry::new(DeltaOp::Add, entry)
+            }
+            EntryKind::File => {
+                entry.digest = super::file_digest(&source_path)?;
+                // 同路径内容未变化时优先引用同路径文件，否则引用内容相同的其他文件
+                let same_path = base_files.get(&entry.path).filter(|(_, _, digest)| *digest == entry.digest);
+                let keep_path = same_path.map(|_| &entry.path).or_else(|| base_by_digest.get(&entry.digest)).cloned();
+
+                if let Some(base_path) = keep_path {
+                    let (_, base_size, base_digest) = base_files[&base_path];
+                    DeltaEntry { op: DeltaOp::Keep, entry, base_path, base_size, base_digest }
+                } else if let Some((base_source, base_size, base_digest)) = base_files
+                    .get(&entry.path)
+                    .filter(|(_, base_size, _)| *base_size <= options.patch_size_limit && entry.size <= options.patch_size_limit)
+                {
+                    let base = fs::read(base_source)?;
+                    let digest = write_frame(&mut writer, &mut entry, File::open(&source_path)?, options, Some(&base))?;
+                    ensure_unchanged(&entry, &digest)?;
+                    let base_path = entry.path.clone();
+                    DeltaEntry { op: DeltaOp::Patch, entry, base_path, base_size: *base_size, base_digest: *base_digest }
+                } else {
+                    let digest = write_frame(&mut writer, &mut entry, File::open(&source_path)?, options, None)?;
+                    ensure_unchanged(&entry, &digest)?;
+                    DeltaEntry::new(DeltaOp::Add, entry)
+                }
+            }
+        };
+        entries.push(delta_entry);
+    }
+
+    // 新版本中不存在或类型改变的旧条目
+    for (entry, _) in base_entries {
+        if new_kinds.get(&entry.path) == Some(&entry.kind) {
+            continue;
+        }
+        let delta_entry = match base_files.get(&entry.path) {
+            Some((_, base_size, base_digest)) => DeltaEntry {
+                op: DeltaOp::Delete,
+                base_path: entry.path.clone(),
+                base_size: *base_size,
+                base_digest: *base_digest,
+                entry,
+            },
+            None => DeltaEntry::new(DeltaOp::Delete, entry),
+        };
+        entries.push(delta_entry);
+    }
+
+    // 操作表帧带zstd内容校验
+    let toc_offset = writer.written();
+    let toc_options = PackOptions { threads: 0, checksum: true, signing_key: None, ..options.clone() };
+    let mut encoder = toc_options.encoder(&mut writer)?;
+    encoder.write_all(&(entries.len() as u64).to_le_bytes())?;
+    for entry in &entries {
+        entry.write_to(&mut encoder)?;
+    }
+    encoder.finish()?;
+
+    let footer = Footer {
+        toc_offset,
+        toc_size: writer.written() - toc_offset,
+        archive_digest: writer.digest(),
+    };
+    let mut inner = writer.into_inner();
+    format::write_footer(&mut inner, &footer)?;
+    if let Some(key) = &options.signing_key {
+        ArchiveSignature::sign(key, &footer.archive_digest).write_to(&mut inner)?;
+    }
+    inner.into_inner()?.sync_all()?;
+
+    info!("Successfully created delta with {} operations: {}", entries.len(), summarize(&entries));
+    Ok(())
+}
+
+/// 按签名策略验证补丁包，并将其原地应用到旧版本目录
+pub fn apply_delta(delta_file: &Path, target_dir: &Path, policy: &SignaturePolicy) -> Result<()> {
+    info!("Applying delta {delta_file:?} to {target_dir:?}");
+
+    let mut delta = DeltaArchive::open(delta_file)?;
+    delta.verify(policy)?;
+    let root = fs::canonicalize(target_dir)?;
+    delta.apply(&root)?;
+
+    info!("Successfully applied delta to {target_dir:?}: {}", summarize(delta.entries()));
+    Ok(())
+}
+
+/// 补丁包读取器
+pub struct DeltaArchive {
+    file: File,
+    footer: Footer,
+    signature: Option<ArchiveSignature>,
+    entries: Vec<DeltaEntry>,
+}
+
+impl DeltaArchive {
+    /// 打开补丁包，读取文件头、文件尾和操作表
+    ///
+    /// 补丁包数据的错误归类为 PackagerError，与读取安装包相同。
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path).map_err(PackagerError::Io)?;
+        Self::parse(file).map_err(PackagerError::classify)
+    }
+
+    /// 读取并校验文件头、文件尾和操作表
+    fn parse(mut file: File) -> Result<Self> {
+        let len = file.seek(SeekFrom::End(0))?;
+
+        // 校验文件头
+        file.seek(SeekFrom::Start(0))?;
+        let mut magic = [0u8; 8];
+        format::read_exact(&mut file, &mut magic)?;
+        if magic != DELTA_MAGIC {
+            return Err(PackagerError::BadMagic("Not a SeeSea delta archive".to_string()).into());
+        }
+        if len < HEADER_LEN + FOOTER_LEN {
+            return Err(PackagerError::truncated().into());
+        }
+        let header = format::read_versioned_header(&mut file, DELTA_VERSION, "delta archive")?;
+        let (signature, footer) = reader::read_trailer(&mut file, 0, len, &header, "operation table")?;
+
+        // 读取操作表
+        file.seek(SeekFrom::Start(footer.toc_offset))?;
+        let frame = Read::by_ref(&mut file).take(footer.toc_size);
+        let entries = read_operations(frame, &footer).map_err(|e| reader::frame_ended_early(e, "operation table"))?;
+
+        Ok(Self { file, footer, signature, entries })
+    }
+
+    /// 操作表中的全部条目
+    pub fn entries(&self) -> &[DeltaEntry] {
+        &self.entries
+    }
+
+    /// 补丁包签名，未签名时为None
+    pub fn signature(&self) -> Option<&ArchiveSignature> {
+        self.signature.as_ref()
+    }
+
+    /// 按签名策略验证签名，并校验整包摘要与实际数据一致
+    pub fn verify(&mut self, policy: &SignaturePolicy) -> Result<()> {
+        policy.check(self.signature.as_ref(), &self.footer.archive_digest)?;
+        self.verify_archive_digest().map_err(PackagerError::classify)
+    }
+
+    /// 校验整包摘要，覆盖文件头、全部数据帧和操作表
+    fn verify_archive_digest(&mut self) -> Result<()> {
+        self.file.seek(SeekFrom::Start(0))?;
+        let toc_end = self.footer.toc_offset + self.footer.toc_size;
+        let mut hasher = HashingWriter::new(io::sink());
+        format::copy_exact(&mut self.file, &mut hasher, toc_end)?;
+        format::verify_digest("delta archive", &self.footer.archive_digest, &hasher.digest())
+    }
+
+    /// 将补丁应用到已规范化的旧版本目录
+    ///
+    /// 先校验涉及的旧文件，再把新数据解压到暂存目录并校验摘要，全部通过后才删除、替换和新增条目。
+    pub fn apply(&mut self, root: &Path) -> Result<()> {
+        // 1. 校验旧版本文件
+        for entry in self.entries.iter().filter(|entry| !entry.base_path.is_empty()) {
+            let base = root.join(sanitize::validate_entry_path(&entry.base_path)?);
+            if !file_matches(&base, entry.base_size, &entry.base_digest)? {
+                anyhow::bail!("Installed file {} does not match the base version of this delta", entry.base_path);
+            }
+        }
+
+        // 2. 解压新数据到暂存目录
+        let staging = root.join(STAGING_DIR);
+        if fs::symlink_metadata(&staging).is_ok() {
+            fs::remove_dir_all(&staging)?;
+        }
+        fs::create_dir(&staging)?;
+        let result = self.stage(root, &staging).and_then(|staged| self.commit(root, staged));
+        let cleanup = fs::remove_dir_all(&staging);
+        result?;
+        cleanup?;
+        Ok(())
+    }
+
+    /// 把移动、差分和新增的文件写入暂存目录，返回各条目暂存的文件或符号链接目标
+    fn stage(&mut self, root: &Path, staging: &Path) -> Result<Vec<Staged>> {
+        let mut staged = Vec::with_capacity(self.entries.len());
+        for (index, entry) in self.entries.iter().enumerate() {
+            let staged_path = staging.join(index.to_string());
+            let item = match (entry.op, entry.entry.kind) {
+                (DeltaOp::Keep, _) if entry.base_path != entry.entry.path => {
+                    let base = root.join(sanitize::validate_entry_path(&entry.base_path)?);
+                    fs::copy(&base, &staged_path)?;
+                    Staged::File(staged_path)
+                }
+                (DeltaOp::Patch | DeltaOp::Add, EntryKind::File) => {
+                    let base = match entry.op {
+                        DeltaOp::Patch => Some(fs::read(root.join(sanitize::validate_entry_path(&entry.base_path)?))?),
+                        _ => None,
+                    };
+                    let mut output = File::create(&staged_path)?;
+                    read_frame(&mut self.file, &entry.entry, base.as_deref(), &mut output)?;
+                    output.sync_all()?;
+                    Staged::File(staged_path)
+                }
+                (DeltaOp::Add, EntryKind::Symlink) => {
+                    if entry.entry.size > MAX_PATH_LEN as u64 {
+                        anyhow::bail!("Symlink target of {} exceeds limit of {MAX_PATH_LEN} bytes", entry.entry.path);
+                    }
+                    let mut content = Vec::new();
+                    read_frame(&mut self.file, &entry.entry, None, &mut content)?;
+                    let link_target = String::from_utf8(content)?;
+                    sanitize::validate_symlink_target(&entry.entry.path, &link_target)?;
+                    Staged::Symlink(link_target)
+                }
+                _ => Staged::None,
+            };
+            staged.push(item);
+        }
+        Ok(staged)
+    }
+
+    /// 删除旧条目，把暂存的数据移动到位并恢复元数据
+    fn commit(&self, root: &Path, staged: Vec<Staged>) -> Result<()> {
+        // 先删除文件和符号链接，再由深到浅删除目录
+        let deleted: Vec<&DeltaEntry> = self.entries.iter().filter(|entry| entry.op == DeltaOp::Delete).collect();
+        for entry in deleted.iter().filter(|entry| entry.entry.kind != EntryKind::Directory) {
+            let target = root.join(sanitize::validate_entry_path(&entry.entry.path)?);
+            match fs::remove_file(&target) {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.into()),
+                _ => debug!("Deleted {target:?}"),
+            }
+        }
+        for entry in deleted.iter().rev().filter(|entry| entry.entry.kind == EntryKind::Directory) {
+            let target = root.join(sanitize::validate_entry_path(&entry.entry.path)?);
+            if let Err(e) = fs::remove_dir(&target)
+                && e.kind() != ErrorKind::NotFound
+            {
+                warn!("Keeping directory {target:?} removed in the new version: {e}");
+            }
+        }
+
+        // 按新版本的顺序放置条目，父目录先于其中的条目
+        let mut directories = Vec::new();
+        for (entry, item) in self.entries.iter().zip(staged) {
+            if entry.op == DeltaOp::Delete {
+                continue;
+            }
+            let target = match item {
+                Staged::Symlink(link_target) => {
+                    let target = sanitize::prepare_symlink_target(root, &entry.entry.path, &link_target)?;
+                    metadata::create_symlink(&link_target, &target)?;
+                    target
+                }
+                Staged::File(staged_path) => {
+                    let target = sanitize::prepare_entry_target(root, &entry.entry.path)?;
+                    fs::rename(&staged_path, &target)?;
+                    target
+                }
+                Staged::None => sanitize::prepare_entry_target(root, &entry.entry.path)?,
+            };
+
+            match entry.entry.kind {
+                EntryKind::Directory => {
+                    if !target.is_dir() {
+                        fs::create_dir(&target)?;
+                    }
+                    directories.push((target, entry));
+                    continue;
+                }
+                EntryKind::File => metadata::set_permissions(&target, entry.entry.mode)?,
+                EntryKind::Symlink => {}
+            }
+            metadata::set_mtime(&target, entry.entry.mtime)?;
+        }
+
+        // 由深到浅恢复目录元数据
+        for (target, entry) in directories.iter().rev() {
+            metadata::set_permissions(target, entry.entry.mode)?;
+            metadata::set_mtime(target, entry.entry.mtime)?;
+        }
+        Ok(())
+    }
+}
+
+/// 条目在暂存目录中的数据
+enum Staged {
+    /// 没有新数据，例如目录和原地保留的文件
+    None,
+    /// 暂存的文件
+    File(PathBuf),
+    /// 符号链接目标
+    Symlink(String),
+}
+
+/// 解码操作表帧中的全部条目；同一路径在新版本中只能出现一次，删除也只能出现一次
+fn read_operations<R: Read>(frame: R, footer: &Footer) -> Result<Vec<DeltaEntry>> {
+    let mut decoder = FrameDecoder::new(Decoder::new(frame)?);
+    let entry_count = format::read_u64(&mut decoder)?;
+    let mut entries = Vec::new();
+    let mut paths = HashSet::new();
+    let mut deleted = HashSet::new();
+    for _ in 0..entry_count {
+        let entry = DeltaEntry::read_from(&mut decoder)?;
+        validate_entry(&entry, footer.toc_offset)?;
+        let unique = match entry.op {
+            DeltaOp::Delete => deleted.insert(entry.entry.path.clone()),
+            _ => paths.insert(entry.entry.path.clone()),
+        };
+        if !unique {
+            return Err(PackagerError::Corrupted(format!("Duplicate delta entry: {}", entry.entry.path)).into());
+        }
+        entries.push(entry);
+    }
+    // 读到操作表帧结尾，触发zstd内容校验
+    io::copy(&mut decoder, &mut io::sink())?;
+    Ok(entries)
+}
+
+/// 校验操作条目的路径和数据帧位置
+fn validate_entry(entry: &DeltaEntry, toc_offset: u64) -> Result<()> {
+    sanitize::validate_entry_path(&entry.entry.path)?;
+    if !entry.base_path.is_empty() {
+        sanitize::validate_entry_path(&entry.base_path)?;
+    }
+
+    let needs_base = matches!(entry.op, DeltaOp::Keep | DeltaOp::Patch);
+    if needs_base && (entry.entry.kind != EntryKind::File || entry.base_path.is_empty()) {
+        return Err(PackagerError::Corrupted(format!("Delta archive is corrupted: {} has no base file", entry.entry.path)).into());
+    }
+    if entry.op == DeltaOp::Patch && entry.base_path != entry.entry.path {
+        return Err(PackagerError::Corrupted(format!("Delta archive is corrupted: patch of {} refers to another file", entry.entry.path)).into());
+    }
+    // 应用差分时旧文件整体读入内存
+    if entry.op == DeltaOp::Patch && (entry.base_size > MAX_PATCH_SIZE || entry.entry.size > MAX_PATCH_SIZE) {
+        return Err(PackagerError::Corrupted(format!(
+            "Delta archive is corrupted: patch of {} exceeds limit of {MAX_PATCH_SIZE} bytes",
+            entry.entry.path
+        ))
+        .into());
+    }
+
+    if entry.has_data() {
+        let end = entry.entry.offset.checked_add(entry.entry.compressed_size);
+        if entry.entry.offset < HEADER_LEN || end.is_none_or(|end| end > toc_offset) {
+            return Err(PackagerError::Corrupted(format!(
+                "Delta archive is corrupted: data of {} lies outside the data section",
+                entry.entry.path
+            ))
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// 写入条目的数据帧，记录偏移和压缩后大小，返回写入数据的摘要
+///
+/// 指定 base 时以其为参考前缀生成差分帧。
+fn write_frame<W: Write, R: Read>(
+    writer: &mut HashingWriter<W>,
+    entry: &mut ArchiveEntry,
+    data: R,
+    options: &PackOptions,
+    base: Option<&[u8]>,
+) -> Result<Digest> {
+    entry.offset = writer.written();
+
+    let mut data = HashingReader::new(data);
+    let mut encoder = match base {
+        Some(base) => patch_encoder(&mut *writer, options, base, entry.size)?,
+        None => options.encoder(&mut *writer)?,
+    };
+    let copied = io::copy(&mut data, &mut encoder)?;
+    encoder.finish()?;
+    if copied != entry.size {
+        anyhow::bail!("Entry {} changed while packaging: expected {} bytes, read {copied}", entry.path, entry.size);
+    }
+
+    entry.compressed_size = writer.written() - entry.offset;
+    Ok(data.digest())
+}
+
+/// 创建以旧文件内容为参考前缀的编码器，窗口需覆盖旧文件和新文件
+fn patch_encoder<'a, W: Write>(writer: W, options: &PackOptions, base: &'a [u8], size: u64) -> Result<Encoder<'a, W>> {
+    let mut encoder = Encoder::with_ref_prefix(writer, options.level, base)?;
+    encoder.include_checksum(options.checksum)?;
+    encoder.long_distance_matching(true)?;
+    encoder.window_log(patch_window_log(base.len() as u64 + size))?;
+    Ok(encoder)
+}
+
+/// 覆盖 len 字节所需的窗口大小（log2字节）
+fn patch_window_log(len: u64) -> u32 {
+    (u64::BITS - len.leading_zeros()).clamp(MIN_WINDOW_LOG, MAX_PATCH_WINDOW_LOG)
+}
+
+/// 解压条目的数据帧到 writer 并校验摘要，base 为差分帧的参考前缀
+fn read_frame<W: Write>(file: &mut File, entry: &ArchiveEntry, base: Option<&[u8]>, writer: &mut W) -> Result<()> {
+    file.seek(SeekFrom::Start(entry.offset))?;
+    let frame = BufReader::new(Read::by_ref(file).take(entry.compressed_size));
+    let mut decoder = match base {
+        Some(base) => Decoder::with_ref_prefix(frame, base)?,
+        None => Decoder::with_buffer(frame)?,
+    };
+    decoder.window_log_max(MAX_PATCH_WINDOW_LOG)?;
+
+    let mut data = HashingReader::new(FrameDecoder::new(decoder));
+    format::copy_exact(&mut data, writer, entry.size).map_err(|e| reader::frame_ended_early(e, &entry.path))?;
+    format::verify_digest(&entry.path, &entry.digest, &data.digest())
+}
+
+/// 打包时再次读取的内容必须与计算摘要时一致
+fn ensure_unchanged(entry: &ArchiveEntry, digest: &Digest) -> Result<()> {
+    if *digest != entry.digest {
+        anyhow::bail!("Entry {} changed while packaging", entry.path);
+    }
+    Ok(())
+}
+
+/// 检查文件是否存在且大小和摘要一致
+fn file_matches(path: &Path, size: u64, digest: &Digest) -> Result<bool> {
+    let Ok(file_metadata) = fs::symlink_metadata(path) else {
+        return Ok(false);
+    };
+    Ok(file_metadata.is_file() && file_metadata.len() == size && super::file_digest(path)? == *digest)
+}
+
+/// 统计各类操作的数量
+fn summarize(entries: &[DeltaEntry]) -> String {
+    let count = |op: DeltaOp| entries.iter().filter(|entry| entry.op == op && entry.entry.kind == EntryKind::File).count();
+    format!(
+        "{} unchanged, {} patched, {} added, {} deleted files",
+        count(DeltaOp::Keep),
+        count(DeltaOp::Patch),
+        count(DeltaOp::Add),
+        count(DeltaOp::Delete)
+    )
+}
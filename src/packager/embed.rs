@@ -0,0 +1,183 @@
+// SeeSea Self-Contained Installer - Packager Embed Module
+// 模块名称: packager::embed
+// 职责范围: 将安装包和安装配置附加到安装程序可执行文件末尾，并在运行时定位它们
+// 已实现功能: 附加安装包和配置、写入定位尾部、从可执行文件中定位安装包和读取嵌入配置
+// 使用依赖: anyhow, log, std::fs, std::io, std::path, error, format, reader
+// 主要接口: EmbeddedPayload, append_payload, locate, find_embedded, read_embedded_config
+// 注意事项: 重新附加时会先去掉原有的嵌入内容，安装程序本体保持不变；输出先写入临时文件，输出路径可以就是安装程序本体
+//
+// 可执行文件布局:
+//   [安装程序本体] [安装包] [install.toml] [尾部]
+// 尾部（未压缩，小端序）:
+//   archive_offset(u64) | archive_len(u64) | config_offset(u64) | config_len(u64) | SFX_MAGIC(8)
+// 偏移均为相对可执行文件开头的绝对偏移；没有嵌入配置时config_len为0。
+
+use super::error::PackagerError;
+use super::format::{self, ArchiveFormat};
+use super::reader::ArchiveReader;
+use anyhow::Result;
+use log::info;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// 嵌入内容尾部魔数
+pub const SFX_MAGIC: [u8; 8] = *b"SSPK-SFX";
+
+/// 尾部长度（字节）
+pub const TRAILER_LEN: u64 = 8 * 4 + 8;
+
+/// 嵌入配置的最大长度（字节）
+pub const MAX_CONFIG_LEN: u64 = 16 * 1024 * 1024;
+
+/// 嵌入在可执行文件中的内容位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbeddedPayload {
+    /// 安装包偏移
+    pub archive_offset: u64,
+    /// 安装包长度
+    pub archive_len: u64,
+    /// 嵌入配置偏移
+    pub config_offset: u64,
+    /// 嵌入配置长度，0表示没有嵌入配置
+    pub config_len: u64,
+}
+
+impl EmbeddedPayload {
+    /// 安装程序本体的长度，即嵌入内容的起始位置
+    pub fn stub_len(&self) -> u64 {
+        self.archive_offset
+    }
+}
+
+/// 在文件末尾查找嵌入内容尾部，没有尾部时返回None
+pub fn locate(file: &mut File) -> Result<Option<EmbeddedPayload>> {
+    let len = file.seek(SeekFrom::End(0))?;
+    if len < TRAILER_LEN {
+        return Ok(None);
+    }
+
+    file.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+    let archive_offset = format::read_u64(file)?;
+    let archive_len = format::read_u64(file)?;
+    let config_offset = format::read_u64(file)?;
+    let config_len = format::read_u64(file)?;
+    let mut magic = [0u8; 8];
+    format::read_exact(file, &mut magic)?;
+    if magic != SFX_MAGIC {
+        return Ok(None);
+    }
+
+    // 安装包、配置和尾部必须首尾相接
+    let archive_end = archive_offset.checked_add(archive_len);
+    let config_end = config_offset.checked_add(config_len);
+    if archive_end != Some(config_offset) || config_end != Some(len - TRAILER_LEN) {
+        return Err(PackagerError::Corrupted("Embedded payload trailer is corrupted".to_string()).into());
+    }
+    if config_len > MAX_CONFIG_LEN {
+        anyhow::bail!("Embedded config exceeds limit of {MAX_CONFIG_LEN} bytes");
+    }
+
+    Ok(Some(EmbeddedPayload { archive_offset, archive_len, config_offset, config_len }))
+}
+
+/// 查找可执行文件中嵌入的内容
+pub fn find_embedded(exe_path: &Path) -> Result<Option<EmbeddedPayload>> {
+    locate(&mut File::open(exe_path)?)
+}
+
+/// 读取可执行文件中嵌入的安装配置
+pub fn read_embedded_config(exe_path: &Path) -> Result<Option<String>> {
+    let mut file = File::open(exe_path)?;
+    let Some(payload) = locate(&mut file)? else {
+        return Ok(None);
+    };
+    if payload.config_len == 0 {
+        return Ok(None);
+    }
+
+    file.seek(SeekFrom::Start(payload.config_offset))?;
+    let mut config = vec![0u8; payload.config_len as usize];
+    format::read_exact(&mut file, &mut config)?;
+    Ok(Some(String::from_utf8(config)?))
+}
+
+/// 将安装包和安装配置附加到安装程序本体之后，写出自解压安装程序
+///
+/// stub 若已带有嵌入内容，只保留其本体部分。
+pub fn append_payload(stub: &Path, archive: &Path, config: Option<&Path>, output: &Path) -> Result<()> {
+    info!("Embedding {archive:?} into {stub:?} as {output:?}");
+
+    // 安装包必须是完整有效的原生格式；加密的安装包没有口令无法校验摘要，安装时由每块的认证标签保护
+    let mut archive_file = File::open(archive)?;
+    let mut prefix = [0u8; 8];
+    format::read_exact(&mut archive_file, &mut prefix)?;
+    match format::detect_format(&prefix) {
+        ArchiveFormat::Native => ArchiveReader::from_file(archive_file.try_clone()?)?.verify_archive_digest()?,
+        ArchiveFormat::Encrypted => {}
+        _ => anyhow::bail!("Only native payload archives can be embedded: {archive:?}"),
+    }
+
+    let mut stub_file = File::open(stub)?;
+    let stub_len = match locate(&mut stub_file)? {
+        Some(payload) => payload.stub_len(),
+        None => stub_file.seek(SeekFrom::End(0))?,
+    };
+
+    // 先写入输出文件旁的临时文件再替换，output 与 stub 或 archive 是同一个文件时读取的内容不会被截断
+    let file_name = output
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Invalid output file: {output:?}"))?;
+    let partial = output.with_file_name(format!(".{}.sfx-partial", file_name.to_string_lossy()));
+    let result = write_installer(&mut stub_file, stub_len, &mut archive_file, config, &partial);
+    // 关闭输入文件后再替换，Windows 上不能替换仍被打开的文件
+    drop((stub_file, archive_file));
+    let result = result.and_then(|lens| {
+        // 保留安装程序本体的权限（可执行位）
+        fs::set_permissions(&partial, fs::metadata(stub)?.permissions())?;
+        fs::rename(&partial, output)?;
+        Ok(lens)
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    let (archive_len, config_len) = result?;
+
+    info!("Embedded {archive_len} bytes of payload and {config_len} bytes of config into {output:?}");
+    Ok(())
+}
+
+/// 写出安装程序本体、安装包、安装配置和尾部，返回安装包和配置的长度
+fn write_installer(stub_file: &mut File, stub_len: u64, archive_file: &mut File, config: Option<&Path>, output: &Path) -> Result<(u64, u64)> {
+    let mut writer = BufWriter::new(File::create(output)?);
+
+    // 安装程序本体
+    stub_file.seek(SeekFrom::Start(0))?;
+    format::copy_exact(stub_file, &mut writer, stub_len)?;
+
+    // 安装包
+    archive_file.seek(SeekFrom::Start(0))?;
+    let archive_len = io::copy(archive_file, &mut writer)?;
+
+    // 安装配置
+    let config_offset = stub_len + archive_len;
+    let config_len = match config {
+        Some(config) => {
+            let len = io::copy(&mut File::open(config)?.take(MAX_CONFIG_LEN + 1), &mut writer)?;
+            if len > MAX_CONFIG_LEN {
+                anyhow::bail!("Config file exceeds limit of {MAX_CONFIG_LEN} bytes: {config:?}");
+            }
+            len
+        }
+        None => 0,
+    };
+
+    // 尾部
+    writer.write_all(&stub_len.to_le_bytes())?;
+    writer.write_all(&archive_len.to_le_bytes())?;
+    writer.write_all(&config_offset.to_le_bytes())?;
+    writer.write_all(&config_len.to_le_bytes())?;
+    writer.write_all(&SFX_MAGIC)?;
+    writer.into_inner()?.sync_all()?;
+    Ok((archive_len, config_len))
+}
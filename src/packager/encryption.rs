@@ -0,0 +1,446 @@
+// SeeSea Self-Contained Installer - Packager Encryption Module
+// 模块名称: packager::encryption
+// 职责范围: 使用口令对整个安装包进行认证加密，并在读取时逐块解密和认证
+// 已实现功能: 口令（从文本、文件或环境变量读取，释放时清零）、Argon2id密钥派生、ChaCha20-Poly1305分块加密写入、
+//             按块随机访问的解密读取、认证失败（口令错误、篡改、截断、重排）检测
+// 使用依赖: anyhow, argon2, chacha20poly1305, getrandom, zeroize, std::fs, std::io, error, format, reader
+// 主要接口: Passphrase, EncryptionHeader, EncryptingWriter, PayloadWriter, DecryptingReader, ENCRYPTED_MAGIC, PASSPHRASE_ENV
+// 注意事项: 加密层位于安装包格式之外：先生成完整的原生格式安装包字节流再加密，拆分分卷时对密文分卷；
+//           每个块都单独认证，读取时只解密被访问的块，任何一块认证失败都会使读取出错，
+//           因此完整解压前的整包摘要校验会在写出任何文件之前发现口令错误和篡改
+//
+// 加密布局: 加密文件头 | 密文块 ...
+//   加密文件头: ENCRYPTED_MAGIC(8) | ENCRYPTION_VERSION(u16) | reserved(u16) | chunk_size(u32)
+//               | memory_kib(u32) | iterations(u32) | parallelism(u32) | salt(16) | nonce_prefix(7) | reserved(1)
+//   密文块: 明文块（除最后一块外都为 chunk_size 字节，最后一块可以为空）| Poly1305标签(16)
+// 密钥由 Argon2id(口令, salt) 派生；第 i 块的nonce为 nonce_prefix | i(u32, 大端序) | 是否最后一块(u8)，
+// 附加认证数据为整个加密文件头，因此修改参数、删除末尾的块或调换块的顺序都会导致认证失败。
+
+use super::error::PackagerError;
+use super::format;
+use super::reader::ArchiveSource;
+use anyhow::Result;
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{AeadInPlace, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use zeroize::Zeroizing;
+
+/// 加密文件头魔数
+pub const ENCRYPTED_MAGIC: [u8; 8] = *b"SSPK-ENC";
+
+/// 当前写入的加密格式版本
+pub const ENCRYPTION_VERSION: u16 = 1;
+
+/// 加密文件头长度（字节）
+pub const ENCRYPTION_HEADER_LEN: u64 = 8 + 2 + 2 + 4 + 4 + 4 + 4 + 16 + 7 + 1;
+
+/// 读取口令的环境变量
+pub const PASSPHRASE_ENV: &str = "SEESEA_PASSPHRASE";
+
+/// 写入时每块的明文长度（字节）
+pub const CHUNK_SIZE: u32 = 64 * 1024;
+
+/// 每块的认证标签长度（字节）
+const TAG_LEN: u64 = 16;
+
+/// 读取时允许的块长度范围，超出范围的文件头视为损坏
+const CHUNK_SIZE_RANGE: std::ops::RangeInclusive<u32> = 4 * 1024..=16 * 1024 * 1024;
+
+/// 读取时允许的Argon2参数上限，避免损坏或恶意的文件头耗尽内存和CPU
+const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+const MAX_ITERATIONS: u32 = 64;
+const MAX_PARALLELISM: u32 = 16;
+
+/// 加密口令，释放时清零
+#[derive(Clone, PartialEq, Eq)]
+pub struct Passphrase(Zeroizing<String>);
+
+impl Passphrase {
+    /// 使用文本创建口令，口令不能为空
+    pub fn new(text: impl Into<String>) -> Result<Self> {
+        let text = Zeroizing::new(text.into());
+        if text.is_empty() {
+            anyhow::bail!("Passphrase must not be empty");
+        }
+        Ok(Self(text))
+    }
+
+    /// 从文件读取口令，去掉末尾的换行符
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let mut text = Zeroizing::new(fs::read_to_string(path)?);
+        let len = text.trim_end_matches(['\r', '\n']).len();
+        text.truncate(len);
+        if text.is_empty() {
+            anyhow::bail!("Passphrase file is empty: {path:?}");
+        }
+        Ok(Self(text))
+    }
+
+    /// 从 SEESEA_PASSPHRASE 环境变量读取口令，未设置时返回None
+    pub fn from_env() -> Result<Option<Self>> {
+        match std::env::var(PASSPHRASE_ENV) {
+            Ok(text) => Self::new(text).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+/// 不输出口令内容
+impl fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Passphrase(..)")
+    }
+}
+
+/// 加密文件头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EncryptionHeader {
+    /// 每块的明文长度（字节）
+    pub chunk_size: u32,
+    /// Argon2内存开销（KiB）
+    pub memory_kib: u32,
+    /// Argon2迭代次数
+    pub iterations: u32,
+    /// Argon2并行度
+    pub parallelism: u32,
+    /// 密钥派生的盐
+    pub salt: [u8; 16],
+    /// 每块nonce的公共前缀
+    pub nonce_prefix: [u8; 7],
+}
+
+impl EncryptionHeader {
+    /// 使用默认参数和随机的盐、nonce前缀生成文件头
+    pub fn generate() -> Result<Self> {
+        let mut salt = [0u8; 16];
+        let mut nonce_prefix = [0u8; 7];
+        getrandom::getrandom(&mut salt)
+            .and_then(|_| getrandom::getrandom(&mut nonce_prefix))
+            .map_err(|e| anyhow::anyhow!("Failed to generate encryption salt: {e}"))?;
+        Ok(Self {
+            chunk_size: CHUNK_SIZE,
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+            salt,
+            nonce_prefix,
+        })
+    }
+
+    /// 编码文件头，同时作为每块的附加认证数据
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(ENCRYPTION_HEADER_LEN as usize);
+        bytes.extend_from_slice(&ENCRYPTED_MAGIC);
+        bytes.extend_from_slice(&ENCRYPTION_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&0u16.to_le_bytes());
+        bytes.extend_from_slice(&self.chunk_size.to_le_bytes());
+        bytes.extend_from_slice(&self.memory_kib.to_le_bytes());
+        bytes.extend_from_slice(&self.iterations.to_le_bytes());
+        bytes.extend_from_slice(&self.parallelism.to_le_bytes());
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(&self.nonce_prefix);
+        bytes.push(0);
+        bytes
+    }
+
+    /// 从流中读取并校验文件头
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut magic = [0u8; 8];
+        format::read_exact(reader, &mut magic)?;
+        if magic != ENCRYPTED_MAGIC {
+            return Err(PackagerError::BadMagic("Not an encrypted SeeSea archive".to_string()).into());
+        }
+        let version = format::read_u16(reader)?;
+        if version != ENCRYPTION_VERSION {
+            return Err(PackagerError::UnsupportedVersion(format!(
+                "Unsupported encrypted archive version: {version} (supported: {ENCRYPTION_VERSION})"
+            ))
+            .into());
+        }
+        format::read_u16(reader)?;
+        let chunk_size = format::read_u32(reader)?;
+        let memory_kib = format::read_u32(reader)?;
+        let iterations = format::read_u32(reader)?;
+        let parallelism = format::read_u32(reader)?;
+        let mut salt = [0u8; 16];
+        format::read_exact(reader, &mut salt)?;
+        let mut nonce_prefix = [0u8; 8];
+        format::read_exact(reader, &mut nonce_prefix)?;
+
+        if !CHUNK_SIZE_RANGE.contains(&chunk_size)
+            || memory_kib > MAX_MEMORY_KIB
+            || iterations > MAX_ITERATIONS
+            || parallelism > MAX_PARALLELISM
+        {
+            return Err(PackagerError::Corrupted(
+                "Encrypted archive header is corrupted: invalid encryption parameters".to_string(),
+            )
+            .into());
+        }
+        let nonce_prefix = nonce_prefix[..7].try_into().expect("slice of 7 bytes");
+        Ok(Self { chunk_size, memory_kib, iterations, parallelism, salt, nonce_prefix })
+    }
+
+    /// 由口令派生密钥并创建加密器
+    fn cipher(&self, passphrase: &Passphrase) -> Result<ChaCha20Poly1305> {
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|e| anyhow::anyhow!("Invalid key derivation parameters: {e}"))?;
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.0.as_bytes(), &self.salt, key.as_mut_slice())
+            .map_err(|e| anyhow::anyhow!("Failed to derive encryption key: {e}"))?;
+        Ok(ChaCha20Poly1305::new(Key::from_slice(key.as_slice())))
+    }
+
+    /// 第 index 块的nonce
+    fn nonce(&self, index: u32, last: bool) -> Nonce {
+        let mut nonce = [0u8; 12];
+        nonce[..7].copy_from_slice(&self.nonce_prefix);
+        nonce[7..11].copy_from_slice(&index.to_be_bytes());
+        nonce[11] = last as u8;
+        Nonce::from(nonce)
+    }
+}
+
+/// 分块加密写入器，写完后必须调用 finish 写出最后一块
+pub struct EncryptingWriter<W: Write> {
+    inner: W,
+    header: EncryptionHeader,
+    aad: Vec<u8>,
+    cipher: ChaCha20Poly1305,
+    /// 下一块的序号
+    index: u32,
+    /// 尚未加密的明文
+    buffer: Zeroizing<Vec<u8>>,
+}
+
+impl<W: Write> EncryptingWriter<W> {
+    /// 写入加密文件头，之后写入的数据都会加密
+    pub fn new(mut inner: W, passphrase: &Passphrase) -> Result<Self> {
+        let header = EncryptionHeader::generate()?;
+        let aad = header.to_bytes();
+        inner.write_all(&aad)?;
+        let cipher = header.cipher(passphrase)?;
+        let buffer = Zeroizing::new(Vec::with_capacity(header.chunk_size as usize + TAG_LEN as usize));
+        Ok(Self { inner, header, aad, cipher, index: 0, buffer })
+    }
+
+    /// 加密并写出缓冲区中的明文
+    fn seal(&mut self, last: bool) -> io::Result<()> {
+        let nonce = self.header.nonce(self.index, last);
+        self.cipher
+            .encrypt_in_place(&nonce, &self.aad, &mut *self.buffer)
+            .map_err(|_| io::Error::other("Failed to encrypt archive data"))?;
+        self.inner.write_all(&self.buffer)?;
+        self.buffer.clear();
+        self.index = self
+            .index
+            .checked_add(1)
+            .ok_or_else(|| io::Error::other("Archive is too large to encrypt"))?;
+        Ok(())
+    }
+
+    /// 加密最后一块并返回内部写入流
+    pub fn finish(mut self) -> Result<W> {
+        self.seal(true)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+impl<W: Write> Write for EncryptingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // 缓冲区满且还有后续数据时才写出，保证最后一块总是在 finish 中写出
+        let chunk_size = self.header.chunk_size as usize;
+        if self.buffer.len() == chunk_size && !buf.is_empty() {
+            self.seal(false)?;
+        }
+        let len = (chunk_size - self.buffer.len()).min(buf.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 写出安装包的输出流: 指定口令时加密，否则原样写入
+pub enum PayloadWriter<W: Write> {
+    /// 不加密
+    Plain(W),
+    /// 加密
+    Encrypted(EncryptingWriter<W>),
+}
+
+impl<W: Write> PayloadWriter<W> {
+    /// 按口令选择是否加密
+    pub fn new(inner: W, passphrase: Option<&Passphrase>) -> Result<Self> {
+        Ok(match passphrase {
+            Some(passphrase) => Self::Encrypted(EncryptingWriter::new(inner, passphrase)?),
+            None => Self::Plain(inner),
+        })
+    }
+
+    /// 写完全部数据，返回内部写入流
+    pub fn finish(self) -> Result<W> {
+        match self {
+            Self::Plain(mut inner) => {
+                inner.flush()?;
+                Ok(inner)
+            }
+            Self::Encrypted(writer) => writer.finish(),
+        }
+    }
+}
+
+impl<W: Write> Write for PayloadWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(inner) => inner.write(buf),
+            Self::Encrypted(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(inner) => inner.flush(),
+            Self::Encrypted(writer) => writer.flush(),
+        }
+    }
+}
+
+/// 按块解密和认证的读取器，支持随机访问
+pub struct DecryptingReader {
+    source: Box<dyn ArchiveSource>,
+    /// 加密数据在来源中的起始位置
+    base: u64,
+    header: EncryptionHeader,
+    aad: Vec<u8>,
+    cipher: ChaCha20Poly1305,
+    /// 密文块数
+    chunk_count: u64,
+    /// 密文总长度（不含文件头）
+    body_len: u64,
+    /// 明文总长度
+    len: u64,
+    position: u64,
+    /// 最近解密的块: 序号和明文
+    chunk: Option<(u64, Zeroizing<Vec<u8>>)>,
+}
+
+impl DecryptingReader {
+    /// 读取来源中 [base, base + len) 范围内的加密数据，口令错误或数据损坏时出错
+    pub fn open(mut source: Box<dyn ArchiveSource>, base: u64, len: u64, passphrase: &Passphrase) -> Result<Self> {
+        source.seek(SeekFrom::Start(base))?;
+        let header = EncryptionHeader::read_from(&mut source)?;
+        if len < ENCRYPTION_HEADER_LEN + TAG_LEN {
+            return Err(PackagerError::Truncated("Encrypted archive is truncated".to_string()).into());
+        }
+
+        // 除最后一块外每块都是完整的；明文长度恰好为块长度整数倍时最后一块也是完整的
+        let body_len = len - ENCRYPTION_HEADER_LEN;
+        let sealed_len = header.chunk_size as u64 + TAG_LEN;
+        let remainder = body_len % sealed_len;
+        if remainder != 0 && remainder < TAG_LEN {
+            return Err(PackagerError::Truncated("Encrypted archive is truncated".to_string()).into());
+        }
+        let chunk_count = body_len.div_ceil(sealed_len);
+        if chunk_count > u32::MAX as u64 + 1 {
+            return Err(PackagerError::Corrupted("Encrypted archive is corrupted: too many chunks".to_string()).into());
+        }
+
+        let mut reader = Self {
+            source,
+            base,
+            aad: header.to_bytes(),
+            cipher: header.cipher(passphrase)?,
+            header,
+            chunk_count,
+            body_len,
+            len: body_len - chunk_count * TAG_LEN,
+            position: 0,
+            chunk: None,
+        };
+        // 第一块认证失败时无法区分口令错误和数据损坏
+        match reader.load_chunk(0).map_err(PackagerError::from) {
+            Ok(()) => Ok(reader),
+            Err(PackagerError::ChecksumMismatch(_)) => Err(PackagerError::ChecksumMismatch(
+                "Wrong passphrase, or the encrypted archive is corrupted".to_string(),
+            )
+            .into()),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// 明文总长度
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// 明文是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 读取并认证第 index 块
+    fn load_chunk(&mut self, index: u64) -> io::Result<()> {
+        if self.chunk.as_ref().is_some_and(|(cached, _)| *cached == index) {
+            return Ok(());
+        }
+        let sealed_len = self.header.chunk_size as u64 + TAG_LEN;
+        let offset = index * sealed_len;
+        let len = sealed_len.min(self.body_len - offset);
+        let last = index + 1 == self.chunk_count;
+
+        let mut buffer = Zeroizing::new(vec![0u8; len as usize]);
+        self.source.seek(SeekFrom::Start(self.base + ENCRYPTION_HEADER_LEN + offset))?;
+        self.source.read_exact(&mut buffer)?;
+        let nonce = self.header.nonce(index as u32, last);
+        self.cipher.decrypt_in_place(&nonce, &self.aad, &mut *buffer).map_err(|_| {
+            PackagerError::ChecksumMismatch(format!(
+                "Encrypted archive failed authentication at chunk {index}: data was modified or truncated"
+            ))
+        })?;
+        self.chunk = Some((index, buffer));
+        Ok(())
+    }
+}
+
+impl Read for DecryptingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.len {
+            return Ok(0);
+        }
+        let chunk_size = self.header.chunk_size as u64;
+        let index = self.position / chunk_size;
+        self.load_chunk(index)?;
+        let Some((_, chunk)) = &self.chunk else {
+            unreachable!("chunk was just loaded");
+        };
+        let offset = (self.position - index * chunk_size) as usize;
+        let len = (chunk.len() - offset).min(buf.len());
+        buf[..len].copy_from_slice(&chunk[offset..offset + len]);
+        self.position += len as u64;
+        Ok(len)
+    }
+}
+
+impl Seek for DecryptingReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        let Some(position) = position else {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position"));
+        };
+        self.position = position;
+        Ok(position)
+    }
+}
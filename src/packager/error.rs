@@ -0,0 +1,153 @@
+// SeeSea Self-Contained Installer - Packager Error Module
+// 模块名称: packager::error
+// 职责范围: 定义读取安装包时的错误类型，区分截断、损坏和I/O错误
+// 已实现功能: PackagerError错误类型、由I/O错误和内部错误归类、zstd帧校验和错误识别
+// 使用依赖: anyhow, zstd, std::fmt, std::io, sanitize
+// 主要接口: PackagerError, PackagerError::classify, FrameDecoder
+// 注意事项: 打包模块的接口仍返回 anyhow::Result，调用方通过 downcast_ref::<PackagerError>() 区分错误类型；
+//           签名验证策略、口令缺失等与安装包数据本身无关的错误不归入 PackagerError
+
+use super::sanitize::UnsafePathError;
+use std::fmt;
+use std::io::{self, ErrorKind, Read};
+use zstd::zstd_safe::{self, zstd_sys::ZSTD_ErrorCode};
+
+/// 读取安装包时的错误
+#[derive(Debug)]
+pub enum PackagerError {
+    /// 安装包被截断: 数据在预期的位置之前结束
+    Truncated(String),
+    /// 魔数不符，不是预期格式的文件
+    BadMagic(String),
+    /// 摘要、帧校验和或认证标签与数据不符
+    ChecksumMismatch(String),
+    /// 条目路径或符号链接目标不安全
+    UnsafePath(UnsafePathError),
+    /// 格式版本或标志位不受支持，通常由更新版本的打包工具生成
+    UnsupportedVersion(String),
+    /// 结构损坏: 字段取值非法或互相矛盾
+    Corrupted(String),
+    /// 读写文件失败
+    Io(io::Error),
+}
+
+impl PackagerError {
+    /// 安装包被截断
+    pub fn truncated() -> Self {
+        Self::Truncated("Archive is truncated".to_string())
+    }
+
+    /// 将内部错误归类为 PackagerError，无法归类的错误原样返回
+    pub fn classify(error: anyhow::Error) -> anyhow::Error {
+        let error = match error.downcast::<PackagerError>() {
+            Ok(error) => return error.into(),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<UnsafePathError>() {
+            Ok(error) => return Self::UnsafePath(error).into(),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<io::Error>() {
+            Ok(error) => return Self::from(error).into(),
+            Err(error) => error,
+        };
+        // 目录表中的路径和符号链接目标必须是UTF-8
+        match error.downcast::<std::string::FromUtf8Error>() {
+            Ok(error) => Self::Corrupted(format!("Archive is corrupted: {error}")).into(),
+            Err(error) => error,
+        }
+    }
+}
+
+impl fmt::Display for PackagerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated(message)
+            | Self::BadMagic(message)
+            | Self::ChecksumMismatch(message)
+            | Self::UnsupportedVersion(message)
+            | Self::Corrupted(message) => f.write_str(message),
+            Self::UnsafePath(error) => error.fmt(f),
+            Self::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+/// 不安全路径和I/O错误以包装的内部错误为来源，调用方可以沿来源链取得原始错误
+impl std::error::Error for PackagerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::UnsafePath(error) => Some(error),
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// 读取数据时的I/O错误: 提前结束为截断；分卷和解密读取器报告的数据错误以及 FrameDecoder 识别的帧校验和错误保持原有类型；
+/// zstd以不带系统错误码的 Other 错误报告其他帧数据错误，归为损坏
+impl From<io::Error> for PackagerError {
+    fn from(error: io::Error) -> Self {
+        if error.get_ref().is_some_and(|inner| inner.is::<PackagerError>()) {
+            let inner = error.into_inner().expect("checked above");
+            return *inner.downcast::<PackagerError>().expect("checked above");
+        }
+        match error.kind() {
+            ErrorKind::UnexpectedEof => Self::truncated(),
+            ErrorKind::InvalidData => Self::Corrupted(format!("Archive is corrupted: {error}")),
+            ErrorKind::Other if error.raw_os_error().is_none() => {
+                Self::Corrupted(format!("Archive is corrupted: invalid compressed data: {error}"))
+            }
+            _ => Self::Io(error),
+        }
+    }
+}
+
+impl From<UnsafePathError> for PackagerError {
+    fn from(error: UnsafePathError) -> Self {
+        Self::UnsafePath(error)
+    }
+}
+
+/// 在I/O错误中携带 PackagerError，用于 Read 和 Seek 实现中报告数据错误
+impl From<PackagerError> for io::Error {
+    fn from(error: PackagerError) -> Self {
+        match error {
+            PackagerError::Io(error) => error,
+            PackagerError::Truncated(_) => io::Error::new(ErrorKind::UnexpectedEof, error),
+            _ => io::Error::new(ErrorKind::InvalidData, error),
+        }
+    }
+}
+
+/// 包装zstd解码器，把帧校验和不符的错误报告为 ChecksumMismatch
+///
+/// zstd只以错误名称区分帧错误，这里按帧校验和错误的错误码取得其名称后比较。
+pub struct FrameDecoder<R>(R);
+
+impl<R: Read> FrameDecoder<R> {
+    /// 包装已配置好的zstd解码器
+    pub fn new(decoder: R) -> Self {
+        Self(decoder)
+    }
+}
+
+impl<R: Read> Read for FrameDecoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf).map_err(|error| {
+            if is_checksum_error(&error) {
+                PackagerError::ChecksumMismatch(format!("Compressed data checksum mismatch: {error}")).into()
+            } else {
+                error
+            }
+        })
+    }
+}
+
+/// zstd解码器报告的帧校验和错误
+fn is_checksum_error(error: &io::Error) -> bool {
+    let code = 0usize.wrapping_sub(ZSTD_ErrorCode::ZSTD_error_checksum_wrong as usize);
+    error.kind() == ErrorKind::Other
+        && error.raw_os_error().is_none()
+        && error.get_ref().is_some_and(|inner| inner.to_string() == zstd_safe::get_error_name(code))
+}
@@ -0,0 +1,60 @@
+// SeeSea Self-Contained Installer - Packager Filter Module
+// 模块名称: packager::filter
+// 职责范围: 按gitignore风格的规则决定打包时跳过哪些条目
+// 已实现功能: 读取源目录根部的 .installerignore，合并 PackOptions 中的排除和包含规则，匹配目录和文件
+// 使用依赖: anyhow, ignore, std::path, options
+// 主要接口: PackFilter, PackFilter::new, PackFilter::is_excluded, IGNORE_FILE
+// 注意事项: 规则按 .installerignore、exclude、include 的顺序生效，后面的规则覆盖前面的；
+//           与git相同，目录被排除后其中的条目不再检查，不能单独重新包含
+//
+// 规则语法与 .gitignore 相同: '#'开头为注释，'!'开头为重新包含，'/'结尾只匹配目录，
+// 以'/'开头或中间带'/'的规则相对源目录根部匹配，否则匹配任意层级的名称
+
+use super::options::PackOptions;
+use anyhow::Result;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::Path;
+
+/// 源目录根部的忽略规则文件名
+pub const IGNORE_FILE: &str = ".installerignore";
+
+/// 打包条目过滤器
+pub struct PackFilter {
+    matcher: Gitignore,
+}
+
+impl PackFilter {
+    /// 由源目录中的 .installerignore 和打包参数中的规则创建过滤器
+    pub fn new(source_dir: &Path, options: &PackOptions) -> Result<Self> {
+        let mut builder = GitignoreBuilder::new(source_dir);
+
+        let ignore_file = source_dir.join(IGNORE_FILE);
+        if options.use_ignore_file
+            && ignore_file.is_file()
+            && let Some(e) = builder.add(&ignore_file)
+        {
+            anyhow::bail!("Invalid ignore file {ignore_file:?}: {e}");
+        }
+        for pattern in &options.exclude {
+            builder
+                .add_line(None, pattern)
+                .map_err(|e| anyhow::anyhow!("Invalid exclude pattern {pattern:?}: {e}"))?;
+        }
+        for pattern in &options.include {
+            builder
+                .add_line(None, &format!("!{pattern}"))
+                .map_err(|e| anyhow::anyhow!("Invalid include pattern {pattern:?}: {e}"))?;
+        }
+
+        let matcher = builder.build()?;
+        Ok(Self { matcher })
+    }
+
+    /// 相对源目录的路径是否被排除；规则文件本身总是被排除
+    pub fn is_excluded(&self, relative_path: &Path, is_dir: bool) -> bool {
+        if relative_path == Path::new(IGNORE_FILE) {
+            return true;
+        }
+        self.matcher.matched(relative_path, is_dir).is_ignore()
+    }
+}
@@ -0,0 +1,374 @@
+// SeeSea Self-Contained Installer - Packager Format Module
+// 模块名称: packager::format
+// 职责范围: 定义安装包容器格式（文件头、数据帧、目录表、文件尾）及其编解码
+// 已实现功能: 魔数与格式版本、目录表（TOC）条目编解码、文件尾、格式识别、摘要记录
+// 使用依赖: anyhow, std::io, std::path, checksum, error
+// 主要接口: ArchiveEntry, EntryKind, CompressionMethod, Header, Footer, write_header, read_header, write_footer, read_footer, detect_format
+// 注意事项: 所有整数均以小端序存储
+//
+// 容器布局（格式版本 1，可随机访问）:
+//   [未压缩]  MAGIC(8) | FORMAT_VERSION(u16) | flags(u16)
+//   [数据帧]  每个有数据的条目各自一个独立的数据帧（普通文件为内容，符号链接为链接目标）
+//   [目录表]  一个zstd帧: entry_count(u64) | TOC条目 * entry_count
+//   [未压缩]  toc_offset(u64) | toc_size(u64) | archive_digest(32) | FOOTER_MAGIC(8)
+//   [签名块]  flags带FLAG_SIGNED时存在，格式见signing模块
+//
+// TOC条目: path_len(u32) | path(UTF-8, '/'分隔) | kind(u8) | method(u8) | mode(u32) | mtime(i64)
+//          | size(u64) | offset(u64) | compressed_size(u64) | digest(32)
+// 目录条目没有数据帧，offset和compressed_size为0。digest为未压缩数据的SHA-256。
+// method为0时数据帧是zstd帧；为1时数据原样存储，compressed_size等于size。
+// 内容相同的文件条目可以共享同一个数据帧（offset、compressed_size、method和digest均相同）。
+// archive_digest 覆盖文件开头到目录表结束的全部字节。flags为标志位，未知标志位拒绝读取。
+// 在此之前只有旧版无文件头格式（见legacy模块），由开头的zstd帧魔数识别。
+
+use super::checksum::{self, DIGEST_LEN, Digest};
+use super::encryption::ENCRYPTED_MAGIC;
+use super::error::PackagerError;
+use super::volume::VOLUME_MAGIC;
+use anyhow::Result;
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Component, Path};
+
+/// 安装包文件头魔数
+pub const MAGIC: [u8; 8] = *b"SEESEAPK";
+
+/// 文件尾魔数
+pub const FOOTER_MAGIC: [u8; 8] = *b"SSPK-END";
+
+/// 当前写入的格式版本
+pub const FORMAT_VERSION: u16 = 1;
+
+/// 标志位: 安装包带有签名块
+pub const FLAG_SIGNED: u16 = 0x0001;
+
+/// 已知的标志位
+const KNOWN_FLAGS: u16 = FLAG_SIGNED;
+
+/// 文件头长度（字节）
+pub const HEADER_LEN: u64 = 12;
+
+/// 文件尾长度（字节）
+pub const FOOTER_LEN: u64 = 8 + 8 + DIGEST_LEN as u64 + 8;
+
+/// zstd帧魔数，用于识别旧版无文件头安装包
+pub const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// 清单中允许的最大路径长度（字节）
+pub const MAX_PATH_LEN: usize = 4096;
+
+/// 安装包格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// 带文件头的原生格式
+    Native,
+    /// 旧版无文件头格式（纯zstd流）
+    Legacy,
+    /// 拆分为多个分卷的原生格式，格式见volume模块
+    Volume,
+    /// 使用口令加密的原生格式，格式见encryption模块
+    Encrypted,
+    /// 无法识别的数据
+    Unknown,
+}
+
+/// 条目类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EntryKind {
+    /// 普通文件
+    #[default]
+    File,
+    /// 目录
+    Directory,
+    /// 符号链接
+    Symlink,
+}
+
+impl EntryKind {
+    /// 转换为存储的类型标记
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::File => 0,
+            Self::Directory => 1,
+            Self::Symlink => 2,
+        }
+    }
+
+    /// 从存储的类型标记解析
+    pub fn from_u8(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::File),
+            1 => Ok(Self::Directory),
+            2 => Ok(Self::Symlink),
+            _ => Err(PackagerError::Corrupted(format!("Unknown archive entry type: {value}")).into()),
+        }
+    }
+}
+
+/// 条目数据的压缩方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompressionMethod {
+    /// zstd压缩
+    #[default]
+    Zstd,
+    /// 原样存储，用于已压缩过的数据
+    Stored,
+}
+
+impl CompressionMethod {
+    /// 转换为存储的压缩方式标记
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::Zstd => 0,
+            Self::Stored => 1,
+        }
+    }
+
+    /// 从存储的压缩方式标记解析
+    pub fn from_u8(value: u8) -> Result<Self> {
+        match value {
+            0 => Ok(Self::Zstd),
+            1 => Ok(Self::Stored),
+            _ => Err(PackagerError::Corrupted(format!("Unknown archive compression method: {value}")).into()),
+        }
+    }
+}
+
+/// 目录表条目
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// 相对路径，使用'/'分隔
+    pub path: String,
+    /// 条目类型
+    pub kind: EntryKind,
+    /// 数据帧的压缩方式
+    pub method: CompressionMethod,
+    /// Unix权限位
+    pub mode: u32,
+    /// 修改时间（Unix时间戳，秒）
+    pub mtime: i64,
+    /// 数据大小（字节），符号链接为链接目标的长度，目录为0
+    pub size: u64,
+    /// 数据帧在安装包中的偏移
+    pub offset: u64,
+    /// 数据帧的压缩后大小
+    pub compressed_size: u64,
+    /// 未压缩数据的SHA-256摘要
+    pub digest: Digest,
+}
+
+impl ArchiveEntry {
+    /// 条目是否带有数据帧
+    pub fn has_data(&self) -> bool {
+        self.kind != EntryKind::Directory
+    }
+
+    /// 将目录表条目写入流
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        if self.path.len() > MAX_PATH_LEN {
+            anyhow::bail!("Archive path too long: {}", self.path);
+        }
+
+        writer.write_all(&(self.path.len() as u32).to_le_bytes())?;
+        writer.write_all(self.path.as_bytes())?;
+        writer.write_all(&[self.kind.to_u8(), self.method.to_u8()])?;
+        writer.write_all(&self.mode.to_le_bytes())?;
+        writer.write_all(&self.mtime.to_le_bytes())?;
+        writer.write_all(&self.size.to_le_bytes())?;
+        writer.write_all(&self.offset.to_le_bytes())?;
+        writer.write_all(&self.compressed_size.to_le_bytes())?;
+        writer.write_all(&self.digest)?;
+        Ok(())
+    }
+
+    /// 从流中读取目录表条目
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let path_len = read_u32(reader)? as usize;
+        if path_len > MAX_PATH_LEN {
+            return Err(PackagerError::Corrupted(format!("Archive path length {path_len} exceeds limit of {MAX_PATH_LEN} bytes")).into());
+        }
+
+        let mut path_buf = vec![0u8; path_len];
+        read_exact(reader, &mut path_buf)?;
+        let path = String::from_utf8(path_buf)?;
+
+        let mut tag_buf = [0u8; 2];
+        read_exact(reader, &mut tag_buf)?;
+        let kind = EntryKind::from_u8(tag_buf[0])?;
+        let method = CompressionMethod::from_u8(tag_buf[1])?;
+
+        let mode = read_u32(reader)?;
+        let mtime = read_u64(reader)? as i64;
+        let size = read_u64(reader)?;
+        let offset = read_u64(reader)?;
+        let compressed_size = read_u64(reader)?;
+        let digest = read_digest(reader)?;
+
+        Ok(Self { path, kind, method, mode, mtime, size, offset, compressed_size, digest })
+    }
+}
+
+/// 文件尾
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Footer {
+    /// 目录表偏移
+    pub toc_offset: u64,
+    /// 目录表压缩后大小
+    pub toc_size: u64,
+    /// 整包摘要
+    pub archive_digest: Digest,
+}
+
+/// 文件头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    /// 格式版本
+    pub version: u16,
+    /// 标志位
+    pub flags: u16,
+}
+
+/// 写入文件头
+pub fn write_header<W: Write>(writer: &mut W, flags: u16) -> Result<()> {
+    writer.write_all(&MAGIC)?;
+    writer.write_all(&FORMAT_VERSION.to_le_bytes())?;
+    writer.write_all(&flags.to_le_bytes())?;
+    Ok(())
+}
+
+/// 读取文件头（魔数之后的部分）
+pub fn read_header<R: Read>(reader: &mut R) -> Result<Header> {
+    read_versioned_header(reader, FORMAT_VERSION, "archive format")
+}
+
+/// 读取与安装包布局相同的文件头（魔数之后的部分），版本必须为 supported，kind 用于错误信息
+pub fn read_versioned_header<R: Read>(reader: &mut R, supported: u16, kind: &str) -> Result<Header> {
+    let version = read_u16(reader)?;
+    let flags = read_u16(reader)?;
+
+    if version != supported {
+        return Err(PackagerError::UnsupportedVersion(format!("Unsupported {kind} version: {version} (supported: {supported})")).into());
+    }
+    if flags & !KNOWN_FLAGS != 0 {
+        return Err(PackagerError::UnsupportedVersion(format!("Unsupported archive flags: {flags:#06x}")).into());
+    }
+
+    Ok(Header { version, flags })
+}
+
+/// 写入文件尾
+pub fn write_footer<W: Write>(writer: &mut W, footer: &Footer) -> Result<()> {
+    writer.write_all(&footer.toc_offset.to_le_bytes())?;
+    writer.write_all(&footer.toc_size.to_le_bytes())?;
+    writer.write_all(&footer.archive_digest)?;
+    writer.write_all(&FOOTER_MAGIC)?;
+    Ok(())
+}
+
+/// 读取文件尾
+pub fn read_footer<R: Read>(reader: &mut R) -> Result<Footer> {
+    let toc_offset = read_u64(reader)?;
+    let toc_size = read_u64(reader)?;
+    let archive_digest = read_digest(reader)?;
+
+    let mut magic = [0u8; 8];
+    read_exact(reader, &mut magic)?;
+    if magic != FOOTER_MAGIC {
+        return Err(PackagerError::Truncated("Archive is truncated or corrupted: missing end-of-archive footer".to_string()).into());
+    }
+
+    Ok(Footer { toc_offset, toc_size, archive_digest })
+}
+
+/// 比较数据摘要与目录表中记录的摘要
+pub fn verify_digest(path: &str, expected: &Digest, actual: &Digest) -> Result<()> {
+    if expected != actual {
+        return Err(PackagerError::ChecksumMismatch(format!(
+            "Checksum mismatch for {path}: expected {}, got {}",
+            checksum::to_hex(expected),
+            checksum::to_hex(actual)
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// 根据文件开头的字节识别安装包格式
+pub fn detect_format(prefix: &[u8]) -> ArchiveFormat {
+    if prefix.starts_with(&MAGIC) {
+        ArchiveFormat::Native
+    } else if prefix.starts_with(&ZSTD_MAGIC) {
+        ArchiveFormat::Legacy
+    } else if prefix.starts_with(&VOLUME_MAGIC) {
+        ArchiveFormat::Volume
+    } else if prefix.starts_with(&ENCRYPTED_MAGIC) {
+        ArchiveFormat::Encrypted
+    } else {
+        ArchiveFormat::Unknown
+    }
+}
+
+/// 将相对路径转换为安装包内的'/'分隔路径
+pub fn archive_path(relative_path: &Path) -> Result<String> {
+    let mut parts = Vec::new();
+    for component in relative_path.components() {
+        match component {
+            Component::Normal(part) => {
+                let part = part
+                    .to_str()
+                    .ok_or_else(|| anyhow::anyhow!("Path is not valid UTF-8: {relative_path:?}"))?;
+                parts.push(part);
+            }
+            Component::CurDir => {}
+            _ => anyhow::bail!("Path is not a plain relative path: {relative_path:?}"),
+        }
+    }
+    Ok(parts.join("/"))
+}
+
+/// 读取定长数据，提前结束时报告安装包被截断
+pub fn read_exact<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<()> {
+    reader.read_exact(buf).map_err(|e| {
+        if e.kind() == ErrorKind::UnexpectedEof {
+            PackagerError::truncated().into()
+        } else {
+            e.into()
+        }
+    })
+}
+
+/// 从流中精确复制size字节，不按声明的大小分配内存，数据不足时报告安装包被截断
+pub fn copy_exact<R: Read, W: Write>(reader: &mut R, writer: &mut W, size: u64) -> Result<()> {
+    let copied = std::io::copy(&mut reader.take(size), writer)?;
+    if copied != size {
+        return Err(PackagerError::truncated().into());
+    }
+    Ok(())
+}
+
+/// 读取SHA-256摘要
+pub fn read_digest<R: Read>(reader: &mut R) -> Result<Digest> {
+    let mut digest = [0u8; DIGEST_LEN];
+    read_exact(reader, &mut digest)?;
+    Ok(digest)
+}
+
+/// 读取小端序u16
+pub fn read_u16<R: Read>(reader: &mut R) -> Result<u16> {
+    let mut buf = [0u8; 2];
+    read_exact(reader, &mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+/// 读取小端序u32
+pub fn read_u32<R: Read>(reader: &mut R) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    read_exact(reader, &mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// 读取小端序u64
+pub fn read_u64<R: Read>(reader: &mut R) -> Result<u64> {
+    let mut buf = [0u8; 8];
+    read_exact(reader, &mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
@@ -0,0 +1,73 @@
+// SeeSea Self-Contained Installer - Packager Legacy Module
+// 模块名称: packager::legacy
+// 职责范围: 读取旧版无文件头的安装包
+// 已实现功能: 旧版记录流解压
+// 使用依赖: anyhow, log, zstd, std::fs, std::io, std::path, error, format, sanitize
+// 主要接口: unpack_legacy
+// 注意事项: 旧版格式为纯zstd流，记录为 path_len(u32) | path | size(u64) | bytes，没有结束标记
+
+use super::error::{FrameDecoder, PackagerError};
+use super::format::{self, MAX_PATH_LEN};
+use super::sanitize;
+use anyhow::Result;
+use log::debug;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zstd::stream::Decoder;
+
+/// 解压旧版安装包到已规范化的目标目录，返回解压的文件数
+pub fn unpack_legacy<R: Read>(input: R, root: &Path) -> Result<usize> {
+    let mut decoder = FrameDecoder::new(Decoder::new(input)?);
+
+    let mut file_count = 0;
+    loop {
+        // 读取文件路径长度，在记录边界处遇到流结束表示正常结束
+        let mut path_len_buf = [0u8; 4];
+        let read_len = read_full(&mut decoder, &mut path_len_buf)?;
+        if read_len == 0 {
+            break;
+        }
+        if read_len < path_len_buf.len() {
+            return Err(PackagerError::truncated().into());
+        }
+
+        let path_len = u32::from_le_bytes(path_len_buf) as usize;
+        if path_len > MAX_PATH_LEN {
+            return Err(PackagerError::Corrupted(format!("Archive path length {path_len} exceeds limit of {MAX_PATH_LEN} bytes")).into());
+        }
+
+        // 读取文件路径
+        let mut path_buf = vec![0u8; path_len];
+        format::read_exact(&mut decoder, &mut path_buf)?;
+        let path_str = String::from_utf8(path_buf)?;
+
+        // 校验路径并创建父目录
+        let file_path = sanitize::prepare_entry_target(root, &path_str)?;
+
+        // 读取文件大小
+        let file_size = format::read_u64(&mut decoder)?;
+
+        // 流式写入文件内容
+        let mut output_file = File::create(&file_path)?;
+        format::copy_exact(&mut decoder, &mut output_file, file_size)?;
+
+        file_count += 1;
+        debug!("Extracted legacy file: {file_path:?}");
+    }
+
+    Ok(file_count)
+}
+
+/// 尽量读满缓冲区，返回实际读取的字节数
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..])?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
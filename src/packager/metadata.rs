@@ -0,0 +1,96 @@
+// SeeSea Self-Contained Installer - Packager Metadata Module
+// 模块名称: packager::metadata
+// 职责范围: 采集和恢复安装包条目的文件系统元数据
+// 已实现功能: 权限位和修改时间的采集与恢复、符号链接的读取与创建
+// 使用依赖: anyhow, filetime, std::fs, std::path
+// 主要接口: entry_mode, entry_mtime, symlink_target, set_permissions, set_mtime, create_symlink
+// 注意事项: 非Unix平台只能近似恢复权限（只读标志），符号链接在Windows上可能需要额外权限
+
+use anyhow::Result;
+use filetime::FileTime;
+use std::fs::{self, Metadata};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// 恢复权限时保留的权限位，不恢复setuid/setgid/sticky位
+const RESTORED_MODE_MASK: u32 = 0o777;
+
+/// 获取条目的Unix权限位
+#[cfg(unix)]
+pub fn entry_mode(metadata: &Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o7777
+}
+
+/// 获取条目的Unix权限位
+#[cfg(not(unix))]
+pub fn entry_mode(metadata: &Metadata) -> u32 {
+    let base = if metadata.is_dir() { 0o755 } else { 0o644 };
+    if metadata.permissions().readonly() { base & 0o555 } else { base }
+}
+
+/// 获取条目修改时间（Unix时间戳，秒）
+pub fn entry_mtime(metadata: &Metadata) -> i64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// 读取符号链接目标，统一使用'/'分隔
+pub fn symlink_target(path: &Path) -> Result<String> {
+    let target = fs::read_link(path)?;
+    let target = target
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Symlink target is not valid UTF-8: {path:?}"))?;
+    Ok(target.replace('\\', "/"))
+}
+
+/// 恢复权限位
+#[cfg(unix)]
+pub fn set_permissions(path: &Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & RESTORED_MODE_MASK))?;
+    Ok(())
+}
+
+/// 恢复权限位，非Unix平台仅根据属主写权限设置只读标志
+#[cfg(not(unix))]
+pub fn set_permissions(path: &Path, mode: u32) -> Result<()> {
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_readonly(mode & RESTORED_MODE_MASK & 0o200 == 0);
+    fs::set_permissions(path, permissions)?;
+    Ok(())
+}
+
+/// 恢复修改时间，对符号链接设置链接本身的时间
+pub fn set_mtime(path: &Path, mtime: i64) -> Result<()> {
+    let time = FileTime::from_unix_time(mtime, 0);
+    if fs::symlink_metadata(path)?.file_type().is_symlink() {
+        filetime::set_symlink_file_times(path, time, time)?;
+    } else {
+        filetime::set_file_mtime(path, time)?;
+    }
+    Ok(())
+}
+
+/// 创建符号链接
+#[cfg(unix)]
+pub fn create_symlink(target: &str, link: &Path) -> Result<()> {
+    std::os::unix::fs::symlink(target, link)?;
+    Ok(())
+}
+
+/// 创建符号链接，根据链接目标当前是否为目录选择链接类型
+#[cfg(windows)]
+pub fn create_symlink(target: &str, link: &Path) -> Result<()> {
+    let resolved = link.parent().map(|parent| parent.join(target));
+    if resolved.is_some_and(|path| path.is_dir()) {
+        std::os::windows::fs::symlink_dir(target, link)?;
+    } else {
+        std::os::windows::fs::symlink_file(target, link)?;
+    }
+    Ok(())
+}
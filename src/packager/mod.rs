@@ -0,0 +1,532 @@
+// SeeSea Self-Contained Installer - Packager Module
+// 模块名称: packager
+// 职责范围: 处理安装文件的zstd打包和解包
+// 期望实现计划: 
+// 1. 实现zstd压缩功能
+// 2. 实现zstd解压功能
+// 3. 实现目录打包功能
+// 4. 实现目录解压功能
+// 已实现功能: zstd压缩和解压功能、带文件头和目录表的容器格式、旧版安装包读取、SHA-256校验、解压路径安全检查、权限/时间/符号链接/空目录保留、流式处理、单条目/子目录随机访问解压、按目录表修复损坏文件、已压缩文件原样存储、内容去重和硬链接解压、可重现打包、按 .installerignore 和打包参数排除条目、安装包嵌入可执行文件（自解压）、Ed25519签名与验证、版本间增量补丁包、与zip和tar.zst互相转换、进度回调、按大小拆分为分卷和分卷拼接、口令认证加密、区分截断/损坏/I/O的错误类型
+// 使用依赖: zstd, sha2, filetime, anyhow, log, std::fs, std::path, walkdir
+// 主要接口: pack_directory, pack_directory_with, PackSummary, compress_file_with, decompress_file_with, unpack_directory, unpack_directory_with, unpack_paths, repair_directory, verify_signature, verify_signature_with, verify_archive, verify_archive_with, bundle_installer, read_manifest, read_manifest_with, is_encrypted, create_delta, apply_delta, import_archive, export_archive
+// 注意事项: 使用zstd算法进行高效压缩，压缩参数见options模块，容器格式定义见format模块；文件内容始终经由定长缓冲区流式处理，不整体读入内存
+
+pub mod checksum;
+pub mod convert;
+pub mod delta;
+pub mod embed;
+pub mod encryption;
+pub mod error;
+pub mod filter;
+pub mod format;
+mod legacy;
+pub mod metadata;
+pub mod options;
+pub mod progress;
+pub mod reader;
+pub mod sanitize;
+pub mod signing;
+pub mod volume;
+pub mod writer;
+
+pub use convert::{ForeignFormat, export_archive, import_archive};
+pub use delta::{apply_delta, create_delta};
+pub use encryption::Passphrase;
+pub use error::PackagerError;
+pub use filter::PackFilter;
+pub use format::{ArchiveEntry, ArchiveFormat, CompressionMethod, EntryKind};
+pub use options::{PackOptions, UnpackOptions};
+pub use progress::{Progress, ProgressHandler};
+pub use reader::ArchiveReader;
+pub use sanitize::{UnsafePathError, UnsafePathReason};
+pub use signing::SignaturePolicy;
+pub use writer::ArchiveWriter;
+
+use anyhow::Result;
+use checksum::HashingReader;
+use encryption::{DecryptingReader, PayloadWriter};
+use progress::{ProgressReader, ProgressTracker};
+use reader::ArchiveSource;
+use volume::{VolumeReader, VolumeWriter};
+use log::{debug, info, warn};
+use std::collections::HashMap;
+use std::fs::{File, create_dir_all};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+use zstd::stream::Decoder;
+
+/// 打包结果摘要
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackSummary {
+    /// 写入的条目数
+    pub entries: usize,
+    /// 与其他文件共享数据帧的条目数
+    pub deduplicated: usize,
+    /// 被忽略规则跳过的路径，目录以'/'结尾，其中的条目不再单独列出
+    pub skipped: Vec<String>,
+    /// 拆分打包时写入的分卷文件，未拆分时为空
+    pub volumes: Vec<PathBuf>,
+}
+
+/// 打包目录为zstd压缩文件
+pub fn pack_directory(source_dir: &Path, output_file: &Path) -> Result<PackSummary> {
+    pack_directory_with(source_dir, output_file, &PackOptions::default())
+}
+
+/// 使用指定压缩参数打包目录
+pub fn pack_directory_with(source_dir: &Path, output_file: &Path, options: &PackOptions) -> Result<PackSummary> {
+    info!("Packaging directory {source_dir:?} to {output_file:?} using zstd (level {}, {} threads)", options.level, options.threads);
+    let options = &options.with_resolved_epoch()?;
+    
+    let (entries, skipped) = collect_entries(source_dir, options)?;
+    
+    // 创建输出文件，写入器负责文件头、数据帧、目录表和文件尾；指定口令时加密整个安装包，
+    // 指定分卷大小时按大小拆分为多个分卷文件（先加密后拆分）
+    let mut summary = PackSummary { skipped, ..Default::default() };
+    let passphrase = options.passphrase.as_ref();
+    match options.volume_size {
+        None => {
+            let output = PayloadWriter::new(io::BufWriter::new(File::create(output_file)?), passphrase)?;
+            let mut writer = ArchiveWriter::with_options(output, options.clone())?;
+            write_entries(&mut writer, entries, options)?;
+            (summary.entries, summary.deduplicated) = (writer.entries().len(), writer.deduplicated());
+            writer.finish()?.finish()?.into_inner()?.sync_all()?;
+        }
+        Some(volume_size) => {
+            let output = PayloadWriter::new(VolumeWriter::create(output_file, volume_size)?, passphrase)?;
+            let mut writer = ArchiveWriter::with_options(output, options.clone())?;
+            write_entries(&mut writer, entries, options)?;
+            (summary.entries, summary.deduplicated) = (writer.entries().len(), writer.deduplicated());
+            summary.volumes = writer.finish()?.finish()?.finish()?;
+            info!("Split archive into {} volumes of at most {volume_size} bytes", summary.volumes.len());
+        }
+    }
+    
+    for path in &summary.skipped {
+        info!("Skipped by ignore rules: {path}");
+    }
+    info!(
+        "Successfully packaged {} entries ({} deduplicated, {} skipped) to {output_file:?}",
+        summary.entries,
+        summary.deduplicated,
+        summary.skipped.len()
+    );
+    Ok(summary)
+}
+
+/// 按顺序读取源文件并写入条目
+fn write_entries<W: io::Write>(writer: &mut ArchiveWriter<W>, entries: Vec<SourceEntry>, options: &PackOptions) -> Result<()> {
+    // 只有大小与其他文件相同的文件才可能重复，只对这些文件预先计算摘要
+    let mut size_counts: HashMap<u64, usize> = HashMap::new();
+    if options.deduplicate {
+        for (entry, _) in entries.iter().filter(|(entry, _)| entry.kind == EntryKind::File && entry.size > 0) {
+            *size_counts.entry(entry.size).or_default() += 1;
+        }
+    }
+    
+    // 进度按文件内容的字节数计算
+    let total = entries.iter().filter(|(entry, _)| entry.kind == EntryKind::File).map(|(entry, _)| entry.size).sum();
+    let mut tracker = ProgressTracker::new(&options.progress, Some(total));
+    
+    for (mut archive_entry, source_path) in entries {
+        match archive_entry.kind {
+            EntryKind::Symlink => {
+                tracker.advance(Some(&archive_entry.path), 0);
+                let target = metadata::symlink_target(&source_path)?;
+                archive_entry.size = target.len() as u64;
+                writer.add_entry(archive_entry, target.as_bytes())?;
+            }
+            EntryKind::Directory => {
+                tracker.advance(Some(&archive_entry.path), 0);
+                writer.add_entry(archive_entry, io::empty())?;
+            }
+            EntryKind::File => {
+                // 读取开头的采样数据选择压缩方式，再通过定长缓冲区流式写入，不整体读入内存
+                let mut file = File::open(&source_path)?;
+                let mut sample = Vec::with_capacity(options::ENTROPY_SAMPLE_LEN);
+                Read::by_ref(&mut file).take(options::ENTROPY_SAMPLE_LEN as u64).read_to_end(&mut sample)?;
+                archive_entry.method = options.choose_method(&archive_entry.path, &sample);
+                
+                let path = archive_entry.path.clone();
+                let end = tracker.processed() + archive_entry.size;
+                let data = ProgressReader::new(sample.as_slice().chain(file), &mut tracker, Some(&path));
+                if size_counts.get(&archive_entry.size).is_some_and(|&count| count > 1) {
+                    let digest = file_digest(&source_path)?;
+                    writer.add_deduplicated(archive_entry, &digest, data)?;
+                } else {
+                    writer.add_entry(archive_entry, data)?;
+                }
+                // 引用已有数据帧的文件不会读取数据，直接计入进度
+                tracker.advance_to(Some(&path), end);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 待打包的条目及其源路径
+type SourceEntry = (ArchiveEntry, PathBuf);
+
+/// 遍历目录生成条目列表及其源路径；不跟随符号链接，链接本身作为条目记录
+///
+/// 同一目录下的条目按文件名排序，顺序不依赖文件系统。文件条目只填写大小，摘要和数据在写入时计算。
+/// 被忽略规则排除的路径单独返回，被排除的目录不再进入。
+fn collect_entries(source_dir: &Path, options: &PackOptions) -> Result<(Vec<SourceEntry>, Vec<String>)> {
+    let filter = PackFilter::new(source_dir, options)?;
+    let mut entries = Vec::new();
+    let mut skipped = Vec::new();
+    let walker = WalkDir::new(source_dir).min_depth(1).sort_by_file_name().into_iter().filter_entry(|entry| {
+        let Ok(relative_path) = entry.path().strip_prefix(source_dir) else {
+            return true;
+        };
+        let is_dir = entry.file_type().is_dir();
+        if !filter.is_excluded(relative_path, is_dir) {
+            return true;
+        }
+        // 规则文件本身不打包，也不列入跳过的路径
+        if relative_path != Path::new(filter::IGNORE_FILE) {
+            let path = format::archive_path(relative_path).unwrap_or_else(|_| relative_path.to_string_lossy().into_owned());
+            skipped.push(if is_dir { format!("{path}/") } else { path });
+        }
+        false
+    });
+    for entry in walker {
+        let entry = entry?;
+        let source_path = entry.path();
+        let relative_path = source_path.strip_prefix(source_dir)?;
+        let entry_metadata = entry.metadata()?;
+        let file_type = entry.file_type();
+        
+        let kind = if file_type.is_symlink() {
+            EntryKind::Symlink
+        } else if file_type.is_dir() {
+            EntryKind::Directory
+        } else if file_type.is_file() {
+            EntryKind::File
+        } else {
+            warn!("Skipping special file: {source_path:?}");
+            continue;
+        };
+        
+        let mut archive_entry = ArchiveEntry {
+            path: format::archive_path(relative_path)?,
+            kind,
+            mode: metadata::entry_mode(&entry_metadata),
+            mtime: metadata::entry_mtime(&entry_metadata),
+            size: if kind == EntryKind::File { entry_metadata.len() } else { 0 },
+            ..Default::default()
+        };
+        options.normalize_entry(&mut archive_entry);
+        entries.push((archive_entry, source_path.to_path_buf()));
+    }
+    Ok((entries, skipped))
+}
+
+/// 从zstd压缩文件解压到目录
+pub fn unpack_directory(input_file: &Path, output_dir: &Path) -> Result<()> {
+    unpack_directory_with(input_file, output_dir, &UnpackOptions::default())
+}
+
+/// 使用指定解压参数解压到目录
+pub fn unpack_directory_with(input_file: &Path, output_dir: &Path, options: &UnpackOptions) -> Result<()> {
+    info!("Unpacking {input_file:?} to {output_dir:?} using zstd");
+    
+    // 创建输出目录，所有条目都必须落在其规范化路径之内
+    create_dir_all(output_dir)?;
+    let root = std::fs::canonicalize(output_dir)?;
+    
+    // 打开输入文件并识别格式，拆分的安装包从第一个分卷识别
+    let mut input = open_file(input_file)?;
+    let file_count = if identify(&mut input)? == ArchiveFormat::Legacy {
+        // 旧版安装包没有签名
+        options.signature.check(None, &[0u8; checksum::DIGEST_LEN])?;
+        warn!("Archive has no header, reading it as a legacy payload without checksums");
+        input.seek(SeekFrom::Start(0))?;
+        legacy::unpack_legacy(input, &root).map_err(PackagerError::classify)?
+    } else {
+        // 完整解压前先验证签名并校验整包摘要，检测篡改、截断和损坏
+        let mut reader = open_reader(input_file, options.passphrase.as_ref())?;
+        reader.verify(&options.signature)?;
+        reader.extract(&root, options, |_| true)?
+    };
+    
+    info!("Successfully unpacked {file_count} entries to {output_dir:?}");
+    Ok(())
+}
+
+/// 只解压指定的条目，paths 中的目录会连同其下所有条目一起解压
+///
+/// 只解压被选中条目的数据帧，返回解压的条目数；验证签名时仍需读取整个安装包计算摘要。
+pub fn unpack_paths(input_file: &Path, output_dir: &Path, paths: &[&str], options: &UnpackOptions) -> Result<usize> {
+    info!("Unpacking {paths:?} from {input_file:?} to {output_dir:?}");
+    
+    let mut reader = open_reader(input_file, options.passphrase.as_ref())?;
+    if options.signature.is_enforced() {
+        reader.verify(&options.signature)?;
+    }
+    for path in paths {
+        if !reader.entries().iter().any(|entry| is_within(&entry.path, path)) {
+            anyhow::bail!("Entry not found in archive: {path}");
+        }
+    }
+    
+    create_dir_all(output_dir)?;
+    let root = std::fs::canonicalize(output_dir)?;
+    let count = reader.extract(&root, options, |entry| paths.iter().any(|path| is_within(&entry.path, path)))?;
+    
+    info!("Successfully unpacked {count} entries to {output_dir:?}");
+    Ok(count)
+}
+
+/// 对照安装包检查目录，只重新解压缺失或内容不符的条目，返回被修复的条目路径
+pub fn repair_directory(input_file: &Path, target_dir: &Path, options: &UnpackOptions) -> Result<Vec<String>> {
+    info!("Checking {target_dir:?} against {input_file:?}");
+    
+    let mut reader = open_reader(input_file, options.passphrase.as_ref())?;
+    if options.signature.is_enforced() {
+        reader.verify(&options.signature)?;
+    }
+    create_dir_all(target_dir)?;
+    let root = std::fs::canonicalize(target_dir)?;
+    
+    let mut damaged = Vec::new();
+    for entry in reader.entries() {
+        if !entry_is_intact(&root, entry)? {
+            debug!("Damaged or missing entry: {}", entry.path);
+            damaged.push(entry.path.clone());
+        }
+    }
+    
+    if !damaged.is_empty() {
+        reader.extract(&root, options, |entry| damaged.contains(&entry.path))?;
+    }
+    
+    info!("Repaired {} entries in {target_dir:?}", damaged.len());
+    Ok(damaged)
+}
+
+/// 按签名策略验证安装包签名并校验整包摘要，不解压任何文件
+pub fn verify_signature(input_file: &Path, policy: &SignaturePolicy) -> Result<()> {
+    verify_signature_with(input_file, &UnpackOptions { signature: policy.clone(), ..Default::default() })
+}
+
+/// 按解压参数中的签名策略验证签名并校验整包摘要，加密的安装包使用其中的口令解密
+pub fn verify_signature_with(input_file: &Path, options: &UnpackOptions) -> Result<()> {
+    let mut input = open_file(input_file)?;
+    if identify(&mut input)? == ArchiveFormat::Legacy {
+        return options.signature.check(None, &[0u8; checksum::DIGEST_LEN]);
+    }
+    open_reader(input_file, options.passphrase.as_ref())?.verify(&options.signature)
+}
+
+/// 完整校验结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// 校验过数据的条目数
+    pub entries: usize,
+    /// 签名者公钥，未签名时为None
+    pub signer: Option<[u8; 32]>,
+}
+
+/// 按签名策略验证签名，校验整包摘要和每个条目的数据摘要，不写出任何文件
+pub fn verify_archive(input_file: &Path, policy: &SignaturePolicy) -> Result<VerifyReport> {
+    verify_archive_with(input_file, &UnpackOptions { signature: policy.clone(), ..Default::default() })
+}
+
+/// 按解压参数中的签名策略完整校验安装包，加密的安装包使用其中的口令解密
+pub fn verify_archive_with(input_file: &Path, options: &UnpackOptions) -> Result<VerifyReport> {
+    info!("Verifying {input_file:?}");
+    
+    let mut input = open_file(input_file)?;
+    if identify(&mut input)? == ArchiveFormat::Legacy {
+        anyhow::bail!("Legacy archive has no checksums to verify: {input_file:?}");
+    }
+    
+    let mut reader = open_reader(input_file, options.passphrase.as_ref())?;
+    reader.verify(&options.signature)?;
+    let entries = reader.entries().to_vec();
+    for entry in &entries {
+        reader.read_entry(entry, &mut io::sink())?;
+    }
+    
+    Ok(VerifyReport { entries: entries.len(), signer: reader.signature().map(|signature| signature.public_key) })
+}
+
+/// 打包目录并连同安装配置一起附加到安装程序本体之后，生成自解压安装程序
+pub fn bundle_installer(stub: &Path, source_dir: &Path, config_file: Option<&Path>, output_file: &Path, options: &PackOptions) -> Result<()> {
+    let file_name = output_file
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Invalid output file: {output_file:?}"))?;
+    let archive = output_file.with_file_name(format!(".{}.sspk-partial", file_name.to_string_lossy()));
+    
+    if options.volume_size.is_some() {
+        anyhow::bail!("A self-contained installer cannot be split into volumes; pack the payload separately instead");
+    }
+    
+    let result = pack_directory_with(source_dir, &archive, options)
+        .and_then(|_| embed::append_payload(stub, &archive, config_file, output_file));
+    let _ = std::fs::remove_file(&archive);
+    result
+}
+
+/// 读取安装包清单，无需解压文件内容
+pub fn read_manifest(input_file: &Path) -> Result<Vec<ArchiveEntry>> {
+    read_manifest_with(input_file, &UnpackOptions::default())
+}
+
+/// 读取安装包清单，加密的安装包使用解压参数中的口令解密
+pub fn read_manifest_with(input_file: &Path, options: &UnpackOptions) -> Result<Vec<ArchiveEntry>> {
+    let reader = open_reader(input_file, options.passphrase.as_ref())?;
+    Ok(reader.entries().to_vec())
+}
+
+/// 安装包（包括分卷和嵌入在可执行文件中的安装包）是否经过口令加密，用于决定是否需要询问口令
+pub fn is_encrypted(input_file: &Path) -> Result<bool> {
+    let (mut source, base, _) = open_source(input_file)?;
+    source.seek(SeekFrom::Start(base))?;
+    Ok(identify(&mut source)? == ArchiveFormat::Encrypted)
+}
+
+/// 打开安装包文件，拆分的安装包打开第一个分卷；打开失败归类为I/O错误
+fn open_file(input_file: &Path) -> Result<File> {
+    Ok(File::open(volume::resolve_path(input_file)).map_err(PackagerError::Io)?)
+}
+
+/// 读取开头的魔数并识别格式
+fn identify<R: Read + ?Sized>(input: &mut R) -> Result<ArchiveFormat> {
+    let mut prefix = Vec::with_capacity(format::MAGIC.len());
+    input.take(format::MAGIC.len() as u64).read_to_end(&mut prefix)?;
+    Ok(format::detect_format(&prefix))
+}
+
+/// 定位安装包数据: 普通文件、拼接后的分卷或可执行文件中嵌入的范围，返回数据来源和安装包所在的范围
+///
+/// 旧版安装包没有目录表，无法随机访问，直接报错。
+fn open_source(input_file: &Path) -> Result<(Box<dyn ArchiveSource>, u64, u64)> {
+    let mut input = open_file(input_file)?;
+    let input_file = &volume::resolve_path(input_file);
+    match identify(&mut input)? {
+        ArchiveFormat::Native | ArchiveFormat::Encrypted => {
+            let len = input.seek(SeekFrom::End(0))?;
+            Ok((Box::new(input), 0, len))
+        }
+        ArchiveFormat::Volume => {
+            let volumes = VolumeReader::open(input_file).map_err(PackagerError::classify)?;
+            let len = volumes.len();
+            Ok((Box::new(volumes), 0, len))
+        }
+        ArchiveFormat::Legacy => anyhow::bail!("Legacy archive has no manifest: {input_file:?}"),
+        ArchiveFormat::Unknown => match embed::locate(&mut input).map_err(PackagerError::classify)? {
+            Some(payload) => Ok((Box::new(input), payload.archive_offset, payload.archive_len)),
+            None => Err(PackagerError::BadMagic(format!("Not a SeeSea payload archive: {input_file:?}")).into()),
+        },
+    }
+}
+
+/// 打开原生格式安装包、分卷安装包或嵌入在可执行文件中的安装包，加密的安装包使用口令解密后读取
+fn open_reader(input_file: &Path, passphrase: Option<&Passphrase>) -> Result<ArchiveReader> {
+    let (mut source, base, len) = open_source(input_file)?;
+    source.seek(SeekFrom::Start(base))?;
+    if identify(&mut source)? != ArchiveFormat::Encrypted {
+        return ArchiveReader::from_source(source, base, len);
+    }
+    
+    let Some(passphrase) = passphrase else {
+        anyhow::bail!("Archive is encrypted, a passphrase is required to read it: {input_file:?}");
+    };
+    let decrypted = DecryptingReader::open(source, base, len, passphrase).map_err(PackagerError::classify)?;
+    let len = decrypted.len();
+    ArchiveReader::from_source(Box::new(decrypted), 0, len)
+}
+
+/// 条目路径是否等于 path 或位于 path 目录之下
+fn is_within(entry_path: &str, path: &str) -> bool {
+    let path = path.trim_end_matches('/');
+    entry_path == path || entry_path.strip_prefix(path).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// 检查目录中的条目是否与目录表记录一致
+fn entry_is_intact(root: &Path, entry: &ArchiveEntry) -> Result<bool> {
+    let target = root.join(sanitize::validate_entry_path(&entry.path)?);
+    let Ok(file_metadata) = std::fs::symlink_metadata(&target) else {
+        return Ok(false);
+    };
+    
+    let intact = match entry.kind {
+        EntryKind::Directory => file_metadata.is_dir(),
+        EntryKind::Symlink => {
+            file_metadata.file_type().is_symlink()
+                && checksum::digest_bytes(metadata::symlink_target(&target)?.as_bytes()) == entry.digest
+        }
+        EntryKind::File => {
+            file_metadata.is_file() && file_metadata.len() == entry.size && file_digest(&target)? == entry.digest
+        }
+    };
+    Ok(intact)
+}
+
+/// 流式计算文件内容的摘要
+fn file_digest(path: &Path) -> Result<checksum::Digest> {
+    let mut file = HashingReader::new(File::open(path)?);
+    io::copy(&mut file, &mut io::sink())?;
+    Ok(file.digest())
+}
+
+/// 压缩单个文件为zstd格式
+pub fn compress_file(input_file: &Path, output_file: &Path) -> Result<()> {
+    compress_file_with(input_file, output_file, &PackOptions::default())
+}
+
+/// 使用指定压缩参数压缩单个文件
+pub fn compress_file_with(input_file: &Path, output_file: &Path, options: &PackOptions) -> Result<()> {
+    info!("Compressing file {input_file:?} to {output_file:?} using zstd (level {}, {} threads)", options.level, options.threads);
+    
+    // 打开输入文件，进度按读取的字节数计算
+    let input = File::open(input_file)?;
+    let mut tracker = ProgressTracker::new(&options.progress, Some(input.metadata()?.len()));
+    let entry = input_file.to_string_lossy();
+    let mut input = ProgressReader::new(input, &mut tracker, Some(&entry));
+    
+    // 创建输出文件
+    let output = File::create(output_file)?;
+    
+    // 按压缩参数创建zstd编码器
+    let mut encoder = options.encoder(output)?;
+    
+    // 流式复制文件内容
+    io::copy(&mut input, &mut encoder)?;
+    
+    // 完成编码
+    encoder.finish()?;
+    
+    info!("Successfully compressed file {input_file:?} to {output_file:?}");
+    Ok(())
+}
+
+/// 解压单个zstd文件
+pub fn decompress_file(input_file: &Path, output_file: &Path) -> Result<()> {
+    decompress_file_with(input_file, output_file, &UnpackOptions::default())
+}
+
+/// 使用指定解压参数解压单个zstd文件，进度按读取的压缩数据字节数计算
+pub fn decompress_file_with(input_file: &Path, output_file: &Path, options: &UnpackOptions) -> Result<()> {
+    info!("Decompressing file {input_file:?} to {output_file:?} using zstd");
+    
+    // 打开输入文件
+    let input = File::open(input_file)?;
+    let mut tracker = ProgressTracker::new(&options.progress, Some(input.metadata()?.len()));
+    let entry = input_file.to_string_lossy();
+    
+    // 创建zstd解码器
+    let mut decoder = Decoder::new(ProgressReader::new(input, &mut tracker, Some(&entry)))?;
+    
+    // 创建输出文件
+    let mut output = File::create(output_file)?;
+    
+    // 流式复制文件内容
+    io::copy(&mut decoder, &mut output)?;
+    
+    info!("Successfully decompressed file {input_file:?} to {output_file:?}");
+    Ok(())
+}
@@ -0,0 +1,283 @@
+// SeeSea Self-Contained Installer - Packager Options Module
+// 模块名称: packager::options
+// 职责范围: 定义打包时的zstd压缩参数和条目压缩方式选择
+// 已实现功能: 压缩级别、工作线程数、长距离匹配窗口、帧校验和配置，开发/发布预设，参数校验，
+//             按扩展名和采样熵识别不可压缩条目，内容去重开关，签名密钥，排除和包含规则，可重现打包（统一时间戳和权限），分卷大小，加密口令，进度回调，增量补丁差分大小上限，解压参数
+// 使用依赖: anyhow, zstd, std::env, std::io, std::thread, encryption, format, progress, signing, volume
+// 主要接口: PackOptions, PackOptions::dev, PackOptions::release, PackOptions::encoder, PackOptions::choose_method,
+//           PackOptions::with_resolved_epoch, PackOptions::normalize_entry, source_date_epoch, UnpackOptions
+// 注意事项: 解压端使用zstd默认的窗口上限，因此长距离匹配窗口不能超过 MAX_WINDOW_LOG；
+//           可重现模式下在调用线程中压缩，输出不随构建机器的CPU核心数变化
+
+use super::encryption::Passphrase;
+use super::format::{ArchiveEntry, CompressionMethod, EntryKind};
+use super::progress::ProgressHandler;
+use super::signing::{SignaturePolicy, SigningKey};
+use super::volume::MIN_VOLUME_SIZE;
+use anyhow::Result;
+use std::io::Write;
+use zstd::stream::Encoder;
+
+/// 长距离匹配窗口的最小值（log2字节）
+pub const MIN_WINDOW_LOG: u32 = 10;
+
+/// 长距离匹配窗口的最大值（log2字节），与zstd解压端默认上限一致
+pub const MAX_WINDOW_LOG: u32 = 27;
+
+/// 可重现构建时间戳的环境变量，见 https://reproducible-builds.org/specs/source-date-epoch/
+pub const SOURCE_DATE_EPOCH_VAR: &str = "SOURCE_DATE_EPOCH";
+
+/// 增量补丁中生成二进制差分的文件大小上限（字节），生成和应用差分时旧文件整体读入内存
+pub const MAX_PATCH_SIZE: u64 = 64 * 1024 * 1024;
+
+/// 判断熵时采样的字节数
+pub const ENTROPY_SAMPLE_LEN: usize = 64 * 1024;
+
+/// 采样熵（比特/字节）不低于该值的条目视为不可压缩
+const INCOMPRESSIBLE_ENTROPY: f64 = 7.5;
+
+/// 默认原样存储的扩展名，这些格式本身已经压缩过
+const DEFAULT_STORE_EXTENSIONS: &[&str] = &[
+    "whl", "zip", "jar", "gz", "tgz", "bz2", "xz", "zst", "7z", "png", "jpg", "jpeg", "gif", "webp", "mp3", "mp4",
+    "woff2",
+];
+
+/// 打包压缩参数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackOptions {
+    /// zstd压缩级别
+    pub level: i32,
+    /// zstd工作线程数，0表示在调用线程中压缩
+    pub threads: u32,
+    /// 长距离匹配窗口（log2字节），None表示不启用长距离匹配
+    pub long_window: Option<u32>,
+    /// 是否在每个zstd帧中写入内容校验和
+    pub checksum: bool,
+    /// 原样存储的文件扩展名（小写，不含'.'）
+    pub store_extensions: Vec<String>,
+    /// 是否对其他文件采样计算熵，熵过高时原样存储
+    pub detect_incompressible: bool,
+    /// 是否对内容相同的文件只存储一份数据
+    pub deduplicate: bool,
+    /// 签名密钥，指定时对安装包签名
+    pub signing_key: Option<SigningKey>,
+    /// 是否读取源目录根部的 .installerignore
+    pub use_ignore_file: bool,
+    /// 额外的排除规则（gitignore语法），在 .installerignore 之后生效
+    pub exclude: Vec<String>,
+    /// 重新包含的规则（gitignore语法，不带'!'），覆盖 .installerignore 和 exclude
+    pub include: Vec<String>,
+    /// 可重现模式：统一条目的修改时间和权限，并固定压缩参数，相同的目录树总是生成相同的字节
+    pub deterministic: bool,
+    /// 可重现模式下所有条目的修改时间，None时读取 SOURCE_DATE_EPOCH，未设置则为0
+    pub source_date_epoch: Option<i64>,
+    /// 分卷大小上限（字节，包含分卷文件头），指定时安装包拆分为多个分卷文件
+    pub volume_size: Option<u64>,
+    /// 加密口令，指定时使用由口令派生的密钥加密整个安装包
+    pub passphrase: Option<Passphrase>,
+    /// 进度回调，按已读取的未压缩数据报告进度
+    pub progress: ProgressHandler,
+    /// 增量补丁中新旧版本都不超过该大小（字节）的变化文件才生成差分，更大的文件完整存储
+    pub patch_size_limit: u64,
+}
+
+impl Default for PackOptions {
+    fn default() -> Self {
+        Self {
+            level: 19,
+            threads: 0,
+            long_window: None,
+            checksum: true,
+            store_extensions: default_store_extensions(),
+            detect_incompressible: true,
+            deduplicate: true,
+            signing_key: None,
+            use_ignore_file: true,
+            exclude: Vec::new(),
+            include: Vec::new(),
+            deterministic: false,
+            source_date_epoch: None,
+            volume_size: None,
+            passphrase: None,
+            progress: ProgressHandler::default(),
+            patch_size_limit: MAX_PATCH_SIZE,
+        }
+    }
+}
+
+impl PackOptions {
+    /// 开发构建预设：低压缩级别，使用全部CPU核心
+    pub fn dev() -> Self {
+        Self {
+            level: 3,
+            threads: available_threads(),
+            ..Self::default()
+        }
+    }
+
+    /// 发布构建预设：最高压缩比，启用最大长距离匹配窗口
+    pub fn release() -> Self {
+        Self {
+            level: 22,
+            threads: available_threads(),
+            long_window: Some(MAX_WINDOW_LOG),
+            ..Self::default()
+        }
+    }
+
+    /// 校验参数范围
+    pub fn validate(&self) -> Result<()> {
+        let levels = zstd::compression_level_range();
+        if !levels.contains(&self.level) {
+            anyhow::bail!(
+                "Invalid compression level {}: must be between {} and {}",
+                self.level,
+                levels.start(),
+                levels.end()
+            );
+        }
+
+        if let Some(window_log) = self.long_window
+            && !(MIN_WINDOW_LOG..=MAX_WINDOW_LOG).contains(&window_log)
+        {
+            anyhow::bail!(
+                "Invalid long-distance window {window_log}: must be between {MIN_WINDOW_LOG} and {MAX_WINDOW_LOG}"
+            );
+        }
+
+        if let Some(volume_size) = self.volume_size
+            && volume_size < MIN_VOLUME_SIZE
+        {
+            anyhow::bail!("Invalid volume size {volume_size}: must be at least {MIN_VOLUME_SIZE} bytes");
+        }
+
+        if self.patch_size_limit > MAX_PATCH_SIZE {
+            anyhow::bail!("Invalid patch size limit {}: must be at most {MAX_PATCH_SIZE} bytes", self.patch_size_limit);
+        }
+
+        // 加密使用随机的盐和nonce，每次输出都不同
+        if self.deterministic && self.passphrase.is_some() {
+            anyhow::bail!("Deterministic packing cannot be combined with encryption");
+        }
+
+        Ok(())
+    }
+
+    /// 根据路径和开头的采样数据选择条目的压缩方式
+    pub fn choose_method(&self, path: &str, sample: &[u8]) -> CompressionMethod {
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        if let Some((_, extension)) = file_name.rsplit_once('.')
+            && self.store_extensions.iter().any(|ext| ext.eq_ignore_ascii_case(extension))
+        {
+            return CompressionMethod::Stored;
+        }
+
+        if self.detect_incompressible && sample_entropy(sample) >= INCOMPRESSIBLE_ENTROPY {
+            return CompressionMethod::Stored;
+        }
+
+        CompressionMethod::Zstd
+    }
+
+    /// 按参数创建zstd编码器
+    pub fn encoder<W: Write>(&self, writer: W) -> Result<Encoder<'static, W>> {
+        self.validate()?;
+
+        let mut encoder = Encoder::new(writer, self.level)?;
+        encoder.include_checksum(self.checksum)?;
+        if self.threads > 0 && !self.deterministic {
+            encoder.multithread(self.threads)?;
+        }
+        if let Some(window_log) = self.long_window {
+            encoder.long_distance_matching(true)?;
+            encoder.window_log(window_log)?;
+        }
+        Ok(encoder)
+    }
+
+    /// 返回确定了可重现时间戳的参数副本：可重现模式下未指定时间戳时读取一次 SOURCE_DATE_EPOCH，未设置则为0
+    ///
+    /// 打包入口在开始时调用一次，之后每个条目使用同一个时间戳。
+    pub fn with_resolved_epoch(&self) -> Result<Self> {
+        let mut options = self.clone();
+        if options.deterministic && options.source_date_epoch.is_none() {
+            options.source_date_epoch = Some(source_date_epoch()?.unwrap_or(0));
+        }
+        Ok(options)
+    }
+
+    /// 可重现模式下统一条目的修改时间和权限，不记录构建机器上的时间和umask差异
+    ///
+    /// 时间戳取自 source_date_epoch，需要读取环境变量时先调用 with_resolved_epoch。
+    pub fn normalize_entry(&self, entry: &mut ArchiveEntry) {
+        if !self.deterministic {
+            return;
+        }
+
+        entry.mtime = self.source_date_epoch.unwrap_or(0);
+        entry.mode = match entry.kind {
+            EntryKind::File if entry.mode & 0o111 != 0 => 0o755,
+            EntryKind::File => 0o644,
+            EntryKind::Directory => 0o755,
+            EntryKind::Symlink => 0o777,
+        };
+    }
+}
+
+/// 读取 SOURCE_DATE_EPOCH 环境变量（Unix时间戳，秒）
+pub fn source_date_epoch() -> Result<Option<i64>> {
+    match std::env::var(SOURCE_DATE_EPOCH_VAR) {
+        Ok(value) => match value.trim().parse() {
+            Ok(epoch) => Ok(Some(epoch)),
+            Err(_) => anyhow::bail!("Invalid {SOURCE_DATE_EPOCH_VAR}: {value}"),
+        },
+        Err(_) => Ok(None),
+    }
+}
+
+/// 解压参数
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnpackOptions {
+    /// 共享同一份数据的文件解压为硬链接，而不是各自写出一份
+    ///
+    /// 只有权限和修改时间相同的文件才会链接到一起，否则仍各自写出。
+    pub hardlinks: bool,
+    /// 解压前的签名验证策略
+    pub signature: SignaturePolicy,
+    /// 读取加密安装包的口令
+    pub passphrase: Option<Passphrase>,
+    /// 进度回调，按已写出的未压缩数据报告进度
+    pub progress: ProgressHandler,
+}
+
+/// 可用的CPU核心数
+fn available_threads() -> u32 {
+    std::thread::available_parallelism().map(|n| n.get() as u32).unwrap_or(1)
+}
+
+/// 默认原样存储的扩展名列表
+fn default_store_extensions() -> Vec<String> {
+    DEFAULT_STORE_EXTENSIONS.iter().map(|ext| ext.to_string()).collect()
+}
+
+/// 计算采样数据的香农熵（比特/字节）
+fn sample_entropy(sample: &[u8]) -> f64 {
+    if sample.is_empty() {
+        return 0.0;
+    }
+
+    let mut counts = [0usize; 256];
+    for &byte in sample {
+        counts[byte as usize] += 1;
+    }
+
+    let len = sample.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
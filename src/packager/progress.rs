@@ -0,0 +1,150 @@
+// SeeSea Self-Contained Installer - Packager Progress Module
+// 模块名称: packager::progress
+// 职责范围: 向调用方报告打包、解压、压缩和解压单个文件的进度
+// 已实现功能: 进度事件、可放入压缩/解压参数的进度回调、累计已处理字节数、读写时自动报告进度的包装器
+// 使用依赖: std::fmt, std::io, std::sync
+// 主要接口: Progress, ProgressHandler, ProgressTracker, ProgressReader, ProgressWriter
+// 注意事项: 回调在处理数据的线程中同步调用，每次读写都会触发，耗时的绘制需要回调自行节流；
+//           打包和解压按未压缩数据计数，单个文件的压缩和解压按读取的输入文件字节计数
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+/// 进度事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress<'a> {
+    /// 已处理的字节数
+    pub processed: u64,
+    /// 需要处理的总字节数，无法预先得知时为None
+    pub total: Option<u64>,
+    /// 正在处理的条目路径
+    pub entry: Option<&'a str>,
+}
+
+/// 进度回调函数
+type ProgressFn = dyn Fn(&Progress) + Send + Sync;
+
+/// 进度回调，默认不报告进度
+#[derive(Clone, Default)]
+pub struct ProgressHandler(Option<Arc<ProgressFn>>);
+
+impl ProgressHandler {
+    /// 使用回调函数创建进度回调
+    pub fn new<F: Fn(&Progress) + Send + Sync + 'static>(callback: F) -> Self {
+        Self(Some(Arc::new(callback)))
+    }
+
+    /// 是否设置了回调函数
+    pub fn is_set(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// 报告进度
+    pub fn report(&self, progress: &Progress) {
+        if let Some(callback) = &self.0 {
+            callback(progress);
+        }
+    }
+}
+
+impl fmt::Debug for ProgressHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.is_set() { "ProgressHandler(Some(..))" } else { "ProgressHandler(None)" })
+    }
+}
+
+/// 指向同一个回调函数时相等
+impl PartialEq for ProgressHandler {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ProgressHandler {}
+
+/// 累计一次操作的进度并报告给回调
+pub struct ProgressTracker<'h> {
+    handler: &'h ProgressHandler,
+    processed: u64,
+    total: Option<u64>,
+}
+
+impl<'h> ProgressTracker<'h> {
+    /// 开始一次总量为 total 字节的操作
+    pub fn new(handler: &'h ProgressHandler, total: Option<u64>) -> Self {
+        Self { handler, processed: 0, total }
+    }
+
+    /// 已处理的字节数
+    pub fn processed(&self) -> u64 {
+        self.processed
+    }
+
+    /// 增加已处理的字节数并报告进度，bytes 为0时只报告当前条目
+    pub fn advance(&mut self, entry: Option<&str>, bytes: u64) {
+        self.processed += bytes;
+        self.handler.report(&Progress { processed: self.processed, total: self.total, entry });
+    }
+
+    /// 将已处理的字节数推进到 processed 并报告进度，用于没有实际读写数据的条目（如去重和硬链接）
+    pub fn advance_to(&mut self, entry: Option<&str>, processed: u64) {
+        self.advance(entry, processed.saturating_sub(self.processed));
+    }
+}
+
+/// 读取时报告进度的包装器
+pub struct ProgressReader<'t, 'h, R: Read> {
+    inner: R,
+    tracker: &'t mut ProgressTracker<'h>,
+    entry: Option<&'t str>,
+}
+
+impl<'t, 'h, R: Read> ProgressReader<'t, 'h, R> {
+    /// 包装读取流，读取的字节计入 tracker
+    pub fn new(inner: R, tracker: &'t mut ProgressTracker<'h>, entry: Option<&'t str>) -> Self {
+        Self { inner, tracker, entry }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<'_, '_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read > 0 {
+            self.tracker.advance(self.entry, read as u64);
+        }
+        Ok(read)
+    }
+}
+
+/// 写入时报告进度的包装器
+pub struct ProgressWriter<'t, 'h, W: Write> {
+    inner: W,
+    tracker: &'t mut ProgressTracker<'h>,
+    entry: Option<&'t str>,
+}
+
+impl<'t, 'h, W: Write> ProgressWriter<'t, 'h, W> {
+    /// 包装写入流，写入的字节计入 tracker
+    pub fn new(inner: W, tracker: &'t mut ProgressTracker<'h>, entry: Option<&'t str>) -> Self {
+        Self { inner, tracker, entry }
+    }
+}
+
+impl<W: Write> Write for ProgressWriter<'_, '_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        if written > 0 {
+            self.tracker.advance(self.entry, written as u64);
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
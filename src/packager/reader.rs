@@ -0,0 +1,356 @@
+// SeeSea Self-Contained Installer - Packager Reader Module
+// 模块名称: packager::reader
+// 职责范围: 随机访问读取原生格式安装包
+// 已实现功能: 文件尾和目录表读取、条目偏移校验、整包摘要校验、签名验证、单条目读取、按条件解压、共享数据帧的文件解压为硬链接、解压进度报告
+// 使用依赖: anyhow, log, zstd, std::fs, std::io, std::path, checksum, error, format, metadata, options, progress, sanitize, signing
+// 主要接口: ArchiveReader::open, from_file, from_range, from_volumes, from_source, entries, find, signature, verify, verify_archive_digest, read_entry, extract
+// 注意事项: 只读取被请求的条目的数据帧，按条目记录的压缩方式解压或直接复制；文件先写入临时文件，摘要校验通过后才替换目标文件
+//
+// 整包摘要需要读取全部字节，因此不在打开时校验，由调用方按需调用 verify_archive_digest
+// 读取和解压的错误归类为 PackagerError，可以区分截断、损坏、不安全路径和I/O错误
+
+use super::checksum::{HashingReader, HashingWriter};
+use super::error::{FrameDecoder, PackagerError};
+use super::format::{self, ArchiveEntry, CompressionMethod, EntryKind, FOOTER_LEN, Footer, HEADER_LEN, Header, MAX_PATH_LEN};
+use super::options::UnpackOptions;
+use super::progress::{ProgressTracker, ProgressWriter};
+use super::signing::{ArchiveSignature, SIGNATURE_BLOCK_LEN, SignaturePolicy};
+use super::volume::VolumeReader;
+use super::{metadata, sanitize};
+use anyhow::Result;
+use log::debug;
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, create_dir, hard_link, remove_file, rename};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use zstd::stream::Decoder;
+
+/// 安装包数据来源: 普通文件或拼接后的分卷
+pub trait ArchiveSource: Read + Seek {}
+
+impl<T: Read + Seek> ArchiveSource for T {}
+
+/// 原生格式安装包读取器
+pub struct ArchiveReader {
+    file: Box<dyn ArchiveSource>,
+    /// 安装包在文件中的起始位置
+    base: u64,
+    footer: Footer,
+    signature: Option<ArchiveSignature>,
+    entries: Vec<ArchiveEntry>,
+}
+
+impl ArchiveReader {
+    /// 打开安装包，读取文件头、文件尾和目录表
+    pub fn open(path: &Path) -> Result<Self> {
+        Self::from_file(File::open(path)?)
+    }
+
+    /// 从已打开的文件读取安装包
+    pub fn from_file(mut file: File) -> Result<Self> {
+        let len = file.seek(SeekFrom::End(0))?;
+        Self::from_range(file, 0, len)
+    }
+
+    /// 读取位于文件中 [base, base + len) 范围内的安装包，例如嵌入在可执行文件中的安装包
+    ///
+    /// 目录表中的偏移均相对于安装包起始位置。
+    pub fn from_range(file: File, base: u64, len: u64) -> Result<Self> {
+        Self::from_source(Box::new(file), base, len)
+    }
+
+    /// 读取拆分为多个分卷的安装包
+    pub fn from_volumes(volumes: VolumeReader) -> Result<Self> {
+        let len = volumes.len();
+        Self::from_source(Box::new(volumes), 0, len)
+    }
+
+    /// 读取数据来源中 [base, base + len) 范围内的安装包，例如解密后的安装包
+    pub fn from_source(file: Box<dyn ArchiveSource>, base: u64, len: u64) -> Result<Self> {
+        Self::parse(file, base, len).map_err(PackagerError::classify)
+    }
+
+    /// 读取并校验文件头、文件尾和目录表
+    fn parse(mut file: Box<dyn ArchiveSource>, base: u64, len: u64) -> Result<Self> {
+        if len < HEADER_LEN + FOOTER_LEN {
+            return Err(PackagerError::truncated().into());
+        }
+
+        // 校验文件头
+        file.seek(SeekFrom::Start(base))?;
+        let mut magic = [0u8; 8];
+        format::read_exact(&mut file, &mut magic)?;
+        if format::detect_format(&magic) != format::ArchiveFormat::Native {
+            return Err(PackagerError::BadMagic("Not a SeeSea payload archive".to_string()).into());
+        }
+        let header = format::read_header(&mut file)?;
+        debug!("Archive format version: {}", header.version);
+        let (signature, footer) = read_trailer(&mut *file, base, len, &header, "table of contents")?;
+
+        // 读取目录表
+        file.seek(SeekFrom::Start(base + footer.toc_offset))?;
+        let frame = Read::by_ref(&mut file).take(footer.toc_size);
+        let entries = read_toc(frame, &footer).map_err(|e| frame_ended_early(e, "table of contents"))?;
+
+        Ok(Self { file, base, footer, signature, entries })
+    }
+
+    /// 目录表中的全部条目
+    pub fn entries(&self) -> &[ArchiveEntry] {
+        &self.entries
+    }
+
+    /// 按路径查找条目
+    pub fn find(&self, path: &str) -> Option<&ArchiveEntry> {
+        self.entries.iter().find(|entry| entry.path == path)
+    }
+
+    /// 安装包签名，未签名时为None
+    pub fn signature(&self) -> Option<&ArchiveSignature> {
+        self.signature.as_ref()
+    }
+
+    /// 按签名策略验证签名，并校验整包摘要与实际数据一致
+    pub fn verify(&mut self, policy: &SignaturePolicy) -> Result<()> {
+        policy.check(self.signature.as_ref(), &self.footer.archive_digest)?;
+        self.verify_archive_digest()
+    }
+
+    /// 校验整包摘要，覆盖文件头、全部数据帧和目录表
+    pub fn verify_archive_digest(&mut self) -> Result<()> {
+        let result = (|| {
+            self.file.seek(SeekFrom::Start(self.base))?;
+            let toc_end = self.footer.toc_offset + self.footer.toc_size;
+            let mut hasher = HashingWriter::new(io::sink());
+            format::copy_exact(&mut self.file, &mut hasher, toc_end)?;
+            format::verify_digest("archive", &self.footer.archive_digest, &hasher.digest())
+        })();
+        result.map_err(PackagerError::classify)
+    }
+
+    /// 解压单个条目的数据到 writer 并校验摘要
+    ///
+    /// 摘要不符时数据已经写入 writer，调用方负责丢弃。
+    pub fn read_entry<W: Write>(&mut self, entry: &ArchiveEntry, writer: &mut W) -> Result<()> {
+        read_entry_data(self.file.as_mut(), self.base, entry, writer).map_err(PackagerError::classify)
+    }
+
+    /// 将满足 filter 的条目解压到已规范化的目标目录，返回解压的条目数
+    pub fn extract<F: Fn(&ArchiveEntry) -> bool>(&mut self, root: &Path, options: &UnpackOptions, filter: F) -> Result<usize> {
+        self.extract_entries(root, options, filter).map_err(PackagerError::classify)
+    }
+
+    /// 按顺序解压满足 filter 的条目，最后恢复目录的元数据
+    fn extract_entries<F: Fn(&ArchiveEntry) -> bool>(&mut self, root: &Path, options: &UnpackOptions, filter: F) -> Result<usize> {
+        let mut directories = Vec::new();
+        // 已解压的文件，按数据帧偏移索引，用于为共享数据帧的文件创建硬链接
+        let mut extracted: HashMap<u64, (PathBuf, &ArchiveEntry)> = HashMap::new();
+        // 进度按写出的文件内容字节数计算
+        let total = self.entries.iter().filter(|entry| entry.kind == EntryKind::File && filter(entry)).map(|entry| entry.size).sum();
+        let mut tracker = ProgressTracker::new(&options.progress, Some(total));
+        let mut count = 0;
+        for entry in self.entries.iter().filter(|entry| filter(entry)) {
+            count += 1;
+            if entry.kind != EntryKind::File {
+                tracker.advance(Some(&entry.path), 0);
+            }
+            if entry.kind == EntryKind::Directory {
+                // 校验路径并创建父目录
+                let target = sanitize::prepare_entry_target(root, &entry.path)?;
+                if !target.is_dir() {
+                    create_dir(&target)?;
+                }
+                // 目录的权限和时间在其内容全部解压后再恢复
+                directories.push((target, entry));
+                continue;
+            }
+
+            // 校验路径并创建父目录，数据校验通过后才落地
+            let target = if entry.kind == EntryKind::Symlink {
+                if entry.size > MAX_PATH_LEN as u64 {
+                    return Err(PackagerError::Corrupted(format!(
+                        "Symlink target of {} exceeds limit of {MAX_PATH_LEN} bytes",
+                        entry.path
+                    ))
+                    .into());
+                }
+                let mut content = Vec::new();
+                read_entry_data(self.file.as_mut(), self.base, entry, &mut content)?;
+
+                let link_target = String::from_utf8(content)?;
+                let target = sanitize::prepare_symlink_target(root, &entry.path, &link_target)?;
+                metadata::create_symlink(&link_target, &target)?;
+                target
+            } else {
+                let target = sanitize::prepare_entry_target(root, &entry.path)?;
+                let original = extracted.get(&entry.offset).filter(|(_, original)| {
+                    options.hardlinks && original.mode == entry.mode && original.mtime == entry.mtime
+                });
+                let end = tracker.processed() + entry.size;
+                if let Some((original_target, _)) = original {
+                    link_file(original_target, &target)?;
+                } else {
+                    extract_file(self.file.as_mut(), self.base, entry, &target, &mut tracker)?;
+                    metadata::set_permissions(&target, entry.mode)?;
+                    extracted.entry(entry.offset).or_insert((target.clone(), entry));
+                }
+                tracker.advance_to(Some(&entry.path), end);
+                target
+            };
+            metadata::set_mtime(&target, entry.mtime)?;
+            debug!("Extracted {:?}: {target:?}", entry.kind);
+        }
+
+        // 由深到浅恢复目录元数据，避免子条目的写入改变父目录的时间
+        for (target, entry) in directories.iter().rev() {
+            metadata::set_permissions(target, entry.mode)?;
+            metadata::set_mtime(target, entry.mtime)?;
+        }
+
+        Ok(count)
+    }
+}
+
+/// 校验条目的数据帧位于文件头和目录表之间
+fn validate_location(entry: &ArchiveEntry, toc_offset: u64) -> Result<()> {
+    if !entry.has_data() {
+        return Ok(());
+    }
+    let end = entry.offset.checked_add(entry.compressed_size);
+    if entry.offset < HEADER_LEN || end.is_none_or(|end| end > toc_offset) {
+        return Err(PackagerError::Corrupted(format!("Archive is corrupted: data of {} lies outside the data section", entry.path)).into());
+    }
+    if entry.method == CompressionMethod::Stored && entry.compressed_size != entry.size {
+        return Err(PackagerError::Corrupted(format!(
+            "Archive is corrupted: stored size of {} does not match its data size",
+            entry.path
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// 解码目录表帧中的全部条目
+fn read_toc<R: Read>(frame: R, footer: &Footer) -> Result<Vec<ArchiveEntry>> {
+    let mut decoder = FrameDecoder::new(Decoder::new(frame)?);
+    let entry_count = format::read_u64(&mut decoder)?;
+    let mut entries = Vec::new();
+    let mut paths = HashSet::new();
+    for _ in 0..entry_count {
+        let entry = ArchiveEntry::read_from(&mut decoder)?;
+        validate_location(&entry, footer.toc_offset)?;
+        if !paths.insert(entry.path.clone()) {
+            return Err(PackagerError::Corrupted(format!("Duplicate archive entry: {}", entry.path)).into());
+        }
+        entries.push(entry);
+    }
+    // 读到目录表帧结尾，触发zstd内容校验
+    io::copy(&mut decoder, &mut io::sink())?;
+    Ok(entries)
+}
+
+/// 读取 [base, base + len) 范围内容器末尾的签名块和文件尾，table 为文件尾指向的表的名称
+///
+/// 安装包和补丁包共用此布局: 带签名时末尾为签名块，文件尾位于其前，表必须紧贴在文件尾之前。
+pub(super) fn read_trailer<F: Read + Seek + ?Sized>(
+    file: &mut F,
+    base: u64,
+    len: u64,
+    header: &Header,
+    table: &str,
+) -> Result<(Option<ArchiveSignature>, Footer)> {
+    let (signature, len) = if header.flags & format::FLAG_SIGNED != 0 {
+        if len < HEADER_LEN + FOOTER_LEN + SIGNATURE_BLOCK_LEN {
+            return Err(PackagerError::truncated().into());
+        }
+        file.seek(SeekFrom::Start(base + len - SIGNATURE_BLOCK_LEN))?;
+        (Some(ArchiveSignature::read_from(&mut &mut *file)?), len - SIGNATURE_BLOCK_LEN)
+    } else {
+        (None, len)
+    };
+
+    file.seek(SeekFrom::Start(base + len - FOOTER_LEN))?;
+    let footer = format::read_footer(&mut &mut *file)?;
+    let toc_end = footer.toc_offset.checked_add(footer.toc_size);
+    if footer.toc_offset < HEADER_LEN || toc_end != Some(len - FOOTER_LEN) {
+        return Err(PackagerError::Corrupted(format!("Archive is truncated or corrupted: invalid {table} location")).into());
+    }
+    Ok((signature, footer))
+}
+
+/// 帧的范围已经过文件尾和目录表校验，帧内数据提前结束说明帧已损坏，而不是安装包被截断
+pub(super) fn frame_ended_early(error: anyhow::Error, frame: &str) -> anyhow::Error {
+    match PackagerError::classify(error).downcast::<PackagerError>() {
+        Ok(PackagerError::Truncated(_)) => {
+            PackagerError::Corrupted(format!("Archive is truncated or corrupted: {frame} ends before its recorded size")).into()
+        }
+        Ok(error) => error.into(),
+        Err(error) => error,
+    }
+}
+
+/// 定位并解压条目的数据帧，边写边校验摘要
+fn read_entry_data<W: Write>(file: &mut dyn ArchiveSource, base: u64, entry: &ArchiveEntry, writer: &mut W) -> Result<()> {
+    if !entry.has_data() {
+        return Ok(());
+    }
+
+    file.seek(SeekFrom::Start(base + entry.offset))?;
+    let frame = Read::take(&mut *file, entry.compressed_size);
+    let digest = match entry.method {
+        CompressionMethod::Zstd => {
+            let mut data = HashingReader::new(FrameDecoder::new(Decoder::new(frame)?));
+            format::copy_exact(&mut data, writer, entry.size).map_err(|e| frame_ended_early(e, &entry.path))?;
+            data.digest()
+        }
+        CompressionMethod::Stored => {
+            let mut data = HashingReader::new(frame);
+            format::copy_exact(&mut data, writer, entry.size).map_err(|e| frame_ended_early(e, &entry.path))?;
+            data.digest()
+        }
+    };
+    format::verify_digest(&entry.path, &entry.digest, &digest)
+}
+
+/// 为已解压的文件创建硬链接，先在临时位置创建再替换目标文件
+fn link_file(original: &Path, target: &Path) -> Result<()> {
+    let partial = partial_path(target)?;
+    let _ = remove_file(&partial);
+    hard_link(original, &partial)?;
+    if let Err(e) = rename(&partial, target) {
+        let _ = remove_file(&partial);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// 目标文件同目录下的临时文件路径
+fn partial_path(target: &Path) -> Result<PathBuf> {
+    let file_name = target
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Invalid extraction target: {target:?}"))?;
+    Ok(target.with_file_name(format!(".{}.sspk-partial", file_name.to_string_lossy())))
+}
+
+/// 将文件数据流式写入同目录下的临时文件，摘要校验通过后再替换目标文件
+fn extract_file(file: &mut dyn ArchiveSource, base: u64, entry: &ArchiveEntry, target: &Path, tracker: &mut ProgressTracker) -> Result<()> {
+    let partial = partial_path(target)?;
+
+    let result = (|| {
+        let mut output_file = File::create(&partial)?;
+        read_entry_data(file, base, entry, &mut ProgressWriter::new(&mut output_file, tracker, Some(&entry.path)))?;
+        output_file.sync_all()?;
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => {
+            rename(&partial, target)?;
+            Ok(())
+        }
+        Err(e) => {
+            let _ = remove_file(&partial);
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,182 @@
+// SeeSea Self-Contained Installer - Packager Sanitize Module
+// 模块名称: packager::sanitize
+// 职责范围: 校验安装包条目路径，防止解压时写出目标目录
+// 已实现功能: 绝对路径和'..'检测、Windows盘符和反斜杠检测、符号链接逃逸检测
+// 使用依赖: std::fs, std::path, std::error
+// 主要接口: UnsafePathError, validate_entry_path, validate_symlink_target, prepare_entry_target, prepare_symlink_target
+// 注意事项: 所有条目路径必须先经过validate_entry_path，再由prepare_entry_target在目标目录中落地
+
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// 不安全路径的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsafePathReason {
+    /// 空路径
+    Empty,
+    /// 绝对路径或带盘符的路径
+    Absolute,
+    /// 包含'..'组件
+    ParentDir,
+    /// 包含'.'、空组件、反斜杠或NUL等非法组件
+    InvalidComponent,
+    /// 经由符号链接指向目标目录之外
+    SymlinkEscape,
+}
+
+impl fmt::Display for UnsafePathReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            Self::Empty => "path is empty",
+            Self::Absolute => "path is absolute",
+            Self::ParentDir => "path contains '..'",
+            Self::InvalidComponent => "path contains an invalid component",
+            Self::SymlinkEscape => "path escapes the output directory through a symlink",
+        };
+        f.write_str(reason)
+    }
+}
+
+/// 不安全路径错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnsafePathError {
+    /// 安装包中记录的路径
+    pub path: String,
+    /// 拒绝原因
+    pub reason: UnsafePathReason,
+}
+
+impl UnsafePathError {
+    fn new(path: &str, reason: UnsafePathReason) -> Self {
+        Self { path: path.to_string(), reason }
+    }
+}
+
+impl fmt::Display for UnsafePathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unsafe path in archive {:?}: {}", self.path, self.reason)
+    }
+}
+
+impl std::error::Error for UnsafePathError {}
+
+/// 以'/'或'\'开头，或带Windows盘符（如"C:"）的路径均视为绝对路径
+fn is_absolute(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    let has_drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    path.starts_with('/') || path.starts_with('\\') || has_drive
+}
+
+/// 对安装包条目路径做词法校验，返回对应的相对路径
+pub fn validate_entry_path(entry_path: &str) -> Result<PathBuf, UnsafePathError> {
+    if entry_path.is_empty() {
+        return Err(UnsafePathError::new(entry_path, UnsafePathReason::Empty));
+    }
+
+    if is_absolute(entry_path) {
+        return Err(UnsafePathError::new(entry_path, UnsafePathReason::Absolute));
+    }
+
+    let mut relative_path = PathBuf::new();
+    for component in entry_path.split('/') {
+        match component {
+            ".." => return Err(UnsafePathError::new(entry_path, UnsafePathReason::ParentDir)),
+            "" | "." => return Err(UnsafePathError::new(entry_path, UnsafePathReason::InvalidComponent)),
+            _ if component.contains(['\\', ':', '\0']) => {
+                return Err(UnsafePathError::new(entry_path, UnsafePathReason::InvalidComponent));
+            }
+            _ => relative_path.push(component),
+        }
+    }
+
+    Ok(relative_path)
+}
+
+/// 校验符号链接目标，链接必须是相对路径且解析后不离开目标目录
+///
+/// '..'只允许出现在目标开头，其数量不能超过链接所在目录的深度。
+pub fn validate_symlink_target(entry_path: &str, target: &str) -> Result<(), UnsafePathError> {
+    if target.is_empty() {
+        return Err(UnsafePathError::new(entry_path, UnsafePathReason::Empty));
+    }
+
+    if is_absolute(target) {
+        return Err(UnsafePathError::new(entry_path, UnsafePathReason::SymlinkEscape));
+    }
+
+    let mut depth = entry_path.split('/').count() as i64 - 1;
+    let mut descended = false;
+    for component in target.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                depth -= 1;
+                if depth < 0 || descended {
+                    return Err(UnsafePathError::new(entry_path, UnsafePathReason::SymlinkEscape));
+                }
+            }
+            _ if component.contains(['\\', ':', '\0']) => {
+                return Err(UnsafePathError::new(entry_path, UnsafePathReason::InvalidComponent));
+            }
+            _ => descended = true,
+        }
+    }
+
+    Ok(())
+}
+
+/// 在目标目录中为条目准备写入位置
+///
+/// 逐级创建父目录，已存在的父级若为符号链接则必须仍然指向目标目录之内；
+/// 目标位置上已存在的符号链接会被删除，避免写入时跟随链接。
+/// root 必须是已规范化（canonicalize）的目标目录。
+pub fn prepare_entry_target(root: &Path, entry_path: &str) -> anyhow::Result<PathBuf> {
+    let relative_path = validate_entry_path(entry_path)?;
+
+    let mut current = root.to_path_buf();
+    if let Some(parent) = relative_path.parent() {
+        for component in parent.components() {
+            current.push(component);
+            match fs::symlink_metadata(&current) {
+                Ok(metadata) if metadata.file_type().is_symlink() => {
+                    let resolved = fs::canonicalize(&current)
+                        .map_err(|_| UnsafePathError::new(entry_path, UnsafePathReason::SymlinkEscape))?;
+                    if !resolved.starts_with(root) || !resolved.is_dir() {
+                        return Err(UnsafePathError::new(entry_path, UnsafePathReason::SymlinkEscape).into());
+                    }
+                }
+                Ok(metadata) if metadata.is_dir() => {}
+                Ok(_) => anyhow::bail!("Cannot extract {entry_path}: {current:?} is not a directory"),
+                Err(e) if e.kind() == ErrorKind::NotFound => fs::create_dir(&current)?,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    let target = root.join(&relative_path);
+    if let Ok(metadata) = fs::symlink_metadata(&target)
+        && metadata.file_type().is_symlink()
+    {
+        fs::remove_file(&target)?;
+    }
+
+    Ok(target)
+}
+
+/// 在目标目录中为符号链接条目准备创建位置
+///
+/// 链接所在目录不能经由其他符号链接到达，保证链接目标的词法深度与实际位置一致。
+pub fn prepare_symlink_target(root: &Path, entry_path: &str, target: &str) -> anyhow::Result<PathBuf> {
+    validate_symlink_target(entry_path, target)?;
+    let link_path = prepare_entry_target(root, entry_path)?;
+
+    if let Some(parent) = link_path.parent()
+        && fs::canonicalize(parent)? != parent
+    {
+        return Err(UnsafePathError::new(entry_path, UnsafePathReason::SymlinkEscape).into());
+    }
+
+    Ok(link_path)
+}
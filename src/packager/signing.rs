@@ -0,0 +1,187 @@
+// SeeSea Self-Contained Installer - Packager Signing Module
+// 模块名称: packager::signing
+// 职责范围: 使用Ed25519对安装包签名和验证签名
+// 已实现功能: 签名密钥生成、保存与加载，公钥解析，签名块编解码，按签名策略验证签名
+// 使用依赖: anyhow, ed25519-dalek, getrandom, log, std::fs, std::io, checksum, error, format
+// 主要接口: ArchiveSignature, SignaturePolicy, generate_signing_key, load_signing_key, save_signing_key, parse_public_key, public_key_hex
+// 注意事项: 签名覆盖整包摘要，整包摘要又覆盖文件头、全部数据帧和目录表；签名本身只说明摘要未被篡改，
+//           调用方还必须校验整包摘要与实际数据一致
+//
+// 签名块（未压缩，位于文件尾之后）: public_key(32) | signature(64) | SIGNATURE_MAGIC(8)
+// 签名内容: SIGNATURE_CONTEXT | archive_digest
+
+use super::checksum::{self, Digest};
+use super::error::PackagerError;
+use super::format;
+use anyhow::Result;
+use ed25519_dalek::{Signature, Signer};
+use log::warn;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::Path;
+
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+/// 签名块魔数
+pub const SIGNATURE_MAGIC: [u8; 8] = *b"SSPK-SIG";
+
+/// 签名块长度（字节）
+pub const SIGNATURE_BLOCK_LEN: u64 = 32 + 64 + 8;
+
+/// 签名内容前缀，避免签名被挪作他用
+const SIGNATURE_CONTEXT: &[u8] = b"SeeSea payload archive signature v1\0";
+
+/// 安装包签名
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveSignature {
+    /// 签名者公钥
+    pub public_key: [u8; 32],
+    /// Ed25519签名
+    pub signature: [u8; 64],
+}
+
+impl ArchiveSignature {
+    /// 对整包摘要签名
+    pub fn sign(key: &SigningKey, archive_digest: &Digest) -> Self {
+        let signature = key.sign(&signed_message(archive_digest));
+        Self {
+            public_key: key.verifying_key().to_bytes(),
+            signature: signature.to_bytes(),
+        }
+    }
+
+    /// 写入签名块
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&self.public_key)?;
+        writer.write_all(&self.signature)?;
+        writer.write_all(&SIGNATURE_MAGIC)?;
+        Ok(())
+    }
+
+    /// 读取签名块
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut public_key = [0u8; 32];
+        format::read_exact(reader, &mut public_key)?;
+        let mut signature = [0u8; 64];
+        format::read_exact(reader, &mut signature)?;
+        let mut magic = [0u8; 8];
+        format::read_exact(reader, &mut magic)?;
+        if magic != SIGNATURE_MAGIC {
+            return Err(PackagerError::Truncated("Archive is truncated or corrupted: missing signature block".to_string()).into());
+        }
+        Ok(Self { public_key, signature })
+    }
+
+    /// 验证签名由 trusted 中的某个公钥对 archive_digest 做出
+    pub fn verify(&self, archive_digest: &Digest, trusted: &[VerifyingKey]) -> Result<()> {
+        let Some(key) = trusted.iter().find(|key| key.to_bytes() == self.public_key) else {
+            anyhow::bail!("Payload is signed by an untrusted key: {}", hex(&self.public_key));
+        };
+        let signature = Signature::from_bytes(&self.signature);
+        key.verify_strict(&signed_message(archive_digest), &signature)
+            .map_err(|_| anyhow::anyhow!("Payload signature is invalid"))?;
+        Ok(())
+    }
+}
+
+/// 解压前的签名验证策略
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SignaturePolicy {
+    /// 不验证签名
+    #[default]
+    Ignore,
+    /// 必须带有由受信任公钥做出的有效签名
+    Require(Vec<VerifyingKey>),
+    /// 带有签名时必须有效且由受信任公钥做出，允许未签名的安装包
+    AllowUnsigned(Vec<VerifyingKey>),
+}
+
+impl SignaturePolicy {
+    /// 是否需要检查签名
+    pub fn is_enforced(&self) -> bool {
+        *self != Self::Ignore
+    }
+
+    /// 按策略检查安装包签名
+    pub fn check(&self, signature: Option<&ArchiveSignature>, archive_digest: &Digest) -> Result<()> {
+        match (self, signature) {
+            (Self::Ignore, _) => Ok(()),
+            (Self::Require(trusted), _) if trusted.is_empty() => {
+                anyhow::bail!("No trusted public key is configured to verify the payload signature; use --allow-unsigned to skip verification")
+            }
+            (Self::Require(_), None) => anyhow::bail!("Payload is not signed"),
+            (Self::Require(trusted), Some(signature)) => signature.verify(archive_digest, trusted),
+            (Self::AllowUnsigned(_), None) => {
+                warn!("Payload is not signed, continuing because unsigned payloads are allowed");
+                Ok(())
+            }
+            (Self::AllowUnsigned(trusted), Some(_)) if trusted.is_empty() => {
+                warn!("Payload is signed but no trusted public key is configured, skipping verification");
+                Ok(())
+            }
+            (Self::AllowUnsigned(trusted), Some(signature)) => signature.verify(archive_digest, trusted),
+        }
+    }
+}
+
+/// 生成新的签名密钥
+pub fn generate_signing_key() -> Result<SigningKey> {
+    let mut seed = [0u8; 32];
+    getrandom::getrandom(&mut seed).map_err(|e| anyhow::anyhow!("Failed to generate signing key: {e}"))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// 保存签名密钥（十六进制文本），Unix上只允许属主读写
+pub fn save_signing_key(path: &Path, key: &SigningKey) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    writeln!(file, "{}", hex(&key.to_bytes()))?;
+    Ok(())
+}
+
+/// 加载签名密钥（十六进制文本）
+pub fn load_signing_key(path: &Path) -> Result<SigningKey> {
+    let contents = fs::read_to_string(path)?;
+    let seed = decode_hex::<32>(contents.trim())
+        .ok_or_else(|| anyhow::anyhow!("Invalid signing key file: {path:?}"))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// 解析十六进制公钥
+pub fn parse_public_key(text: &str) -> Result<VerifyingKey> {
+    let bytes = decode_hex::<32>(text.trim()).ok_or_else(|| anyhow::anyhow!("Invalid public key: {text}"))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|_| anyhow::anyhow!("Invalid public key: {text}"))
+}
+
+/// 将公钥格式化为十六进制字符串
+pub fn public_key_hex(key: &VerifyingKey) -> String {
+    hex(&key.to_bytes())
+}
+
+/// 签名内容
+fn signed_message(archive_digest: &Digest) -> Vec<u8> {
+    [SIGNATURE_CONTEXT, archive_digest.as_slice()].concat()
+}
+
+/// 十六进制格式化
+fn hex(bytes: &[u8; 32]) -> String {
+    checksum::to_hex(bytes)
+}
+
+/// 解析定长十六进制字符串
+fn decode_hex<const N: usize>(text: &str) -> Option<[u8; N]> {
+    if text.len() != N * 2 || !text.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0u8; N];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
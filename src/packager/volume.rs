@@ -0,0 +1,318 @@
+// SeeSea Self-Contained Installer - Packager Volume Module
+// 模块名称: packager::volume
+// 职责范围: 将安装包拆分为多个限定大小的分卷文件，并把分卷重新拼接为可随机访问的安装包
+// 已实现功能: 分卷文件头、按大小切分写入、分卷命名、分卷发现与校验（缺失、顺序错乱、截断、混入其他安装包的分卷）、跨分卷随机读取
+// 使用依赖: anyhow, sha2, std::fs, std::io, std::path, checksum, error, format
+// 主要接口: VolumeWriter, VolumeReader, VolumeHeader, volume_path, resolve_path, VOLUME_MAGIC, MIN_VOLUME_SIZE
+// 注意事项: 分卷大小包含分卷文件头，每个分卷文件都不超过指定大小；
+//           分卷数和分卷集标识在写完全部数据后回填到每个分卷的文件头中，未写完的分卷集不能读取
+//
+// 分卷布局: 安装包字节流按顺序切分，每个分卷为 分卷文件头 | 安装包字节流的一段
+//   分卷文件头: VOLUME_MAGIC(8) | VOLUME_VERSION(u16) | reserved(u16) | index(u32, 从1开始) | count(u32)
+//               | volume_size(u64) | set_id(16)
+// 除最后一个分卷外，每个分卷的文件大小都等于 volume_size。
+// set_id 取安装包字节流SHA-256摘要的前16字节，同一分卷集的分卷相同，可重现打包时保持不变。
+// 分卷文件名为安装包文件名加三位序号，例如 payload.sspk.001、payload.sspk.002。
+
+use super::checksum::Digest;
+use super::error::PackagerError;
+use super::format;
+use anyhow::Result;
+use sha2::{Digest as _, Sha256};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// 分卷文件头魔数
+pub const VOLUME_MAGIC: [u8; 8] = *b"SSPK-VOL";
+
+/// 当前写入的分卷格式版本
+pub const VOLUME_VERSION: u16 = 1;
+
+/// 分卷文件头长度（字节）
+pub const VOLUME_HEADER_LEN: u64 = 8 + 2 + 2 + 4 + 4 + 8 + 16;
+
+/// 允许的最小分卷大小（字节），避免生成数量过多的分卷
+pub const MIN_VOLUME_SIZE: u64 = 64 * 1024;
+
+/// 分卷文件头
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VolumeHeader {
+    /// 分卷序号，从1开始
+    pub index: u32,
+    /// 分卷集中的分卷数
+    pub count: u32,
+    /// 分卷大小上限（字节），包含分卷文件头
+    pub volume_size: u64,
+    /// 分卷集标识
+    pub set_id: [u8; 16],
+}
+
+impl VolumeHeader {
+    /// 将分卷文件头写入流
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_all(&VOLUME_MAGIC)?;
+        writer.write_all(&VOLUME_VERSION.to_le_bytes())?;
+        writer.write_all(&0u16.to_le_bytes())?;
+        writer.write_all(&self.index.to_le_bytes())?;
+        writer.write_all(&self.count.to_le_bytes())?;
+        writer.write_all(&self.volume_size.to_le_bytes())?;
+        writer.write_all(&self.set_id)?;
+        Ok(())
+    }
+
+    /// 从流中读取分卷文件头
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut magic = [0u8; 8];
+        format::read_exact(reader, &mut magic)?;
+        if magic != VOLUME_MAGIC {
+            return Err(PackagerError::BadMagic("Not a volume of a split SeeSea archive".to_string()).into());
+        }
+        let version = format::read_u16(reader)?;
+        if version != VOLUME_VERSION {
+            return Err(PackagerError::UnsupportedVersion(format!(
+                "Unsupported split archive version: {version} (supported: {VOLUME_VERSION})"
+            ))
+            .into());
+        }
+        format::read_u16(reader)?;
+        let index = format::read_u32(reader)?;
+        let count = format::read_u32(reader)?;
+        let volume_size = format::read_u64(reader)?;
+        let mut set_id = [0u8; 16];
+        format::read_exact(reader, &mut set_id)?;
+        Ok(Self { index, count, volume_size, set_id })
+    }
+}
+
+/// 第 index 个分卷的文件路径（index从1开始）
+pub fn volume_path(archive: &Path, index: u32) -> PathBuf {
+    let mut name = archive.as_os_str().to_os_string();
+    name.push(format!(".{index:03}"));
+    PathBuf::from(name)
+}
+
+/// 解析用户给出的安装包路径: 文件不存在但存在第一个分卷时返回第一个分卷的路径，否则原样返回
+pub fn resolve_path(input_file: &Path) -> PathBuf {
+    let first = volume_path(input_file, 1);
+    if !input_file.exists() && first.is_file() { first } else { input_file.to_path_buf() }
+}
+
+/// 分卷文件路径对应的安装包路径，即去掉末尾的数字序号
+fn archive_path_of(volume: &Path) -> PathBuf {
+    let text = volume.as_os_str().to_string_lossy();
+    match text.rsplit_once('.') {
+        Some((archive, index)) if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) => PathBuf::from(archive),
+        _ => volume.to_path_buf(),
+    }
+}
+
+/// 按大小切分写入分卷的写入流
+///
+/// 分卷文件在写入第一个字节时才创建；finish 回填分卷数和分卷集标识。
+pub struct VolumeWriter {
+    archive: PathBuf,
+    volume_size: u64,
+    volumes: Vec<PathBuf>,
+    current: Option<BufWriter<File>>,
+    /// 当前分卷还能写入的数据字节数
+    remaining: u64,
+    hasher: Sha256,
+}
+
+impl VolumeWriter {
+    /// 创建写入 archive.001、archive.002 ... 的写入流，每个分卷文件不超过 volume_size 字节
+    pub fn create(archive: &Path, volume_size: u64) -> Result<Self> {
+        if volume_size < MIN_VOLUME_SIZE {
+            anyhow::bail!("Volume size {volume_size} is below the minimum of {MIN_VOLUME_SIZE} bytes");
+        }
+        Ok(Self {
+            archive: archive.to_path_buf(),
+            volume_size,
+            volumes: Vec::new(),
+            current: None,
+            remaining: 0,
+            hasher: Sha256::new(),
+        })
+    }
+
+    /// 结束当前分卷并创建下一个分卷，文件头中的分卷数暂时为0
+    fn next_volume(&mut self) -> io::Result<()> {
+        if let Some(mut current) = self.current.take() {
+            current.flush()?;
+        }
+        let index = self.volumes.len() as u32 + 1;
+        let path = volume_path(&self.archive, index);
+        let mut file = BufWriter::new(File::create(&path)?);
+        let header = VolumeHeader { index, count: 0, volume_size: self.volume_size, set_id: [0u8; 16] };
+        header.write_to(&mut file).map_err(io::Error::other)?;
+        self.volumes.push(path);
+        self.current = Some(file);
+        self.remaining = self.volume_size - VOLUME_HEADER_LEN;
+        Ok(())
+    }
+
+    /// 回填每个分卷的文件头并同步到磁盘，删除上次打包遗留的多余分卷，返回分卷路径
+    pub fn finish(mut self) -> Result<Vec<PathBuf>> {
+        if self.volumes.is_empty() {
+            self.next_volume()?;
+        }
+        if let Some(mut current) = self.current.take() {
+            current.flush()?;
+        }
+
+        let digest: Digest = self.hasher.finalize().into();
+        let mut set_id = [0u8; 16];
+        set_id.copy_from_slice(&digest[..16]);
+        let count = self.volumes.len() as u32;
+        for (index, path) in (1..).zip(&self.volumes) {
+            let mut file = OpenOptions::new().write(true).open(path)?;
+            VolumeHeader { index, count, volume_size: self.volume_size, set_id }.write_to(&mut file)?;
+            file.sync_all()?;
+        }
+
+        let mut stale = count + 1;
+        while volume_path(&self.archive, stale).is_file() {
+            fs::remove_file(volume_path(&self.archive, stale))?;
+            stale += 1;
+        }
+        Ok(self.volumes)
+    }
+}
+
+impl Write for VolumeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.current.is_none() || self.remaining == 0 {
+            self.next_volume()?;
+        }
+        let len = buf.len().min(self.remaining.try_into().unwrap_or(usize::MAX));
+        let Some(current) = self.current.as_mut() else {
+            return Err(io::Error::other("volume writer is closed"));
+        };
+        current.write_all(&buf[..len])?;
+        self.remaining -= len as u64;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.current.as_mut() {
+            Some(current) => current.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 把分卷集拼接为一个可随机访问的字节流
+pub struct VolumeReader {
+    /// 每个分卷的文件、数据在字节流中的起始位置和数据长度
+    volumes: Vec<(File, u64, u64)>,
+    len: u64,
+    position: u64,
+}
+
+impl VolumeReader {
+    /// 打开分卷集，path 可以是任意一个分卷或去掉序号的安装包路径
+    ///
+    /// 校验每个分卷都存在、序号与文件名一致、属于同一个分卷集且长度完整。
+    pub fn open(path: &Path) -> Result<Self> {
+        let archive = if path.exists() { archive_path_of(path) } else { path.to_path_buf() };
+        let first_path = volume_path(&archive, 1);
+        let first = match File::open(&first_path) {
+            Ok(mut file) => VolumeHeader::read_from(&mut file)?,
+            Err(e) if e.kind() == ErrorKind::NotFound => anyhow::bail!("Missing volume 1 of split archive: {first_path:?}"),
+            Err(e) => return Err(e.into()),
+        };
+        if first.count == 0 {
+            return Err(PackagerError::Truncated(format!("Split archive {archive:?} is incomplete: packing did not finish")).into());
+        }
+
+        let data_size = first.volume_size.saturating_sub(VOLUME_HEADER_LEN);
+        let mut volumes = Vec::new();
+        let mut len = 0;
+        for index in 1..=first.count {
+            let volume = volume_path(&archive, index);
+            let mut file = match File::open(&volume) {
+                Ok(file) => file,
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    anyhow::bail!("Missing volume {index} of {}: {volume:?}", first.count)
+                }
+                Err(e) => return Err(e.into()),
+            };
+            let header = VolumeHeader::read_from(&mut file)?;
+            if header.set_id != first.set_id || header.count != first.count || header.volume_size != first.volume_size {
+                return Err(PackagerError::Corrupted(format!("Volume {volume:?} belongs to a different split archive")).into());
+            }
+            if header.index != index {
+                return Err(PackagerError::Corrupted(format!(
+                    "Volume {volume:?} is out of order: it contains volume {} of {}, expected volume {index}",
+                    header.index,
+                    first.count
+                ))
+                .into());
+            }
+
+            let volume_len = file.metadata()?.len().saturating_sub(VOLUME_HEADER_LEN);
+            if (index < first.count && volume_len != data_size) || volume_len > data_size {
+                return Err(PackagerError::Truncated(format!(
+                    "Volume {volume:?} is truncated or corrupted: expected {} bytes, found {}",
+                    data_size + VOLUME_HEADER_LEN,
+                    volume_len + VOLUME_HEADER_LEN
+                ))
+                .into());
+            }
+            volumes.push((file, len, volume_len));
+            len += volume_len;
+        }
+        Ok(Self { volumes, len, position: 0 })
+    }
+
+    /// 拼接后的字节流长度
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// 字节流是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// 分卷数
+    pub fn volume_count(&self) -> usize {
+        self.volumes.len()
+    }
+}
+
+impl Read for VolumeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.len {
+            return Ok(0);
+        }
+        let index = self.volumes.partition_point(|(_, start, len)| start + len <= self.position);
+        let (file, start, len) = &mut self.volumes[index];
+        let offset = self.position - *start;
+        let available = (*len - offset).min(buf.len() as u64) as usize;
+        file.seek(SeekFrom::Start(VOLUME_HEADER_LEN + offset))?;
+        let read = file.read(&mut buf[..available])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for VolumeReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        let Some(position) = position else {
+            return Err(io::Error::new(ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position"));
+        };
+        self.position = position;
+        Ok(position)
+    }
+}
@@ -0,0 +1,153 @@
+// SeeSea Self-Contained Installer - Packager Writer Module
+// 模块名称: packager::writer
+// 职责范围: 按可随机访问的容器格式写出安装包
+// 已实现功能: 文件头写入、逐条目独立压缩帧或原样存储、相同内容共享数据帧、尾部目录表、文件尾和签名块
+// 使用依赖: anyhow, log, zstd, std::io, checksum, format, options, signing
+// 主要接口: ArchiveWriter::new, with_options, add_entry, add_deduplicated, finish
+// 注意事项: 条目数据流式写入，不整体读入内存；写入器不校验条目路径，由调用方保证
+
+use super::checksum::{DIGEST_LEN, Digest, HashingReader, HashingWriter};
+use super::format::{self, ArchiveEntry, CompressionMethod, EntryKind, Footer};
+use super::options::PackOptions;
+use super::signing::ArchiveSignature;
+use anyhow::Result;
+use log::debug;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+/// 安装包写入器
+pub struct ArchiveWriter<W: Write> {
+    writer: HashingWriter<W>,
+    options: PackOptions,
+    entries: Vec<ArchiveEntry>,
+    /// 已写入的文件数据帧，按 (摘要, 大小) 索引到目录表条目
+    frames: HashMap<(Digest, u64), usize>,
+    deduplicated: usize,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    /// 使用默认压缩参数创建写入器并写入文件头
+    pub fn new(inner: W) -> Result<Self> {
+        Self::with_options(inner, PackOptions::default())
+    }
+
+    /// 使用指定压缩参数创建写入器并写入文件头
+    pub fn with_options(inner: W, options: PackOptions) -> Result<Self> {
+        options.validate()?;
+        let flags = if options.signing_key.is_some() { format::FLAG_SIGNED } else { 0 };
+        let mut writer = HashingWriter::new(inner);
+        format::write_header(&mut writer, flags)?;
+        Ok(Self { writer, options, entries: Vec::new(), frames: HashMap::new(), deduplicated: 0 })
+    }
+
+    /// 添加条目，data 必须恰好提供 entry.size 字节
+    ///
+    /// 数据按 entry.method 压缩或原样存储；entry 中的偏移、压缩大小和摘要由写入器填写。
+    pub fn add_entry<R: Read>(&mut self, mut entry: ArchiveEntry, data: R) -> Result<()> {
+        entry.offset = 0;
+        entry.compressed_size = 0;
+        entry.digest = [0u8; DIGEST_LEN];
+
+        if entry.has_data() {
+            let offset = self.writer.written();
+
+            // 每个条目一个独立的数据帧，边写入边计算未压缩数据的摘要
+            let mut data = HashingReader::new(data);
+            let mut limited = (&mut data).take(entry.size);
+            let written = match entry.method {
+                CompressionMethod::Zstd => {
+                    let mut encoder = self.options.encoder(&mut self.writer)?;
+                    let written = io::copy(&mut limited, &mut encoder)?;
+                    encoder.finish()?;
+                    written
+                }
+                CompressionMethod::Stored => io::copy(&mut limited, &mut self.writer)?,
+            };
+
+            if written != entry.size {
+                anyhow::bail!(
+                    "Entry {} changed while packaging: expected {} bytes, got {written}",
+                    entry.path,
+                    entry.size
+                );
+            }
+
+            entry.offset = offset;
+            entry.compressed_size = self.writer.written() - offset;
+            entry.digest = data.digest();
+        }
+
+        if entry.kind == EntryKind::File {
+            self.frames.entry((entry.digest, entry.size)).or_insert(self.entries.len());
+        }
+        debug!("Added {:?}: {}", entry.kind, entry.path);
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// 添加文件条目，若已写入过内容相同的数据则直接引用其数据帧
+    ///
+    /// digest 为调用方预先计算的数据摘要；没有可引用的数据帧时按 add_entry 写入 data，
+    /// 此时实际写入数据的摘要必须与 digest 一致。
+    pub fn add_deduplicated<R: Read>(&mut self, mut entry: ArchiveEntry, digest: &Digest, data: R) -> Result<()> {
+        if entry.kind == EntryKind::File
+            && let Some(&index) = self.frames.get(&(*digest, entry.size))
+        {
+            let existing = &self.entries[index];
+            entry.method = existing.method;
+            entry.offset = existing.offset;
+            entry.compressed_size = existing.compressed_size;
+            entry.digest = *digest;
+
+            debug!("Deduplicated {} against {}", entry.path, existing.path);
+            self.deduplicated += 1;
+            self.entries.push(entry);
+            return Ok(());
+        }
+
+        let path = entry.path.clone();
+        self.add_entry(entry, data)?;
+        if self.entries.last().is_some_and(|added| added.digest != *digest) {
+            anyhow::bail!("Entry {path} changed while packaging: content differs from its precomputed checksum");
+        }
+        Ok(())
+    }
+
+    /// 通过引用已有数据帧添加的条目数
+    pub fn deduplicated(&self) -> usize {
+        self.deduplicated
+    }
+
+    /// 已添加的条目
+    pub fn entries(&self) -> &[ArchiveEntry] {
+        &self.entries
+    }
+
+    /// 写入目录表、文件尾和签名块，返回内部写入流
+    pub fn finish(mut self) -> Result<W> {
+        let toc_offset = self.writer.written();
+
+        // 目录表帧带zstd内容校验，只读取部分条目时也能发现目录表损坏
+        let toc_options = PackOptions { threads: 0, checksum: true, signing_key: None, ..self.options.clone() };
+        let mut encoder = toc_options.encoder(&mut self.writer)?;
+        encoder.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+        for entry in &self.entries {
+            entry.write_to(&mut encoder)?;
+        }
+        encoder.finish()?;
+
+        let footer = Footer {
+            toc_offset,
+            toc_size: self.writer.written() - toc_offset,
+            archive_digest: self.writer.digest(),
+        };
+
+        let mut inner = self.writer.into_inner();
+        format::write_footer(&mut inner, &footer)?;
+        if let Some(key) = &self.options.signing_key {
+            ArchiveSignature::sign(key, &footer.archive_digest).write_to(&mut inner)?;
+        }
+        inner.flush()?;
+        Ok(inner)
+    }
+}
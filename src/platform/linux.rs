@@ -59,16 +59,8 @@ impl super::Platform for LinuxImpl {
     fn get_install_options(&self, config: &Config) -> Result<InstallOptions> {
         debug!("Getting Linux specific install options");
         
-        // 先获取全局安装选项
-        let mut install_options = config.install_options.clone();
-        
-        // 如果配置中有Linux特定选项，则用它们覆盖全局选项
-        if let Some(platform_config) = &config.platform {
-            if let Some(default_dir) = &platform_config.linux_default_dir {
-                debug!("Using Linux specific default_dir: {default_dir}");
-                install_options.default_dir = default_dir.clone();
-            }
-        }
+        // 用 [platform.linux] 表和旧版 linux_default_dir 覆盖全局选项
+        let install_options = config.platform_install_options("linux")?;
         
         debug!("Using merged install options");
         Ok(install_options)
@@ -59,16 +59,8 @@ impl super::Platform for MacOSImpl {
     fn get_install_options(&self, config: &Config) -> Result<InstallOptions> {
         debug!("Getting macOS specific install options");
         
-        // 先获取全局安装选项
-        let mut install_options = config.install_options.clone();
-        
-        // 如果配置中有macOS特定选项，则用它们覆盖全局选项
-        if let Some(platform_config) = &config.platform {
-            if let Some(default_dir) = &platform_config.macos_default_dir {
-                debug!("Using macOS specific default_dir: {default_dir}");
-                install_options.default_dir = default_dir.clone();
-            }
-        }
+        // 用 [platform.macos] 表和旧版 macos_default_dir 覆盖全局选项
+        let install_options = config.platform_install_options("macos")?;
         
         debug!("Using merged install options");
         Ok(install_options)
@@ -0,0 +1,188 @@
+// SeeSea Self-Contained Installer - Progress Bar Module
+// 模块名称: progress_bar
+// 职责范围: 在终端中绘制打包和解压的进度条
+// 已实现功能: 进度百分比、已处理和总字节数、平均吞吐量、预计剩余时间、当前条目
+// 使用依赖: std::io, std::sync, std::time, packager::progress
+// 主要接口: ProgressBar, ProgressBar::new, ProgressBar::handler, ProgressBar::finish, render_line
+// 注意事项: 只在标准错误输出为终端时绘制，重定向到文件或管道时不输出任何内容；
+//           两次绘制至少间隔100毫秒，避免频繁的回调拖慢打包和解压
+
+use crate::packager::{Progress, ProgressHandler};
+use std::io::{self, IsTerminal, Write};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 两次绘制的最小间隔
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 进度条宽度（字符）
+const BAR_WIDTH: usize = 24;
+
+/// 当前条目最多显示的字符数
+const MAX_ENTRY_CHARS: usize = 40;
+
+/// 终端进度条
+pub struct ProgressBar {
+    state: Option<Arc<Mutex<BarState>>>,
+}
+
+/// 进度条状态
+struct BarState {
+    label: String,
+    /// 第一次报告进度的时间，解压前的签名和摘要校验不计入吞吐量
+    start: Option<Instant>,
+    last_draw: Option<Instant>,
+    processed: u64,
+    total: Option<u64>,
+    entry: Option<String>,
+    finished: bool,
+}
+
+impl BarState {
+    /// 重新绘制当前行
+    fn draw(&mut self) {
+        let elapsed = self.start.map_or(Duration::ZERO, |start| start.elapsed());
+        let line = render_line(&self.label, self.processed, self.total, elapsed, self.entry.as_deref());
+        let mut stderr = io::stderr().lock();
+        let _ = write!(stderr, "\r\x1b[2K{line}");
+        let _ = stderr.flush();
+        self.last_draw = Some(Instant::now());
+    }
+}
+
+impl ProgressBar {
+    /// 创建进度条，标准错误输出不是终端时不绘制
+    pub fn new(label: &str) -> Self {
+        let state = io::stderr().is_terminal().then(|| {
+            Arc::new(Mutex::new(BarState {
+                label: label.to_string(),
+                start: None,
+                last_draw: None,
+                processed: 0,
+                total: None,
+                entry: None,
+                finished: false,
+            }))
+        });
+        Self { state }
+    }
+
+    /// 生成更新本进度条的进度回调，不绘制时返回空回调
+    pub fn handler(&self) -> ProgressHandler {
+        let Some(state) = &self.state else {
+            return ProgressHandler::default();
+        };
+        let state = Arc::clone(state);
+        ProgressHandler::new(move |progress: &Progress| {
+            let Ok(mut state) = state.lock() else {
+                return;
+            };
+            state.start.get_or_insert_with(Instant::now);
+            state.processed = progress.processed;
+            state.total = progress.total;
+            if let Some(entry) = progress.entry
+                && state.entry.as_deref() != Some(entry)
+            {
+                state.entry = Some(entry.to_string());
+            }
+            if !state.finished && state.last_draw.is_none_or(|last| last.elapsed() >= REDRAW_INTERVAL) {
+                state.draw();
+            }
+        })
+    }
+
+    /// 绘制最终状态并换行，之后的进度不再绘制；可重复调用
+    pub fn finish(&self) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        let Ok(mut state) = state.lock() else {
+            return;
+        };
+        if state.finished {
+            return;
+        }
+        state.finished = true;
+        if state.last_draw.is_some() {
+            state.entry = None;
+            state.draw();
+            eprintln!();
+        }
+    }
+}
+
+impl Drop for ProgressBar {
+    /// 出错提前返回时也结束进度条所在的行
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+/// 生成一行进度文本: 标签、进度条、百分比、字节数、吞吐量、预计剩余时间和当前条目
+///
+/// 总量未知时只显示已处理字节数和吞吐量。
+pub fn render_line(label: &str, processed: u64, total: Option<u64>, elapsed: Duration, entry: Option<&str>) -> String {
+    let seconds = elapsed.as_secs_f64();
+    let rate = if seconds > 0.0 { processed as f64 / seconds } else { 0.0 };
+
+    let mut line = match total {
+        Some(total) => {
+            let fraction = if total == 0 { 1.0 } else { (processed as f64 / total as f64).min(1.0) };
+            let filled = (fraction * BAR_WIDTH as f64) as usize;
+            let eta = if rate > 0.0 {
+                format_duration(Duration::from_secs_f64(total.saturating_sub(processed) as f64 / rate))
+            } else {
+                "--:--".to_string()
+            };
+            format!(
+                "{label} [{}{}] {:>3}% {} / {} {}/s ETA {eta}",
+                "#".repeat(filled),
+                ".".repeat(BAR_WIDTH - filled),
+                (fraction * 100.0) as u32,
+                format_bytes(processed),
+                format_bytes(total),
+                format_bytes(rate as u64)
+            )
+        }
+        None => format!("{label} {} {}/s", format_bytes(processed), format_bytes(rate as u64)),
+    };
+
+    if let Some(entry) = entry {
+        // 过长的路径只保留结尾部分
+        let chars = entry.chars().count();
+        if chars > MAX_ENTRY_CHARS {
+            let tail: String = entry.chars().skip(chars - (MAX_ENTRY_CHARS - 3)).collect();
+            line.push_str(&format!(" ...{tail}"));
+        } else {
+            line.push(' ');
+            line.push_str(entry);
+        }
+    }
+    line
+}
+
+/// 以二进制单位格式化字节数
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// 将时长格式化为 m:ss 或 h:mm:ss
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
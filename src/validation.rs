@@ -0,0 +1,544 @@
+// SeeSea Self-Contained Installer - Config Validation Module
+// 模块名称: validation
+// 职责范围: 收集安装配置中的全部错误和警告，并定位到配置文件中的行列
+// 已实现功能: 未知键和未知平台检查、类型错误定位、名称和版本号检查、依赖关系（不存在的依赖和循环依赖）检查、
+//             组件文件检查、变量引用检查、平台覆盖表检查、问题在配置文件中的位置
+// 使用依赖: config, interpolation, layering, version, anyhow, serde_ignored, serde_path_to_error, toml, toml_edit, std::collections, std::fmt, std::fs, std::path
+// 主要接口: Severity, CheckMode, Location, Diagnostic, ValidationReport, deserialize_config, check_config, validate_layers
+// 注意事项: 加载配置时同样执行这些检查，出现错误时拒绝配置、警告只记录日志；依赖关系、平台名称、变量引用等新加入的检查
+//           在加载时只是警告，以前能加载的配置仍能加载；
+//           问题所在的键形如 commands[0].args[1]，按值来源找到定义它的文件后在文件中查找位置，--set 的值没有位置
+
+use crate::config::Config;
+use crate::interpolation;
+use crate::layering::ConfigLayers;
+use crate::version::Version;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+/// 平台覆盖表支持的平台
+const OVERRIDE_PLATFORMS: &[&str] = &["linux", "macos", "windows"];
+
+/// 依赖和组件的 platforms 列表中可以使用的平台
+const KNOWN_PLATFORMS: &[&str] = &["linux", "macos", "windows", "freebsd", "netbsd", "openbsd"];
+
+/// 问题的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// 配置不能使用
+    Error,
+    /// 配置可以使用，但很可能不是预期的效果
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => f.write_str("error"),
+            Self::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+/// 检查的场合，决定随 validate 命令加入的检查的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckMode {
+    /// 加载配置: 新加入的检查只是警告
+    Load,
+    /// validate 命令: 新加入的检查同样是错误
+    Strict,
+}
+
+impl CheckMode {
+    /// 新加入的检查发现的问题的严重程度
+    fn added_severity(self) -> Severity {
+        match self {
+            Self::Load => Severity::Warning,
+            Self::Strict => Severity::Error,
+        }
+    }
+
+    /// 新加入的检查发现的问题: 空的组件和依赖名称、依赖之间不存在的引用、循环依赖、未知的平台名称和平台覆盖表、变量引用
+    fn added_check(self, key: impl Into<String>, message: impl Into<String>) -> Diagnostic {
+        Diagnostic::new(self.added_severity(), key, message)
+    }
+}
+
+/// 问题在配置中的位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// 定义该值的文件路径，或 --set 等来源名称
+    pub source: String,
+    /// 行号，从1开始；来源不是文件时为 None
+    pub line: Option<usize>,
+    /// 列号，从1开始
+    pub column: Option<usize>,
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "{}:{line}:{column}", self.source),
+            _ => f.write_str(&self.source),
+        }
+    }
+}
+
+/// 一个验证问题
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    /// 严重程度
+    pub severity: Severity,
+    /// 问题所在的键，例如 dependencies[0].depends_on[1]，整个配置的问题为空
+    pub key: String,
+    /// 问题说明
+    pub message: String,
+    /// 问题在配置文件中的位置
+    pub location: Option<Location>,
+}
+
+impl Diagnostic {
+    /// 指定严重程度的问题
+    pub fn new(severity: Severity, key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { severity, key: key.into(), message: message.into(), location: None }
+    }
+
+    /// 错误
+    pub fn error(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, key, message)
+    }
+
+    /// 警告
+    pub fn warning(key: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, key, message)
+    }
+
+    /// 带位置和严重程度的完整描述，例如 install.toml:3:1: error: project.version: ...
+    pub fn render(&self) -> String {
+        match &self.location {
+            Some(location) => format!("{location}: {}: {self}", self.severity),
+            None => format!("{}: {self}", self.severity),
+        }
+    }
+}
+
+/// 不含位置和严重程度的描述
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.key.is_empty() {
+            f.write_str(&self.message)
+        } else {
+            write!(f, "{}: {}", self.key, self.message)
+        }
+    }
+}
+
+/// 验证结果
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    /// 按发现顺序排列的问题
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    /// 错误数量
+    pub fn error_count(&self) -> usize {
+        self.diagnostics.iter().filter(|d| d.severity == Severity::Error).count()
+    }
+
+    /// 警告数量
+    pub fn warning_count(&self) -> usize {
+        self.diagnostics.iter().filter(|d| d.severity == Severity::Warning).count()
+    }
+
+    /// 是否有错误
+    pub fn has_errors(&self) -> bool {
+        self.error_count() > 0
+    }
+}
+
+/// 将TOML表转换为配置，收集未知的键；类型错误或缺少必填项时配置为 None，错误在返回的问题中
+pub fn deserialize_config(table: toml::Table, mode: CheckMode) -> (Option<Config>, Vec<Diagnostic>) {
+    let mut ignored = Vec::new();
+    let mut record = |path: serde_ignored::Path| ignored.push(ignored_key(&path));
+    let deserializer = serde_ignored::Deserializer::new(toml::Value::Table(table), &mut record);
+    let result: Result<Config, _> = serde_path_to_error::deserialize(deserializer);
+
+    let mut diagnostics = Vec::new();
+    for key in ignored {
+        match key.strip_prefix("platform.") {
+            // 平台名称拼写错误时覆盖不会生效，因此 validate 命令报告为错误
+            Some(platform) if !platform.contains(['.', '[']) => diagnostics.push(mode.added_check(
+                key.as_str(),
+                format!("Unknown platform '{platform}', expected one of: {}", OVERRIDE_PLATFORMS.join(", ")),
+            )),
+            _ => diagnostics.push(Diagnostic::warning(key, "Unknown key, it is ignored")),
+        }
+    }
+    match result {
+        Ok(config) => (Some(config), diagnostics),
+        Err(e) => {
+            let key = e.path().to_string();
+            let key = if key == "." { String::new() } else { key };
+            diagnostics.push(Diagnostic::error(key, e.into_inner().message().to_string()));
+            (None, diagnostics)
+        }
+    }
+}
+
+/// serde_ignored 的路径转换为 commands[0].args 形式的键
+fn ignored_key(path: &serde_ignored::Path) -> String {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => String::new(),
+        Path::Seq { parent, index } => format!("{}[{index}]", ignored_key(parent)),
+        Path::Map { parent, key } => {
+            let parent = ignored_key(parent);
+            if parent.is_empty() { key.clone() } else { format!("{parent}.{key}") }
+        }
+        Path::Some { parent } | Path::NewtypeStruct { parent } | Path::NewtypeVariant { parent } => ignored_key(parent),
+    }
+}
+
+/// 检查配置内容，base_dir 为解析组件文件相对路径的目录，为 None 时不检查组件文件
+pub fn check_config(config: &Config, base_dir: Option<&Path>, mode: CheckMode) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    // 项目名称和版本
+    if config.project.name.is_empty() {
+        diagnostics.push(Diagnostic::error("project.name", "Project name cannot be empty"));
+    }
+    if config.project.version.is_empty() {
+        diagnostics.push(Diagnostic::error("project.version", "Project version cannot be empty"));
+    } else if let Some(message) = version_problem(&config.project.version) {
+        // 无法比较版本时更新检查不可用，但仍可以安装，因此只是警告
+        diagnostics.push(Diagnostic::warning("project.version", message));
+    }
+    if let Some(version) = &config.installer_version
+        && let Some(message) = version_problem(version)
+    {
+        diagnostics.push(Diagnostic::warning("installer_version", message));
+    }
+
+    // 安装选项和平台覆盖表
+    if config.install_options.default_dir.is_empty() {
+        diagnostics.push(Diagnostic::error("install_options.default_dir", "Default install directory cannot be empty"));
+    }
+    for platform in OVERRIDE_PLATFORMS {
+        match config.platform_install_options(platform) {
+            Ok(options) if options.default_dir.is_empty() => diagnostics.push(Diagnostic::error(
+                format!("platform.{platform}.default_dir"),
+                format!("Default install directory cannot be empty in [platform.{platform}]"),
+            )),
+            Ok(_) => {}
+            Err(e) => diagnostics.push(Diagnostic::error(format!("platform.{platform}"), e.to_string())),
+        }
+    }
+
+    // 命令
+    for (index, command) in config.commands.iter().enumerate() {
+        let key = format!("commands[{index}]");
+        if command.name.is_empty() {
+            diagnostics.push(Diagnostic::error(format!("{key}.name"), format!("Command name cannot be empty at index {index}")));
+        }
+        if command.program.is_empty() {
+            diagnostics.push(Diagnostic::error(
+                format!("{key}.program"),
+                format!("Command program cannot be empty for command '{0}'", command.name),
+            ));
+        }
+        if command.timeout == Some(0) {
+            diagnostics.push(Diagnostic::error(
+                format!("{key}.timeout"),
+                format!("Command timeout cannot be zero for command '{0}'", command.name),
+            ));
+        }
+        if let Some(retries) = command.retries
+            && retries > 10
+        {
+            diagnostics.push(Diagnostic::error(
+                format!("{key}.retries"),
+                format!("Command retries cannot exceed 10 for command '{0}'", command.name),
+            ));
+        }
+    }
+
+    // 组件
+    if let Some(components) = &config.components {
+        let nodes = components
+            .iter()
+            .map(|c| Node { name: &c.name, depends_on: c.depends_on.as_deref().unwrap_or_default(), platforms: c.platforms.as_deref() })
+            .collect::<Vec<_>>();
+        // 组件引用不存在的组件一直是加载错误
+        check_graph("components", "component", &nodes, Severity::Error, mode, &mut diagnostics);
+
+        for (index, component) in components.iter().enumerate() {
+            if let Some(version) = &component.version
+                && let Some(message) = version_problem(version)
+            {
+                diagnostics.push(Diagnostic::warning(format!("components[{index}].version"), message));
+            }
+            if let (Some(base_dir), Some(files)) = (base_dir, &component.files) {
+                for (file_index, file) in files.iter().enumerate() {
+                    // 含变量的路径在安装时才能确定
+                    if !file.contains("${") && !base_dir.join(file).exists() {
+                        diagnostics.push(Diagnostic::warning(
+                            format!("components[{index}].files[{file_index}]"),
+                            format!("Component file not found: {file}"),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    // 依赖
+    if let Some(dependencies) = &config.dependencies {
+        let nodes = dependencies
+            .iter()
+            .map(|d| Node { name: &d.name, depends_on: d.depends_on.as_deref().unwrap_or_default(), platforms: d.platforms.as_deref() })
+            .collect::<Vec<_>>();
+        check_graph("dependencies", "dependency", &nodes, mode.added_severity(), mode, &mut diagnostics);
+
+        for (index, dependency) in dependencies.iter().enumerate() {
+            // 依赖版本可以带比较运算符，例如 >=1.2.0
+            let version = dependency.version.trim_start_matches(['>', '<', '=', '^', '~']).trim();
+            if version != "*"
+                && let Some(message) = version_problem(version)
+            {
+                diagnostics.push(Diagnostic::warning(format!("dependencies[{index}].version"), message));
+            }
+        }
+    }
+
+    // 插件
+    if let Some(plugins) = &config.plugins {
+        let mut plugin_names = HashSet::new();
+        for (index, plugin) in plugins.iter().enumerate() {
+            if !plugin_names.insert(&plugin.name) {
+                diagnostics.push(Diagnostic::error(format!("plugins[{index}].name"), format!("Duplicate plugin name: {0}", plugin.name)));
+            }
+            if !Path::new(&plugin.path).exists() {
+                diagnostics.push(Diagnostic::error(format!("plugins[{index}].path"), format!("Plugin path does not exist: {0}", plugin.path)));
+            }
+        }
+    }
+
+    // 变量引用
+    match interpolation::check_variables(config) {
+        Ok(problems) => diagnostics.extend(problems.into_iter().map(|(key, message)| mode.added_check(key, message))),
+        Err(e) => diagnostics.push(mode.added_check("", e.to_string())),
+    }
+
+    diagnostics
+}
+
+/// 依赖图中的节点: 组件或依赖
+struct Node<'a> {
+    name: &'a str,
+    depends_on: &'a [String],
+    platforms: Option<&'a [String]>,
+}
+
+/// 检查名称重复、未知平台、不存在的依赖和循环依赖；section 为配置中的键，kind 为消息中的名称，missing 为不存在的依赖的严重程度
+fn check_graph(section: &str, kind: &str, nodes: &[Node], missing: Severity, mode: CheckMode, diagnostics: &mut Vec<Diagnostic>) {
+    let capitalized = format!("{}{}", kind[..1].to_uppercase(), &kind[1..]);
+    let mut indices = HashMap::new();
+    for (index, node) in nodes.iter().enumerate() {
+        if node.name.is_empty() {
+            diagnostics.push(mode.added_check(format!("{section}[{index}].name"), format!("{capitalized} name cannot be empty")));
+        }
+        if indices.insert(node.name, index).is_some() {
+            diagnostics.push(Diagnostic::error(format!("{section}[{index}].name"), format!("Duplicate {kind} name: {}", node.name)));
+        }
+        for (platform_index, platform) in node.platforms.unwrap_or_default().iter().enumerate() {
+            if !KNOWN_PLATFORMS.contains(&platform.as_str()) {
+                diagnostics.push(mode.added_check(
+                    format!("{section}[{index}].platforms[{platform_index}]"),
+                    format!("Unknown platform '{platform}', expected one of: {}", KNOWN_PLATFORMS.join(", ")),
+                ));
+            }
+        }
+    }
+
+    for (index, node) in nodes.iter().enumerate() {
+        for (dep_index, dep) in node.depends_on.iter().enumerate() {
+            if !indices.contains_key(dep.as_str()) {
+                diagnostics.push(Diagnostic::new(
+                    missing,
+                    format!("{section}[{index}].depends_on[{dep_index}]"),
+                    format!("{capitalized} {0} depends on non-existent {kind}: {1}", node.name, dep),
+                ));
+            }
+        }
+    }
+
+    // 深度优先搜索，每个环只报告一次，位置为环中第一个节点的 depends_on
+    let mut state = vec![Visit::New; nodes.len()];
+    let mut stack = Vec::new();
+    for index in 0..nodes.len() {
+        find_cycles(index, nodes, &indices, &mut state, &mut stack, &mut |cycle| {
+            let path = cycle.iter().chain(&cycle[..1]).map(|&i| nodes[i].name).collect::<Vec<_>>();
+            diagnostics.push(mode.added_check(
+                format!("{section}[{}].depends_on", cycle[0]),
+                format!("{capitalized} cycle: {}", path.join(" -> ")),
+            ));
+        });
+    }
+}
+
+/// 深度优先搜索中节点的状态
+#[derive(Clone, Copy, PartialEq)]
+enum Visit {
+    New,
+    InProgress,
+    Done,
+}
+
+/// 从 index 开始查找环，找到时以环中节点的下标调用 found
+fn find_cycles(
+    index: usize,
+    nodes: &[Node],
+    indices: &HashMap<&str, usize>,
+    state: &mut [Visit],
+    stack: &mut Vec<usize>,
+    found: &mut impl FnMut(&[usize]),
+) {
+    match state[index] {
+        Visit::Done => return,
+        Visit::InProgress => {
+            let start = stack.iter().position(|&i| i == index).unwrap_or_default();
+            found(&stack[start..]);
+            return;
+        }
+        Visit::New => {}
+    }
+    state[index] = Visit::InProgress;
+    stack.push(index);
+    for dep in nodes[index].depends_on {
+        if let Some(&dep_index) = indices.get(dep.as_str()) {
+            find_cycles(dep_index, nodes, indices, state, stack, found);
+        }
+    }
+    stack.pop();
+    state[index] = Visit::Done;
+}
+
+/// 版本号不能按 MAJOR.MINOR.PATCH[-PRE] 解析时返回说明
+fn version_problem(version: &str) -> Option<String> {
+    Version::parse(version)
+        .is_err()
+        .then(|| format!("Invalid version '{version}', expected MAJOR.MINOR.PATCH such as 1.2.0 or 1.2.0-beta"))
+}
+
+/// 验证合并后的配置层，为每个问题查找定义它的文件和位置；base_dir 为解析组件文件相对路径的目录
+pub fn validate_layers(layers: &ConfigLayers, base_dir: Option<&Path>) -> ValidationReport {
+    let (config, mut diagnostics) = deserialize_config(layers.table.clone(), CheckMode::Strict);
+    if let Some(config) = &config {
+        diagnostics.extend(check_config(config, base_dir, CheckMode::Strict));
+    }
+
+    let mut files = HashMap::new();
+    for diagnostic in &mut diagnostics {
+        diagnostic.location = locate(&diagnostic.key, &layers.sources, &mut files);
+    }
+    // 按文件中的位置排列，没有位置的问题在前
+    diagnostics.sort_by_key(|d| d.location.as_ref().map(|l| (l.source.clone(), l.line, l.column)));
+    ValidationReport { diagnostics }
+}
+
+/// 键的一段: 表中的键或数组下标
+#[derive(Debug, PartialEq)]
+enum KeySegment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// 拆分 commands[0].args[1] 形式的键
+fn split_key(key: &str) -> Vec<KeySegment<'_>> {
+    let mut segments = Vec::new();
+    for part in key.split('.').filter(|part| !part.is_empty()) {
+        let (name, indices) = part.split_once('[').map_or((part, ""), |(name, rest)| (name, rest));
+        if !name.is_empty() {
+            segments.push(KeySegment::Key(name));
+        }
+        for index in indices.split('[') {
+            if let Ok(index) = index.trim_end_matches(']').parse() {
+                segments.push(KeySegment::Index(index));
+            }
+        }
+    }
+    segments
+}
+
+/// 查找键所在的来源和位置: 先找定义该值或其上级的层，再找定义其下级的层
+fn locate(key: &str, sources: &BTreeMap<String, String>, files: &mut HashMap<String, Option<String>>) -> Option<Location> {
+    let segments = split_key(key);
+    let dotted = segments
+        .iter()
+        .map_while(|segment| match segment {
+            KeySegment::Key(name) => Some(*name),
+            KeySegment::Index(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let source = (1..=dotted.len())
+        .rev()
+        .find_map(|len| sources.get(&dotted[..len].join(".")))
+        .or_else(|| {
+            let prefix = format!("{}.", dotted.join("."));
+            sources.iter().find(|(existing, _)| !dotted.is_empty() && existing.starts_with(&prefix)).map(|(_, source)| source)
+        })?;
+
+    let mut location = Location { source: source.clone(), line: None, column: None };
+    let contents = files.entry(source.clone()).or_insert_with(|| fs::read_to_string(source).ok());
+    if let Some(contents) = contents
+        && let Ok(document) = toml_edit::ImDocument::parse(contents.as_str())
+        && let Some(span) = locate_in_table(document.as_table(), &segments)
+    {
+        let before = &contents[..span.start];
+        location.line = Some(before.matches('\n').count() + 1);
+        location.column = Some(before.rsplit('\n').next().unwrap_or_default().chars().count() + 1);
+    }
+    Some(location)
+}
+
+/// 在表中查找键的位置，找不到下级时返回最深一级已找到的位置
+fn locate_in_table(table: &dyn toml_edit::TableLike, segments: &[KeySegment]) -> Option<Range<usize>> {
+    let (KeySegment::Key(name), rest) = segments.split_first()? else {
+        return None;
+    };
+    let (key, item) = table.get_key_value(name)?;
+    if rest.is_empty() {
+        return key.span().or_else(|| item.span());
+    }
+    let found = match item {
+        toml_edit::Item::Table(table) => locate_in_table(table, rest),
+        toml_edit::Item::Value(value) => locate_in_value(value, rest),
+        toml_edit::Item::ArrayOfTables(array) => match rest.split_first() {
+            Some((KeySegment::Index(index), rest)) => array.get(*index).and_then(|table| {
+                if rest.is_empty() { table.span() } else { locate_in_table(table, rest).or_else(|| table.span()) }
+            }),
+            _ => None,
+        },
+        toml_edit::Item::None => None,
+    };
+    found.or_else(|| key.span())
+}
+
+/// 在内联值中查找键的位置
+fn locate_in_value(value: &toml_edit::Value, segments: &[KeySegment]) -> Option<Range<usize>> {
+    let Some((first, rest)) = segments.split_first() else {
+        return value.span();
+    };
+    match (value, first) {
+        (toml_edit::Value::InlineTable(table), KeySegment::Key(_)) => locate_in_table(table, segments),
+        (toml_edit::Value::Array(array), KeySegment::Index(index)) => {
+            let item = array.get(*index)?;
+            locate_in_value(item, rest).or_else(|| item.span())
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,126 @@
+// SeeSea Installer - Commands Tests
+// 测试安装包命令（pack、unpack、list、verify）
+
+use clap::Parser;
+use seesea_installer::Args;
+use seesea_installer::commands;
+use seesea_installer::packager::{PackOptions, signing};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// 解析命令行并执行安装包命令，返回命令结果和输出
+fn run(args: &[&str]) -> (anyhow::Result<()>, String) {
+    let args = Args::try_parse_from([&["seesea-installer"], args].concat()).unwrap();
+    let mut out = Vec::new();
+    let result = commands::run_packager_command(&args, &mut out).expect("not a packager command");
+    (result, String::from_utf8(out).unwrap())
+}
+
+/// 创建源目录并用pack命令打包
+fn pack(root: &Path) -> String {
+    let source = root.join("building");
+    fs::create_dir_all(source.join("lib")).unwrap();
+    fs::write(source.join("app.txt"), b"application").unwrap();
+    fs::write(source.join("lib/config.toml"), b"key = \"value\"\n").unwrap();
+
+    let archive = root.join("payload.sspk").to_str().unwrap().to_string();
+    let (result, output) = run(&["pack", "--preset", "dev", "--source", source.to_str().unwrap(), "--output", &archive]);
+    result.unwrap();
+    assert!(output.contains("Packaged 3 entries"), "unexpected output: {output}");
+    archive
+}
+
+#[test]
+fn test_list_human_and_json() {
+    let temp_dir = tempdir().unwrap();
+    let archive = pack(temp_dir.path());
+
+    let (result, output) = run(&["list", &archive]);
+    result.unwrap();
+    assert!(output.lines().any(|line| line.ends_with(" app.txt") && line.contains(" 11 ")));
+    assert!(output.lines().any(|line| line.starts_with('d') && line.ends_with(" lib/")));
+    assert!(output.contains("3 entries, 25 bytes"));
+
+    let (result, output) = run(&["list", &archive, "--json"]);
+    result.unwrap();
+    let listing: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let entries = listing.as_array().unwrap();
+    assert_eq!(entries.len(), 3);
+    let app = entries.iter().find(|entry| entry["path"] == "app.txt").unwrap();
+    assert_eq!(app["kind"], "file");
+    assert_eq!(app["size"], 11);
+    assert_eq!(app["sha256"].as_str().unwrap().len(), 64);
+    assert!(entries.iter().any(|entry| entry["path"] == "lib" && entry["kind"] == "directory"));
+}
+
+#[test]
+fn test_verify_reports_failure() {
+    let temp_dir = tempdir().unwrap();
+    let archive = pack(temp_dir.path());
+
+    // 没有受信任的公钥时拒绝未签名的安装包，除非指定 --allow-unsigned
+    let (result, _) = run(&["verify", &archive]);
+    let error = result.unwrap_err();
+    assert!(error.to_string().contains("No trusted public key"), "unexpected error: {error}");
+    let (result, output) = run(&["verify", &archive, "--allow-unsigned"]);
+    result.unwrap();
+    assert!(output.contains("Signature: none"));
+    assert!(output.contains("3 entries verified"));
+
+    // 损坏的安装包校验失败，main据此以非零退出码结束
+    let mut bytes = fs::read(&archive).unwrap();
+    bytes[16] ^= 0x01;
+    fs::write(&archive, &bytes).unwrap();
+    let (result, output) = run(&["verify", &archive, "--allow-unsigned"]);
+    let error = result.unwrap_err();
+    assert!(error.to_string().contains("Verification of"), "unexpected error: {error}");
+    assert!(!output.contains("OK"));
+}
+
+#[test]
+fn test_verify_signature_with_public_key() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let key = signing::generate_signing_key().unwrap();
+    let archive = root.join("signed.sspk");
+    fs::create_dir_all(root.join("building")).unwrap();
+    fs::write(root.join("building/app.txt"), b"application").unwrap();
+    let options = PackOptions { signing_key: Some(key.clone()), ..PackOptions::dev() };
+    seesea_installer::pack_directory_with(&root.join("building"), &archive, &options).unwrap();
+    let archive = archive.to_str().unwrap();
+
+    let public_key = signing::public_key_hex(&key.verifying_key());
+    let (result, output) = run(&["verify", archive, "--public-key", &public_key]);
+    result.unwrap();
+    assert!(output.contains("Signature: valid"));
+
+    let other = signing::public_key_hex(&signing::generate_signing_key().unwrap().verifying_key());
+    let (result, _) = run(&["verify", archive, "--public-key", &other]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_unpack_command() {
+    let temp_dir = tempdir().unwrap();
+    let archive = pack(temp_dir.path());
+    let output = temp_dir.path().join("output");
+    let output_arg = output.to_str().unwrap();
+
+    let (result, _) = run(&["unpack", &archive, "--output", output_arg]);
+    assert!(result.is_err());
+    assert!(!output.join("app.txt").exists());
+
+    let (result, _) = run(&["unpack", &archive, "--output", output_arg, "--entry", "lib", "--allow-unsigned"]);
+    result.unwrap();
+    assert!(output.join("lib/config.toml").exists());
+    assert!(!output.join("app.txt").exists());
+
+    let (result, _) = run(&["unpack", &archive, "--output", output_arg, "--allow-unsigned"]);
+    result.unwrap();
+    assert_eq!(fs::read(output.join("app.txt")).unwrap(), b"application");
+
+    // 安装命令不由安装包命令处理
+    let args = Args::try_parse_from(["seesea-installer", "install"]).unwrap();
+    assert!(commands::run_packager_command(&args, &mut Vec::new()).is_none());
+}
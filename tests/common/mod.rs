@@ -0,0 +1,90 @@
+// SeeSea Installer - Test Helpers
+// 集成测试共用的辅助函数
+
+#![allow(dead_code)]
+
+use seesea_installer::packager::{ArchiveEntry, EntryKind, checksum, format};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// 生成不可压缩的伪随机数据
+pub fn random_bytes(len: usize, mut state: u64) -> Vec<u8> {
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+/// 按原生格式手工构造安装包，entries 为 (路径, 类型, 数据)，digests 为每个条目在目录表中记录的摘要
+pub fn write_raw_archive(archive: &Path, entries: &[(&str, EntryKind, &[u8])], digests: &[checksum::Digest]) {
+    let mut writer = checksum::HashingWriter::new(fs::File::create(archive).unwrap());
+    format::write_header(&mut writer, 0).unwrap();
+
+    let mut toc = Vec::new();
+    for ((path, kind, content), digest) in entries.iter().zip(digests) {
+        let mut entry = ArchiveEntry {
+            path: path.to_string(),
+            kind: *kind,
+            mode: if *kind == EntryKind::File { 0o644 } else { 0o755 },
+            size: content.len() as u64,
+            digest: *digest,
+            ..Default::default()
+        };
+        if *kind != EntryKind::Directory {
+            entry.offset = writer.written();
+            let frame = zstd::stream::encode_all(*content, 3).unwrap();
+            writer.write_all(&frame).unwrap();
+            entry.compressed_size = frame.len() as u64;
+        }
+        toc.push(entry);
+    }
+
+    write_toc(writer, &toc);
+}
+
+/// 写入目录表和文件尾，整包摘要按已写入的字节计算
+pub fn write_toc(mut writer: checksum::HashingWriter<fs::File>, toc: &[ArchiveEntry]) {
+    let mut toc_data = (toc.len() as u64).to_le_bytes().to_vec();
+    for entry in toc {
+        entry.write_to(&mut toc_data).unwrap();
+    }
+    let toc_offset = writer.written();
+    writer.write_all(&zstd::stream::encode_all(toc_data.as_slice(), 3).unwrap()).unwrap();
+
+    let footer = format::Footer {
+        toc_offset,
+        toc_size: writer.written() - toc_offset,
+        archive_digest: writer.digest(),
+    };
+    let mut output = writer.into_inner();
+    format::write_footer(&mut output, &footer).unwrap();
+}
+
+/// 按原生格式手工构造摘要正确的安装包，条目类型任意
+pub fn write_typed_archive(archive: &Path, entries: &[(&str, EntryKind, &[u8])]) {
+    let digests: Vec<_> = entries.iter().map(|(_, _, content)| checksum::digest_bytes(content)).collect();
+    write_raw_archive(archive, entries, &digests);
+}
+
+/// 按原生格式手工构造摘要正确、只包含普通文件的安装包
+pub fn write_archive(archive: &Path, files: &[(&str, &[u8])]) {
+    let entries: Vec<_> = files.iter().map(|(path, content)| (*path, EntryKind::File, *content)).collect();
+    write_typed_archive(archive, &entries);
+}
+
+/// 按旧版无文件头格式手工构造安装包
+pub fn write_legacy_archive(archive: &Path, files: &[(&str, &[u8])]) {
+    let mut encoder = zstd::stream::Encoder::new(fs::File::create(archive).unwrap(), 3).unwrap();
+    for (path, content) in files {
+        encoder.write_all(&(path.len() as u32).to_le_bytes()).unwrap();
+        encoder.write_all(path.as_bytes()).unwrap();
+        encoder.write_all(&(content.len() as u64).to_le_bytes()).unwrap();
+        encoder.write_all(content).unwrap();
+    }
+    encoder.finish().unwrap();
+}
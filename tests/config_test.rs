@@ -184,3 +184,138 @@ ignore_errors = false
     // 验证macOS配置
     assert_eq!(platform_config.macos_default_dir.unwrap(), "/Applications/Test");
 }
+
+#[test]
+fn test_minimal_config_uses_defaults() {
+    let temp_dir = tempdir().unwrap();
+    let config_path = temp_dir.path().join("install.toml");
+    
+    let config_content = r#"
+[project]
+name = "minimal"
+version = "0.1.0"
+
+[install_options]
+default_dir = "/opt/minimal"
+"#;
+    std::fs::write(&config_path, config_content).unwrap();
+    
+    let config = config::load_config(config_path.to_str().unwrap()).unwrap();
+    let options = &config.install_options;
+    assert_eq!(options.default_dir, "/opt/minimal");
+    assert!(!options.create_desktop_shortcut);
+    assert!(!options.create_start_menu_shortcut);
+    assert!(!options.add_to_path);
+    assert!(options.create_uninstaller);
+    assert!(!options.silent);
+    assert!(!options.create_service);
+    assert!(!options.auto_check_updates);
+    assert!(options.backup_enabled);
+    assert!(config.commands.is_empty());
+    
+    // 命令只需要名称和程序
+    let config = config::parse_config(&format!("{config_content}\n[[commands]]\nname = \"hello\"\nprogram = \"/bin/echo\"\n")).unwrap();
+    assert!(config.commands[0].args.is_empty());
+    assert!(!config.commands[0].background);
+    assert!(!config.commands[0].ignore_errors);
+}
+
+#[test]
+fn test_readme_example_config() {
+    // 取出README中配置文件一节的TOML示例
+    let readme = include_str!("../README.md");
+    let section = &readme[readme.find("### Configuration File").unwrap()..];
+    let start = section.find("```toml\n").unwrap() + "```toml\n".len();
+    let example = &section[start..start + section[start..].find("```").unwrap()];
+    
+    let temp_dir = tempdir().unwrap();
+    let config_path = temp_dir.path().join("install.toml");
+    std::fs::write(&config_path, example).unwrap();
+    
+    let config = config::load_config(config_path.to_str().unwrap()).unwrap();
+    assert_eq!(config.project.name, "SeeSea");
+    assert_eq!(config.install_options.default_dir, "/opt/seesea");
+    assert_eq!(config.commands.len(), 1);
+    assert!(config.commands[0].background);
+    assert!(!config.commands[0].ignore_errors);
+    assert_eq!(config.platform_install_options("linux").unwrap().default_dir, "/usr/local/seesea");
+    assert_eq!(config.platform_install_options("windows").unwrap().default_dir, "C:\\Program Files\\SeeSea");
+}
+
+#[test]
+fn test_platform_override_tables() {
+    let config_content = r#"
+[project]
+name = "test-platform"
+version = "1.0.0"
+
+[install_options]
+default_dir = "/opt/test"
+create_desktop_shortcut = true
+post_install_script = "scripts/post-install.sh"
+service_name = "test"
+
+[platform]
+linux_default_dir = "/usr/local/test"
+windows_default_dir = "C:\\Legacy\\Test"
+
+[platform.linux]
+create_desktop_shortcut = false
+create_service = true
+service_description = "Test service"
+
+[platform.windows]
+default_dir = "C:\\Program Files\\Test"
+post_install_script = "scripts\\post-install.ps1"
+"#;
+    let config = config::parse_config(config_content).unwrap();
+    
+    // Linux表覆盖快捷方式和服务设置，旧版平铺键仍然生效，未覆盖的选项沿用全局值
+    let linux = config.platform_install_options("linux").unwrap();
+    assert_eq!(linux.default_dir, "/usr/local/test");
+    assert!(!linux.create_desktop_shortcut);
+    assert!(linux.create_service);
+    assert_eq!(linux.service_name.as_deref(), Some("test"));
+    assert_eq!(linux.service_description.as_deref(), Some("Test service"));
+    assert_eq!(linux.post_install_script.as_deref(), Some("scripts/post-install.sh"));
+    
+    // 平台表优先于旧版平铺键
+    let windows = config.platform_install_options("windows").unwrap();
+    assert_eq!(windows.default_dir, "C:\\Program Files\\Test");
+    assert_eq!(windows.post_install_script.as_deref(), Some("scripts\\post-install.ps1"));
+    assert!(windows.create_desktop_shortcut);
+    assert!(!windows.create_service);
+    
+    // 没有平台配置时使用全局选项
+    let macos = config.platform_install_options("macos").unwrap();
+    assert_eq!(macos.default_dir, "/opt/test");
+    assert!(macos.create_desktop_shortcut);
+    
+    // 全局选项本身不受平台表影响
+    assert_eq!(config.install_options.default_dir, "/opt/test");
+    assert!(!config.install_options.create_service);
+}
+
+#[test]
+fn test_invalid_platform_override_tables() {
+    let base = r#"
+[project]
+name = "test-platform"
+version = "1.0.0"
+
+[install_options]
+default_dir = "/opt/test"
+"#;
+    
+    // 拼写错误的选项不能被静默忽略
+    let error = config::parse_config(&format!("{base}\n[platform.macos]\ncreate_desktop_shortcutt = true\n")).unwrap_err();
+    assert!(error.to_string().contains("[platform.macos]"), "unexpected error: {error}");
+    assert!(error.to_string().contains("create_desktop_shortcutt"), "unexpected error: {error}");
+    
+    // 类型错误
+    let error = config::parse_config(&format!("{base}\n[platform.linux]\nsilent = \"yes\"\n")).unwrap_err();
+    assert!(error.to_string().contains("[platform.linux]"), "unexpected error: {error}");
+    
+    // 平台表中的默认目录同样不能为空
+    assert!(config::parse_config(&format!("{base}\n[platform.windows]\ndefault_dir = \"\"\n")).is_err());
+}
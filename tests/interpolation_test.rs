@@ -0,0 +1,125 @@
+// SeeSea Installer - Interpolation Tests
+// 测试配置值中 ${...} 变量的展开和检查
+
+use clap::Parser;
+use seesea_installer::interpolation::Variables;
+use seesea_installer::validation::{self, CheckMode, Severity};
+use seesea_installer::{Args, Installer, config};
+use std::path::Path;
+
+const CONFIG: &str = r#"
+[project]
+name = "demo"
+version = "2.1.0"
+
+[install_options]
+default_dir = "${home}/.demo"
+post_install_script = "${install_dir}/scripts/post-install.sh --version ${project.version}"
+
+[[commands]]
+name = "start"
+program = "${install_dir}/bin/${project.name}${exe_suffix}"
+args = ["--data", "${temp_dir}/${project.name}", "--platform=${os}-${arch}"]
+working_dir = "${install_dir}"
+"#;
+
+#[test]
+fn test_expand_variables() {
+    let config = config::parse_config(CONFIG).unwrap();
+    let install_dir = Path::new("/opt/demo");
+    let variables = Variables::new(&config, Some(install_dir));
+    
+    assert_eq!(variables.expand("${install_dir}/bin").unwrap(), "/opt/demo/bin");
+    assert_eq!(variables.expand("${project.name}-${project.version}").unwrap(), "demo-2.1.0");
+    assert_eq!(variables.expand("${ os }").unwrap(), std::env::consts::OS);
+    assert_eq!(variables.expand("${family}").unwrap(), std::env::consts::FAMILY);
+    assert_eq!(variables.expand("${temp_dir}").unwrap(), std::env::temp_dir().to_string_lossy());
+    assert_eq!(variables.expand("${env:CARGO_PKG_NAME}").unwrap(), env!("CARGO_PKG_NAME"));
+    
+    // 不是变量引用的 $ 原样保留，$${ 为字面量 ${
+    assert_eq!(variables.expand("costs $5, $${install_dir} and $").unwrap(), "costs $5, ${install_dir} and $");
+}
+
+#[test]
+fn test_expand_errors() {
+    let config = config::parse_config(CONFIG).unwrap();
+    let variables = Variables::new(&config, None);
+    
+    let error = variables.expand("${instal_dir}/bin").unwrap_err();
+    assert!(error.to_string().contains("Unknown variable ${instal_dir}"), "unexpected error: {error}");
+    
+    let error = variables.expand("${install_dir}/bin").unwrap_err();
+    assert!(error.to_string().contains("install directory is known"), "unexpected error: {error}");
+    
+    let error = variables.expand("${env:SEESEA_TEST_SURELY_UNSET_VARIABLE}").unwrap_err();
+    assert!(error.to_string().contains("SEESEA_TEST_SURELY_UNSET_VARIABLE"), "unexpected error: {error}");
+    
+    assert!(variables.expand("${install_dir").is_err());
+    assert!(variables.expand("${}").is_err());
+}
+
+#[test]
+fn test_interpolate_config() {
+    let config = config::parse_config(CONFIG).unwrap();
+    let install_dir = Path::new("/opt/demo");
+    let variables = Variables::new(&config, Some(install_dir));
+    
+    let options = variables.expand_install_options(&config.install_options).unwrap();
+    assert_eq!(options.post_install_script.as_deref(), Some("/opt/demo/scripts/post-install.sh --version 2.1.0"));
+    assert!(!options.default_dir.contains("${"));
+    
+    let commands = variables.expand_commands(&config.commands).unwrap();
+    let command = &commands[0];
+    assert_eq!(command.program, format!("/opt/demo/bin/demo{}", std::env::consts::EXE_SUFFIX));
+    assert_eq!(command.args[1], format!("{}/demo", std::env::temp_dir().to_string_lossy()));
+    assert_eq!(command.args[2], format!("--platform={}-{}", std::env::consts::OS, std::env::consts::ARCH));
+    assert_eq!(command.working_dir.as_deref(), Some("/opt/demo"));
+}
+
+#[test]
+fn test_unknown_variables_when_loading() {
+    // 拼写错误的变量在加载配置时记录为警告并给出所在的键，配置仍能加载；validate 命令报告为错误
+    let contents = CONFIG.replace("${install_dir}\"", "${install_directory}\"");
+    let config = config::parse_config(&contents).unwrap();
+    let warnings = validation::check_config(&config, None, CheckMode::Load);
+    let warning = warnings.iter().find(|d| d.key == "commands[0].working_dir").expect("no warning for the unknown variable");
+    assert_eq!(warning.severity, Severity::Warning);
+    assert!(warning.message.contains("${install_directory}"), "unexpected message: {}", warning.message);
+    let errors = validation::check_config(&config, None, CheckMode::Strict);
+    assert!(errors.iter().any(|d| d.key == "commands[0].working_dir" && d.severity == Severity::Error));
+    
+    // 展开未知变量时仍然出错
+    let error = Variables::new(&config, Some(Path::new("/opt/demo"))).expand_commands(&config.commands).unwrap_err();
+    assert!(error.to_string().contains("commands[0].working_dir"), "unexpected error: {error}");
+    
+    // 默认安装目录不能引用安装目录本身
+    let config = config::parse_config(&CONFIG.replace("${home}/.demo", "${install_dir}/demo")).unwrap();
+    let warnings = validation::check_config(&config, None, CheckMode::Load);
+    assert!(warnings.iter().any(|d| d.key == "install_options.default_dir" && d.severity == Severity::Warning), "{warnings:?}");
+    
+    // 展开时出错同样给出所在的键
+    let config = config::parse_config(&CONFIG.replace("${temp_dir}", "${env:SEESEA_TEST_SURELY_UNSET_VARIABLE}")).unwrap();
+    let error = Variables::new(&config, Some(Path::new("/opt/demo"))).expand_commands(&config.commands).unwrap_err();
+    assert!(error.to_string().contains("commands[0].args[1]"), "unexpected error: {error}");
+}
+
+#[test]
+fn test_other_platform_values_not_expanded() {
+    // 只在其他平台的覆盖表和项目描述中引用未设置的环境变量，不影响当前平台的安装
+    let other = if cfg!(windows) { "linux" } else { "windows" };
+    let contents = format!(
+        "{CONFIG}\n[platform.{other}]\npost_install_script = \"${{env:SEESEA_TEST_SURELY_UNSET_VARIABLE}}/setup\"\n"
+    )
+    .replace("version = \"2.1.0\"", "version = \"2.1.0\"\ndescription = \"Installed from ${env:SEESEA_TEST_SURELY_UNSET_VARIABLE}\"");
+    let config = config::parse_config(&contents).unwrap();
+    
+    let temp_dir = tempfile::tempdir().unwrap();
+    let install_dir = temp_dir.path().join("demo");
+    let args = Args::try_parse_from(["seesea-installer", "--install-dir", &install_dir.display().to_string()]).unwrap();
+    let installer = Installer::new(config.clone(), &args);
+    assert!(installer.is_ok(), "unexpected error: {:?}", installer.err());
+    
+    // 当前平台的值仍然展开
+    let options = Variables::new(&config, Some(&install_dir)).expand_install_options(&config.install_options).unwrap();
+    assert_eq!(options.post_install_script, Some(format!("{}/scripts/post-install.sh --version 2.1.0", install_dir.display())));
+}
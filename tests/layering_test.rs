@@ -0,0 +1,158 @@
+// SeeSea Installer - Config Layering Tests
+// 测试配置的 include 指令、环境覆盖文件、--set 覆盖值及值来源记录
+
+use clap::Parser;
+use seesea_installer::layering::{self, ConfigOverrides};
+use seesea_installer::{Args, commands, config};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// 创建基础配置、包含它的 install.toml 和 production 环境覆盖文件
+fn write_configs(root: &Path) {
+    fs::create_dir_all(root.join("shared")).unwrap();
+    fs::write(
+        root.join("shared/base.toml"),
+        r#"
+[project]
+name = "layered"
+version = "1.0.0"
+
+[install_options]
+default_dir = "/opt/layered"
+add_to_path = true
+log_level = "info"
+
+[[commands]]
+name = "base-command"
+program = "/bin/true"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        root.join("install.toml"),
+        r#"
+include = ["shared/base.toml"]
+
+[project]
+version = "1.1.0"
+
+[install_options]
+log_level = "debug"
+"#,
+    )
+    .unwrap();
+    fs::write(
+        root.join("install.production.toml"),
+        r#"
+[install_options]
+log_level = "warn"
+silent = true
+"#,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_include_directive() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    write_configs(root);
+
+    // load_config 同样展开 include
+    let config = config::load_config(root.join("install.toml").to_str().unwrap()).unwrap();
+    assert_eq!(config.project.name, "layered");
+    assert_eq!(config.project.version, "1.1.0");
+    assert_eq!(config.install_options.default_dir, "/opt/layered");
+    assert!(config.install_options.add_to_path);
+    assert_eq!(config.install_options.log_level.as_deref(), Some("debug"));
+    assert_eq!(config.commands.len(), 1);
+}
+
+#[test]
+fn test_overlay_order_and_sources() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    write_configs(root);
+    let base = root.join("shared/base.toml").display().to_string();
+    let install = root.join("install.toml").display().to_string();
+    let production = root.join("install.production.toml").display().to_string();
+
+    let overrides = ConfigOverrides {
+        environment: Some("production".to_string()),
+        set: vec![
+            "install_options.silent=false".to_string(),
+            "install_options.default_dir=/srv/layered".to_string(),
+            "install_options.components=[\"core\", \"docs\"]".to_string(),
+        ],
+    };
+    let layered = layering::load_layered_config(&root.join("install.toml"), &overrides).unwrap();
+    let options = &layered.config.install_options;
+
+    // 被包含的文件 < 配置文件 < 环境覆盖文件 < --set
+    assert_eq!(options.log_level.as_deref(), Some("warn"));
+    assert!(!options.silent);
+    assert_eq!(options.default_dir, "/srv/layered");
+    assert_eq!(options.components.as_deref(), Some(&["core".to_string(), "docs".to_string()][..]));
+    assert!(options.add_to_path);
+
+    let source = |key: &str| layered.sources.get(key).map(String::as_str);
+    assert_eq!(source("project.name"), Some(base.as_str()));
+    assert_eq!(source("project.version"), Some(install.as_str()));
+    assert_eq!(source("install_options.log_level"), Some(production.as_str()));
+    assert_eq!(source("install_options.silent"), Some("--set"));
+    assert_eq!(source("install_options.default_dir"), Some("--set"));
+    assert_eq!(source("install_options.add_to_path"), Some(base.as_str()));
+    assert_eq!(source("commands"), Some(base.as_str()));
+    assert!(!layered.sources.contains_key("include"));
+
+    // 合并结果可以重新解析，用于嵌入到安装程序中
+    let merged = config::parse_config(&layered.to_toml().unwrap()).unwrap();
+    assert_eq!(merged.install_options.log_level.as_deref(), Some("warn"));
+}
+
+#[test]
+fn test_layering_errors() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    write_configs(root);
+    let install = root.join("install.toml");
+    let load = |overrides: ConfigOverrides| layering::load_layered_config(&install, &overrides).unwrap_err().to_string();
+
+    // 环境覆盖文件不存在
+    let error = load(ConfigOverrides { environment: Some("staging".to_string()), ..Default::default() });
+    assert!(error.contains("install.staging.toml"), "unexpected error: {error}");
+
+    // --set 格式错误，覆盖后的值类型错误
+    let error = load(ConfigOverrides { set: vec!["install_options.silent".to_string()], ..Default::default() });
+    assert!(error.contains("KEY=VALUE"), "unexpected error: {error}");
+    assert!(load(ConfigOverrides { set: vec!["install_options..silent=true".to_string()], ..Default::default() }).contains("dotted path"));
+    assert!(layering::load_layered_config(&install, &ConfigOverrides { set: vec!["install_options.silent=maybe".to_string()], ..Default::default() }).is_err());
+
+    // 循环包含
+    fs::write(root.join("shared/base.toml"), "include = [\"../install.toml\"]\n").unwrap();
+    let error = load(ConfigOverrides::default());
+    assert!(error.contains("Config include cycle"), "unexpected error: {error}");
+
+    // 配置文本中不能使用 include
+    let error = config::parse_config("include = [\"base.toml\"]\n").unwrap_err();
+    assert!(error.to_string().contains("only supported in config files"), "unexpected error: {error}");
+}
+
+#[test]
+fn test_config_command_reports_sources() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    write_configs(root);
+    let install = root.join("install.toml").display().to_string();
+
+    let args = Args::try_parse_from(["seesea-installer", "config", "--config", &install, "--env", "production", "--set", "project.version=2"]).unwrap();
+    let mut out = Vec::new();
+    commands::run_packager_command(&args, &mut out).expect("not a packager command").unwrap();
+    let output = String::from_utf8(out).unwrap();
+
+    // 原值为字符串时不加引号的数字同样作为字符串
+    assert!(output.contains("project.version = \"2\"  # --set"), "unexpected output: {output}");
+    assert!(output.contains(&format!("install_options.log_level = \"warn\"  # {}", root.join("install.production.toml").display())), "unexpected output: {output}");
+    assert!(output.contains(&format!("install_options.default_dir = \"/opt/layered\"  # {}", root.join("shared/base.toml").display())), "unexpected output: {output}");
+}
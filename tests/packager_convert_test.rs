@@ -0,0 +1,140 @@
+// SeeSea Installer - Packager Convert Tests
+// 测试原生安装包与zip、tar.zst之间的导入导出
+
+use seesea_installer::packager::{self, EntryKind, PackOptions, UnpackOptions};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// 创建包含文件、可执行文件、空目录和符号链接的源目录并打包
+fn create_archive(root: &Path) -> std::path::PathBuf {
+    let source = root.join("building");
+    fs::create_dir_all(source.join("lib/empty")).unwrap();
+    fs::write(source.join("app.txt"), b"application").unwrap();
+    fs::write(source.join("lib/data.bin"), vec![7u8; 100_000]).unwrap();
+    fs::write(source.join("run.sh"), b"#!/bin/sh\necho run\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(source.join("run.sh"), fs::Permissions::from_mode(0o755)).unwrap();
+        std::os::unix::fs::symlink("../app.txt", source.join("lib/app-link")).unwrap();
+    }
+
+    let archive = root.join("payload.sspk");
+    let options = PackOptions { deterministic: true, source_date_epoch: Some(1_700_000_000), ..PackOptions::dev() };
+    packager::pack_directory_with(&source, &archive, &options).unwrap();
+    archive
+}
+
+/// 导出再导入后条目的路径、类型、权限、修改时间、大小和内容摘要不变
+fn assert_round_trip(extension: &str) {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let archive = create_archive(root);
+    let exported = root.join(format!("payload.{extension}"));
+    let imported = root.join("imported.sspk");
+
+    let count = packager::export_archive(&archive, &exported, &PackOptions::dev(), &UnpackOptions::default()).unwrap();
+    let original = packager::read_manifest(&archive).unwrap();
+    assert_eq!(count, original.len());
+
+    let summary = packager::import_archive(&exported, &imported, &PackOptions::dev()).unwrap();
+    assert_eq!(summary.entries, original.len());
+    let converted = packager::read_manifest(&imported).unwrap();
+    for (before, after) in original.iter().zip(&converted) {
+        assert_eq!((&before.path, before.kind, before.mode, before.size), (&after.path, after.kind, after.mode, after.size));
+        assert_eq!(before.digest, after.digest, "{}", before.path);
+        assert_eq!(before.mtime, after.mtime, "{}", before.path);
+    }
+
+    let output = root.join("output");
+    packager::unpack_directory(&imported, &output).unwrap();
+    assert_eq!(fs::read(output.join("lib/data.bin")).unwrap(), vec![7u8; 100_000]);
+    #[cfg(unix)]
+    assert_eq!(fs::read_link(output.join("lib/app-link")).unwrap(), Path::new("../app.txt"));
+}
+
+#[test]
+fn test_zip_round_trip() {
+    // zip的时间为2秒精度，测试使用的时间戳为偶数秒
+    assert_round_trip("zip");
+}
+
+#[test]
+fn test_tar_zst_round_trip() {
+    assert_round_trip("tar.zst");
+}
+
+#[test]
+fn test_import_tar_with_hard_link() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let input = root.join("external.tar.zst");
+
+    // 其他工具生成的tar: 路径带"./"前缀，包含根目录条目和硬链接
+    let mut builder = tar::Builder::new(zstd::Encoder::new(File::create(&input).unwrap(), 3).unwrap());
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Directory);
+    header.set_mode(0o755);
+    header.set_size(0);
+    builder.append_data(&mut header, "./", std::io::empty()).unwrap();
+    let mut header = tar::Header::new_gnu();
+    header.set_mode(0o600);
+    header.set_size(5);
+    builder.append_data(&mut header, "./data.txt", &b"hello"[..]).unwrap();
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Link);
+    header.set_mode(0o600);
+    header.set_size(0);
+    builder.append_link(&mut header, "./copy.txt", "data.txt").unwrap();
+    builder.into_inner().unwrap().finish().unwrap().flush().unwrap();
+
+    let output = root.join("payload.sspk");
+    let summary = packager::import_archive(&input, &output, &PackOptions::dev()).unwrap();
+    assert_eq!(summary.entries, 2);
+    assert_eq!(summary.deduplicated, 1);
+
+    let entries = packager::read_manifest(&output).unwrap();
+    assert_eq!(entries[0].path, "data.txt");
+    assert_eq!(entries[0].mode, 0o600);
+    assert_eq!(entries[1].path, "copy.txt");
+    assert_eq!(entries[1].kind, EntryKind::File);
+    assert_eq!(entries[1].offset, entries[0].offset);
+
+    packager::unpack_directory(&output, &root.join("output")).unwrap();
+    assert_eq!(fs::read(root.join("output/copy.txt")).unwrap(), b"hello");
+}
+
+#[test]
+fn test_import_rejects_unsafe_paths() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+
+    let input = root.join("evil.zip");
+    let mut zip = zip::ZipWriter::new(File::create(&input).unwrap());
+    zip.start_file("../evil.txt", zip::write::FileOptions::default()).unwrap();
+    zip.write_all(b"evil").unwrap();
+    zip.finish().unwrap();
+    let error = packager::import_archive(&input, &root.join("evil.sspk"), &PackOptions::dev()).unwrap_err();
+    assert!(error.downcast_ref::<packager::UnsafePathError>().is_some(), "unexpected error: {error}");
+
+    let input = root.join("escape.zip");
+    let mut zip = zip::ZipWriter::new(File::create(&input).unwrap());
+    zip.add_symlink("link", "../../etc/passwd", zip::write::FileOptions::default()).unwrap();
+    zip.finish().unwrap();
+    assert!(packager::import_archive(&input, &root.join("escape.sspk"), &PackOptions::dev()).is_err());
+
+    // 超长的符号链接目标不整体读入内存
+    let input = root.join("long-link.zip");
+    let mut zip = zip::ZipWriter::new(File::create(&input).unwrap());
+    zip.add_symlink("link", "a/".repeat(64 * 1024), zip::write::FileOptions::default()).unwrap();
+    zip.finish().unwrap();
+    let error = packager::import_archive(&input, &root.join("long-link.sspk"), &PackOptions::dev()).unwrap_err();
+    assert!(error.to_string().contains("exceeds limit"), "unexpected error: {error}");
+
+    // 不支持的扩展名
+    let archive = create_archive(root);
+    let error = packager::export_archive(&archive, &root.join("payload.rar"), &PackOptions::dev(), &UnpackOptions::default()).unwrap_err();
+    assert!(error.to_string().contains("Unsupported export format"), "unexpected error: {error}");
+}
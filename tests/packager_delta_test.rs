@@ -0,0 +1,192 @@
+// SeeSea Installer - Packager Delta Tests
+// 测试版本间增量补丁包的生成和原地应用
+
+mod common;
+
+use seesea_installer::packager::delta::{self, DeltaArchive, DeltaOp};
+use seesea_installer::packager::{self, EntryKind, PackOptions, SignaturePolicy, signing};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// 创建旧版本和新版本目录，返回新版本中变化的大文件内容
+fn create_versions(root: &Path) -> Vec<u8> {
+    let base = root.join("v1");
+    let new = root.join("v2");
+    let model = common::random_bytes(1024 * 1024, 0x2545_F491_4F6C_DD1D);
+
+    fs::create_dir_all(base.join("lib/old")).unwrap();
+    fs::write(base.join("model.bin"), &model).unwrap();
+    fs::write(base.join("readme.txt"), b"unchanged").unwrap();
+    fs::write(base.join("lib/moved.txt"), b"moved content").unwrap();
+    fs::write(base.join("lib/old/removed.txt"), b"removed").unwrap();
+    fs::write(base.join("plugins"), b"was a file").unwrap();
+
+    // 新版本: 大文件中间改动少量字节，移动一个文件，删除一个目录，文件变为目录，新增文件
+    let mut new_model = model;
+    new_model[512 * 1024..512 * 1024 + 16].copy_from_slice(b"version 2 update");
+    fs::create_dir_all(new.join("lib")).unwrap();
+    fs::create_dir_all(new.join("plugins")).unwrap();
+    fs::write(new.join("model.bin"), &new_model).unwrap();
+    fs::write(new.join("readme.txt"), b"unchanged").unwrap();
+    fs::write(new.join("lib/renamed.txt"), b"moved content").unwrap();
+    fs::write(new.join("plugins/added.txt"), b"added").unwrap();
+    new_model
+}
+
+/// 比较两个目录的条目和文件内容
+fn assert_same_tree(expected: &Path, actual: &Path) {
+    let list = |root: &Path| {
+        let mut paths: Vec<_> = walkdir::WalkDir::new(root)
+            .min_depth(1)
+            .into_iter()
+            .map(|entry| entry.unwrap().path().strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        paths.sort();
+        paths
+    };
+    let paths = list(expected);
+    assert_eq!(paths, list(actual));
+    for path in paths.iter().filter(|path| expected.join(path).is_file()) {
+        assert_eq!(fs::read(expected.join(path)).unwrap(), fs::read(actual.join(path)).unwrap(), "{path:?}");
+    }
+}
+
+/// 复制目录作为已安装的旧版本
+fn install_copy(source: &Path, target: &Path) {
+    for entry in walkdir::WalkDir::new(source) {
+        let entry = entry.unwrap();
+        let path = target.join(entry.path().strip_prefix(source).unwrap());
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&path).unwrap();
+        } else {
+            fs::copy(entry.path(), &path).unwrap();
+        }
+    }
+}
+
+#[test]
+fn test_create_and_apply_delta() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let new_model = create_versions(root);
+    let delta_file = root.join("update.sspd");
+    let install = root.join("install");
+    install_copy(&root.join("v1"), &install);
+
+    packager::create_delta(&root.join("v1"), &root.join("v2"), &delta_file, &PackOptions::dev()).unwrap();
+
+    // 操作表: 未变化和移动的文件引用旧文件，大文件为差分，删除的条目单独列出
+    let archive = DeltaArchive::open(&delta_file).unwrap();
+    let op = |path: &str, op: DeltaOp| archive.entries().iter().find(|e| e.entry.path == path && e.op == op).cloned();
+    assert_eq!(op("readme.txt", DeltaOp::Keep).unwrap().base_path, "readme.txt");
+    assert_eq!(op("lib/renamed.txt", DeltaOp::Keep).unwrap().base_path, "lib/moved.txt");
+    assert!(op("model.bin", DeltaOp::Patch).is_some());
+    assert!(op("plugins/added.txt", DeltaOp::Add).is_some());
+    assert!(op("lib/moved.txt", DeltaOp::Delete).is_some());
+    assert_eq!(op("lib/old", DeltaOp::Delete).unwrap().entry.kind, EntryKind::Directory);
+    assert_eq!(op("plugins", DeltaOp::Delete).unwrap().entry.kind, EntryKind::File);
+    assert_eq!(op("plugins", DeltaOp::Add).unwrap().entry.kind, EntryKind::Directory);
+
+    // 1 MiB不可压缩文件的差分只需要很少的数据
+    let delta_len = fs::metadata(&delta_file).unwrap().len();
+    assert!(delta_len < 16 * 1024, "delta is {delta_len} bytes");
+
+    packager::apply_delta(&delta_file, &install, &SignaturePolicy::Ignore).unwrap();
+    assert_same_tree(&root.join("v2"), &install);
+    assert_eq!(fs::read(install.join("model.bin")).unwrap(), new_model);
+    assert!(!install.join(".sspk-delta-staging").exists());
+}
+
+#[test]
+fn test_large_changed_file_is_stored_whole() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let new_model = create_versions(root);
+    let delta_file = root.join("update.sspd");
+    let install = root.join("install");
+    install_copy(&root.join("v1"), &install);
+
+    // 超过差分大小上限的变化文件不读入内存生成差分，而是作为新增条目完整存储
+    let options = PackOptions { patch_size_limit: 512 * 1024, ..PackOptions::dev() };
+    packager::create_delta(&root.join("v1"), &root.join("v2"), &delta_file, &options).unwrap();
+    let archive = DeltaArchive::open(&delta_file).unwrap();
+    let model = archive.entries().iter().find(|e| e.entry.path == "model.bin").unwrap();
+    assert_eq!(model.op, DeltaOp::Add);
+    assert!(model.base_path.is_empty());
+    assert!(!archive.entries().iter().any(|e| e.op == DeltaOp::Patch));
+    assert!(fs::metadata(&delta_file).unwrap().len() > new_model.len() as u64);
+
+    packager::apply_delta(&delta_file, &install, &SignaturePolicy::Ignore).unwrap();
+    assert_same_tree(&root.join("v2"), &install);
+
+    // 上限不能超过应用端接受的大小
+    let options = PackOptions { patch_size_limit: packager::options::MAX_PATCH_SIZE + 1, ..PackOptions::dev() };
+    assert!(packager::create_delta(&root.join("v1"), &root.join("v2"), &root.join("invalid.sspd"), &options).is_err());
+}
+
+#[test]
+fn test_reject_mismatched_base() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    create_versions(root);
+    let delta_file = root.join("update.sspd");
+    let install = root.join("install");
+    install_copy(&root.join("v1"), &install);
+    packager::create_delta(&root.join("v1"), &root.join("v2"), &delta_file, &PackOptions::dev()).unwrap();
+
+    // 已安装的文件被修改过，不是补丁包对应的旧版本
+    fs::write(install.join("readme.txt"), b"locally modified").unwrap();
+    let error = packager::apply_delta(&delta_file, &install, &SignaturePolicy::Ignore).unwrap_err();
+    assert!(error.to_string().contains("does not match the base version"), "unexpected error: {error}");
+
+    // 目录保持不变
+    assert!(install.join("lib/old/removed.txt").exists());
+    assert!(!install.join("plugins/added.txt").exists());
+    assert_eq!(fs::read(install.join("model.bin")).unwrap(), fs::read(root.join("v1/model.bin")).unwrap());
+    assert!(!install.join(".sspk-delta-staging").exists());
+}
+
+#[test]
+fn test_signed_delta() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    create_versions(root);
+    let delta_file = root.join("update.sspd");
+    let install = root.join("install");
+    install_copy(&root.join("v1"), &install);
+
+    let key = signing::generate_signing_key().unwrap();
+    let options = PackOptions { signing_key: Some(key.clone()), ..PackOptions::dev() };
+    packager::create_delta(&root.join("v1"), &root.join("v2"), &delta_file, &options).unwrap();
+
+    // 不受信任的公钥和篡改过的补丁包都被拒绝，且不修改目录
+    let other = signing::generate_signing_key().unwrap();
+    let untrusted = SignaturePolicy::Require(vec![other.verifying_key()]);
+    assert!(packager::apply_delta(&delta_file, &install, &untrusted).is_err());
+
+    let original = fs::read(&delta_file).unwrap();
+    let mut tampered = original.clone();
+    tampered[20] ^= 0x01;
+    fs::write(&delta_file, &tampered).unwrap();
+    let trusted = SignaturePolicy::Require(vec![key.verifying_key()]);
+    assert!(packager::apply_delta(&delta_file, &install, &trusted).is_err());
+    assert!(install.join("lib/moved.txt").exists());
+
+    fs::write(&delta_file, &original).unwrap();
+    packager::apply_delta(&delta_file, &install, &trusted).unwrap();
+    assert_same_tree(&root.join("v2"), &install);
+}
+
+#[test]
+fn test_reject_non_delta_file() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    create_versions(root);
+    let archive = root.join("payload.sspk");
+    packager::pack_directory_with(&root.join("v2"), &archive, &PackOptions::dev()).unwrap();
+
+    let error = DeltaArchive::open(&archive).err().unwrap();
+    assert!(error.to_string().contains("Not a SeeSea delta archive"), "unexpected error: {error}");
+    assert!(delta::apply_delta(&archive, &root.join("v1"), &SignaturePolicy::Ignore).is_err());
+}
@@ -0,0 +1,126 @@
+// SeeSea Installer - Packager Embed Tests
+// 测试安装包嵌入可执行文件（自解压安装程序）的功能
+
+use seesea_installer::packager::{self, PackOptions, UnpackOptions, embed};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+const STUB: &[u8] = b"\x7fELF fake installer binary";
+const CONFIG: &str = "[project]\nname = \"seesea\"\nversion = \"1.0.0\"\n";
+
+/// 创建程序本体、源目录和配置文件
+fn create_bundle_inputs(root: &Path) {
+    fs::write(root.join("stub"), STUB).unwrap();
+    fs::write(root.join("install.toml"), CONFIG).unwrap();
+    fs::create_dir_all(root.join("building/lib")).unwrap();
+    fs::write(root.join("building/seesea-1.0.0-py3-none-any.whl"), b"wheel contents").unwrap();
+    fs::write(root.join("building/lib/config.toml"), b"key = \"value\"\n").unwrap();
+}
+
+/// 生成自解压安装程序
+fn bundle(root: &Path, stub: &Path, output: &Path) {
+    let config = root.join("install.toml");
+    packager::bundle_installer(stub, &root.join("building"), Some(&config), output, &PackOptions::dev()).unwrap();
+}
+
+#[test]
+fn test_bundle_and_extract_embedded_payload() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let installer = root.join("seesea-setup");
+    let output = root.join("install");
+    create_bundle_inputs(root);
+
+    bundle(root, &root.join("stub"), &installer);
+
+    // 程序本体保持不变，尾部定位安装包和配置
+    let bytes = fs::read(&installer).unwrap();
+    assert_eq!(&bytes[..STUB.len()], STUB);
+    let payload = embed::find_embedded(&installer).unwrap().unwrap();
+    assert_eq!(payload.stub_len(), STUB.len() as u64);
+    assert_eq!(embed::read_embedded_config(&installer).unwrap().as_deref(), Some(CONFIG));
+
+    // 安装程序本身可以直接作为安装包读取和解压
+    let manifest = packager::read_manifest(&installer).unwrap();
+    assert!(manifest.iter().any(|e| e.path == "lib/config.toml"));
+    packager::unpack_directory(&installer, &output).unwrap();
+    assert_eq!(fs::read(output.join("seesea-1.0.0-py3-none-any.whl")).unwrap(), b"wheel contents");
+
+    // 只恢复损坏的文件
+    fs::write(output.join("lib/config.toml"), b"tampered").unwrap();
+    assert_eq!(packager::repair_directory(&installer, &output, &UnpackOptions::default()).unwrap(), ["lib/config.toml"]);
+    assert_eq!(fs::read(output.join("lib/config.toml")).unwrap(), b"key = \"value\"\n");
+}
+
+#[test]
+fn test_rebundle_replaces_payload() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let first = root.join("first");
+    let second = root.join("second");
+    create_bundle_inputs(root);
+
+    bundle(root, &root.join("stub"), &first);
+    fs::write(root.join("building/extra.txt"), b"extra").unwrap();
+    bundle(root, &first, &second);
+
+    // 以已嵌入内容的安装程序为本体时，只保留原来的程序本体
+    let payload = embed::find_embedded(&second).unwrap().unwrap();
+    assert_eq!(payload.stub_len(), STUB.len() as u64);
+    let manifest = packager::read_manifest(&second).unwrap();
+    assert!(manifest.iter().any(|e| e.path == "extra.txt"));
+}
+
+#[test]
+fn test_executable_without_payload() {
+    let temp_dir = tempdir().unwrap();
+    let stub = temp_dir.path().join("stub");
+    fs::write(&stub, STUB).unwrap();
+
+    assert_eq!(embed::find_embedded(&stub).unwrap(), None);
+    assert_eq!(embed::read_embedded_config(&stub).unwrap(), None);
+    assert!(packager::read_manifest(&stub).is_err());
+}
+
+#[test]
+fn test_reject_corrupted_trailer() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let installer = root.join("seesea-setup");
+    create_bundle_inputs(root);
+    bundle(root, &root.join("stub"), &installer);
+
+    // 篡改尾部记录的安装包长度
+    let mut bytes = fs::read(&installer).unwrap();
+    let len_pos = bytes.len() - embed::TRAILER_LEN as usize + 8;
+    bytes[len_pos] ^= 0x01;
+    fs::write(&installer, &bytes).unwrap();
+
+    let error = embed::find_embedded(&installer).unwrap_err();
+    assert!(error.to_string().contains("corrupted"), "unexpected error: {error}");
+    assert!(packager::unpack_directory(&installer, &root.join("output")).is_err());
+}
+
+#[test]
+fn test_bundle_over_stub() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let stub = root.join("stub");
+    create_bundle_inputs(root);
+
+    // 输出路径就是程序本体（或指向它的路径）时，程序本体不能在复制前被截断
+    bundle(root, &stub, &root.join("building/../stub"));
+    let bytes = fs::read(&stub).unwrap();
+    assert_eq!(&bytes[..STUB.len()], STUB);
+    assert_eq!(embed::find_embedded(&stub).unwrap().unwrap().stub_len(), STUB.len() as u64);
+
+    // 再次打包到同一个文件时替换原有的嵌入内容
+    fs::write(root.join("building/extra.txt"), b"extra").unwrap();
+    bundle(root, &stub, &stub);
+    assert_eq!(embed::find_embedded(&stub).unwrap().unwrap().stub_len(), STUB.len() as u64);
+    assert!(packager::read_manifest(&stub).unwrap().iter().any(|e| e.path == "extra.txt"));
+    packager::unpack_directory(&stub, &root.join("output")).unwrap();
+    assert_eq!(fs::read(root.join("output/extra.txt")).unwrap(), b"extra");
+    assert!(!fs::read_dir(root).unwrap().any(|e| e.unwrap().file_name().to_string_lossy().ends_with("-partial")));
+}
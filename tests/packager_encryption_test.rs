@@ -0,0 +1,141 @@
+// SeeSea Installer - Packager Encryption Tests
+// 测试口令加密安装包的打包和解压、口令错误和篡改检测，以及与分卷、自解压安装程序的组合
+
+use seesea_installer::packager::encryption::ENCRYPTED_MAGIC;
+use seesea_installer::packager::volume::MIN_VOLUME_SIZE;
+use seesea_installer::packager::{self, PackOptions, Passphrase, UnpackOptions};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// 创建包含可识别明文的源目录
+fn create_source(root: &Path) -> std::path::PathBuf {
+    let source = root.join("building");
+    fs::create_dir_all(source.join("config")).unwrap();
+    fs::write(source.join("config/secret.toml"), "api_key = \"PLAINTEXT-MARKER\"\n".repeat(5000)).unwrap();
+    fs::write(source.join("app.txt"), b"application").unwrap();
+    // 不可压缩的数据，使密文跨越多个块
+    let noise: Vec<u8> = (0..200_000u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8).collect();
+    fs::write(source.join("model.bin"), noise).unwrap();
+    source
+}
+
+fn encrypted_options(passphrase: &str) -> PackOptions {
+    PackOptions { passphrase: Some(Passphrase::new(passphrase).unwrap()), ..PackOptions::dev() }
+}
+
+fn unpack_options(passphrase: &str) -> UnpackOptions {
+    UnpackOptions { passphrase: Some(Passphrase::new(passphrase).unwrap()), ..Default::default() }
+}
+
+#[test]
+fn test_encrypted_round_trip() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let source = create_source(root);
+    let archive = root.join("payload.sspk");
+    packager::pack_directory_with(&source, &archive, &encrypted_options("correct horse")).unwrap();
+
+    // 密文中不出现明文和原生格式魔数
+    let bytes = fs::read(&archive).unwrap();
+    assert!(bytes.starts_with(&ENCRYPTED_MAGIC));
+    assert!(!bytes.windows(16).any(|window| window == b"PLAINTEXT-MARKER"));
+    assert!(packager::is_encrypted(&archive).unwrap());
+
+    let options = unpack_options("correct horse");
+    assert_eq!(packager::read_manifest_with(&archive, &options).unwrap().len(), 4);
+    assert_eq!(packager::verify_archive_with(&archive, &options).unwrap().entries, 4);
+    packager::unpack_directory_with(&archive, &root.join("output"), &options).unwrap();
+    assert_eq!(
+        fs::read(root.join("output/config/secret.toml")).unwrap(),
+        fs::read(source.join("config/secret.toml")).unwrap()
+    );
+    assert_eq!(packager::unpack_paths(&archive, &root.join("partial"), &["app.txt"], &options).unwrap(), 1);
+
+    // 没有口令或口令错误时拒绝读取
+    let error = packager::read_manifest(&archive).unwrap_err();
+    assert!(error.to_string().contains("passphrase is required"), "unexpected error: {error}");
+    let error = packager::unpack_directory_with(&archive, &root.join("wrong"), &unpack_options("wrong horse")).unwrap_err();
+    assert!(error.to_string().contains("Wrong passphrase"), "unexpected error: {error}");
+    assert!(!root.join("wrong/app.txt").exists());
+}
+
+#[test]
+fn test_tampered_archive_is_not_extracted() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let source = create_source(root);
+    let archive = root.join("payload.sspk");
+    packager::pack_directory_with(&source, &archive, &encrypted_options("passphrase")).unwrap();
+    let original = fs::read(&archive).unwrap();
+    let options = unpack_options("passphrase");
+
+    // 修改最后一块中的一个字节，第一块仍能解密，错误在读取时发现
+    let mut tampered = original.clone();
+    let index = tampered.len() - 20;
+    tampered[index] ^= 0x01;
+    fs::write(&archive, &tampered).unwrap();
+    let error = packager::unpack_directory_with(&archive, &root.join("output"), &options).unwrap_err();
+    assert!(format!("{error:#}").contains("failed authentication"), "unexpected error: {error:#}");
+    assert!(!root.join("output/app.txt").exists());
+
+    // 截断末尾
+    fs::write(&archive, &original[..original.len() - 100]).unwrap();
+    assert!(packager::unpack_directory_with(&archive, &root.join("output"), &options).is_err());
+    assert!(!root.join("output/app.txt").exists());
+
+    // 修改文件头中的密钥派生参数
+    let mut tampered = original.clone();
+    tampered[16] ^= 0x01;
+    fs::write(&archive, &tampered).unwrap();
+    assert!(packager::unpack_directory_with(&archive, &root.join("output"), &options).is_err());
+
+    fs::write(&archive, &original).unwrap();
+    packager::unpack_directory_with(&archive, &root.join("output"), &options).unwrap();
+}
+
+#[test]
+fn test_encrypted_volumes_and_installer() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let source = create_source(root);
+    let options = unpack_options("passphrase");
+
+    // 先加密后拆分
+    let archive = root.join("payload.sspk");
+    let pack_options = PackOptions { volume_size: Some(MIN_VOLUME_SIZE), ..encrypted_options("passphrase") };
+    let summary = packager::pack_directory_with(&source, &archive, &pack_options).unwrap();
+    assert!(summary.volumes.len() > 1);
+    assert!(packager::is_encrypted(&archive).unwrap());
+    packager::unpack_directory_with(&archive, &root.join("output"), &options).unwrap();
+    assert_eq!(fs::read(root.join("output/model.bin")).unwrap(), fs::read(source.join("model.bin")).unwrap());
+
+    // 嵌入自解压安装程序
+    let stub = root.join("stub");
+    fs::write(&stub, b"#!/bin/sh\nexit 0\n").unwrap();
+    let setup = root.join("setup");
+    packager::bundle_installer(&stub, &source, None, &setup, &encrypted_options("passphrase")).unwrap();
+    assert!(packager::is_encrypted(&setup).unwrap());
+    assert_eq!(packager::read_manifest_with(&setup, &options).unwrap().len(), 4);
+    assert!(packager::read_manifest_with(&setup, &unpack_options("other")).is_err());
+}
+
+#[test]
+fn test_passphrase_sources_and_options() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+
+    // 口令文件去掉末尾的换行符，但保留其他空白
+    fs::write(root.join("passphrase.txt"), " spaced passphrase \r\n").unwrap();
+    let passphrase = Passphrase::from_file(&root.join("passphrase.txt")).unwrap();
+    assert_eq!(passphrase, Passphrase::new(" spaced passphrase ").unwrap());
+    assert_eq!(format!("{passphrase:?}"), "Passphrase(..)");
+
+    fs::write(root.join("empty.txt"), "\n").unwrap();
+    assert!(Passphrase::from_file(&root.join("empty.txt")).is_err());
+    assert!(Passphrase::new("").is_err());
+
+    // 加密输出每次都不同，不能用于可重现打包
+    let options = PackOptions { deterministic: true, ..encrypted_options("passphrase") };
+    assert!(options.validate().is_err());
+}
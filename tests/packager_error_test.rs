@@ -0,0 +1,266 @@
+// SeeSea Installer - Packager Error Tests
+// 向解压、清单读取和校验接口输入截断和翻转比特的安装包和补丁包，检查不会panic且错误被正确归类
+
+use seesea_installer::packager::encryption::ENCRYPTION_HEADER_LEN;
+use seesea_installer::packager::{self, PackOptions, PackagerError, Passphrase, SignaturePolicy, UnpackOptions};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// 打包一个包含压缩文件、原样存储文件、空目录和符号链接的安装包，返回其字节
+fn sample_archive(root: &Path, options: &PackOptions) -> Vec<u8> {
+    let source = root.join("building");
+    fs::create_dir_all(source.join("lib/empty")).unwrap();
+    fs::write(source.join("app.txt"), "compressible text\n".repeat(64)).unwrap();
+    fs::write(source.join("lib/data.zip"), b"already compressed").unwrap();
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("app.txt", source.join("link")).unwrap();
+
+    let archive = root.join("sample.sspk");
+    packager::pack_directory_with(&source, &archive, options).unwrap();
+    fs::read(&archive).unwrap()
+}
+
+/// 生成一个包含未变化、差分、新增和删除操作的补丁包，返回旧版本目录和补丁包的字节
+fn sample_delta(root: &Path) -> (std::path::PathBuf, Vec<u8>) {
+    let (v1, v2) = (root.join("v1"), root.join("v2"));
+    for dir in [&v1, &v2] {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join("kept.txt"), "unchanged\n").unwrap();
+    }
+    fs::write(v1.join("app.txt"), "version 1\n".repeat(64)).unwrap();
+    fs::write(v2.join("app.txt"), "version 2\n".repeat(64)).unwrap();
+    fs::write(v1.join("old.txt"), "removed").unwrap();
+    fs::write(v2.join("new.txt"), "added").unwrap();
+
+    let delta = root.join("sample.sspd");
+    packager::create_delta(&v1, &v2, &delta, &PackOptions::dev()).unwrap();
+    (v1, fs::read(&delta).unwrap())
+}
+
+/// 应用补丁必须失败且不修改目标目录，错误为 PackagerError
+fn expect_delta_rejected(delta: &Path, target: &Path, case: &str) -> PackagerError {
+    let error = match packager::apply_delta(delta, target, &SignaturePolicy::Ignore).unwrap_err().downcast::<PackagerError>() {
+        Ok(error) => error,
+        Err(error) => panic!("{case}: expected a PackagerError, got: {error:?}"),
+    };
+    assert!(!matches!(error, PackagerError::Io(_)), "{case}: damaged data reported as an I/O error: {error}");
+    assert_eq!(fs::read_to_string(target.join("app.txt")).unwrap(), "version 1\n".repeat(64), "{case}: target was modified");
+    assert!(target.join("old.txt").exists() && !target.join("new.txt").exists(), "{case}: target was modified");
+    error
+}
+
+/// 解压、读取清单和完整校验都必须失败，且错误为 PackagerError，返回解压的错误
+fn expect_rejected(archive: &Path, output: &Path, options: &UnpackOptions, case: &str) -> PackagerError {
+    let classify = |error: anyhow::Error| match error.downcast::<PackagerError>() {
+        Ok(error) => error,
+        Err(error) => panic!("{case}: expected a PackagerError, got: {error:?}"),
+    };
+    if let Ok(entries) = packager::read_manifest_with(archive, options) {
+        // 只破坏数据帧时目录表仍然可读，但完整校验必须发现
+        assert!(!entries.is_empty(), "{case}");
+    }
+    let verified = packager::verify_archive_with(archive, options);
+    assert!(verified.is_err(), "{case}: verification accepted a damaged archive");
+    classify(verified.unwrap_err());
+
+    let error = classify(packager::unpack_directory_with(archive, output, options).unwrap_err());
+    assert!(!matches!(error, PackagerError::Io(_)), "{case}: damaged data reported as an I/O error: {error}");
+    error
+}
+
+#[test]
+fn test_truncated_archives() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let bytes = sample_archive(root, &PackOptions::dev());
+    let archive = root.join("truncated.sspk");
+    let output = root.join("output");
+
+    for len in 0..bytes.len() {
+        fs::write(&archive, &bytes[..len]).unwrap();
+        let case = format!("truncated to {len} of {} bytes", bytes.len());
+        let error = expect_rejected(&archive, &output, &UnpackOptions::default(), &case);
+        if len < 8 {
+            assert!(matches!(error, PackagerError::BadMagic(_)), "{case}: {error}");
+        } else {
+            assert!(matches!(error, PackagerError::Truncated(_)), "{case}: {error}");
+        }
+    }
+    assert!(!output.join("app.txt").exists());
+}
+
+#[test]
+fn test_bit_flipped_archives() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let bytes = sample_archive(root, &PackOptions::dev());
+    let archive = root.join("flipped.sspk");
+    // 文件尾: toc_offset(8) | toc_size(8) | 整包摘要(32) | 魔数(8)
+    let footer = bytes.len() - 56;
+
+    for index in 0..bytes.len() {
+        let mut damaged = bytes.clone();
+        damaged[index] ^= 1 << (index % 8);
+        fs::write(&archive, &damaged).unwrap();
+        let output = root.join(format!("output-{index}"));
+        let case = format!("bit {} of byte {index} flipped", index % 8);
+        let error = expect_rejected(&archive, &output, &UnpackOptions::default(), &case);
+
+        let expected = match index {
+            0..8 => matches!(error, PackagerError::BadMagic(_)),
+            8..10 => matches!(error, PackagerError::UnsupportedVersion(_)),
+            // 标志位: 置上签名标志后签名块缺失，其他位不受支持
+            10..12 => matches!(error, PackagerError::Truncated(_) | PackagerError::UnsupportedVersion(_)),
+            // 数据帧和目录表: 整包摘要或zstd帧校验不符，目录表解码失败时为损坏
+            _ if index < footer => matches!(error, PackagerError::ChecksumMismatch(_) | PackagerError::Corrupted(_)),
+            _ if index < footer + 16 => matches!(error, PackagerError::Corrupted(_) | PackagerError::Truncated(_)),
+            _ if index < footer + 48 => matches!(error, PackagerError::ChecksumMismatch(_)),
+            _ => matches!(error, PackagerError::Truncated(_)),
+        };
+        assert!(expected, "{case}: unexpected classification {error:?}");
+        assert!(!output.join("app.txt").exists(), "{case}: files were extracted");
+    }
+}
+
+#[test]
+fn test_damaged_encrypted_archives() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let passphrase = Passphrase::new("fuzz").unwrap();
+    let bytes = sample_archive(root, &PackOptions { passphrase: Some(passphrase.clone()), ..PackOptions::dev() });
+    let archive = root.join("encrypted.sspk");
+    let output = root.join("output");
+    let options = UnpackOptions { passphrase: Some(passphrase), ..Default::default() };
+
+    // 密钥派生较慢，只抽样检查文件头之后的位置
+    let header = ENCRYPTION_HEADER_LEN as usize;
+    for len in [header, header + 1, header + 16, bytes.len() / 2, bytes.len() - 1] {
+        fs::write(&archive, &bytes[..len]).unwrap();
+        let case = format!("encrypted archive truncated to {len} bytes");
+        let error = expect_rejected(&archive, &output, &options, &case);
+        assert!(matches!(error, PackagerError::Truncated(_) | PackagerError::ChecksumMismatch(_)), "{case}: {error}");
+    }
+    for index in (header..bytes.len()).step_by(bytes.len() / 8) {
+        let mut damaged = bytes.clone();
+        damaged[index] ^= 0x80;
+        fs::write(&archive, &damaged).unwrap();
+        let case = format!("encrypted archive byte {index} flipped");
+        let error = expect_rejected(&archive, &output, &options, &case);
+        assert!(matches!(error, PackagerError::ChecksumMismatch(_)), "{case}: {error}");
+    }
+
+    let mut damaged = bytes.clone();
+    damaged[8] ^= 0x01;
+    fs::write(&archive, &damaged).unwrap();
+    let error = expect_rejected(&archive, &output, &options, "encrypted archive version");
+    assert!(matches!(error, PackagerError::UnsupportedVersion(_)), "{error}");
+    assert!(!output.join("app.txt").exists());
+}
+
+#[test]
+fn test_frame_checksum_mismatch() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let bytes = sample_archive(root, &PackOptions::dev());
+    let archive = root.join("checksum.sspk");
+
+    // 目录表帧末尾的4字节为zstd内容校验和，条目可以解码，读到帧结尾时才发现不符
+    let footer = bytes.len() - 56;
+    let toc_end = footer - 1;
+    let mut damaged = bytes.clone();
+    damaged[toc_end] ^= 0x01;
+    fs::write(&archive, &damaged).unwrap();
+    let error = packager::read_manifest(&archive).unwrap_err();
+    assert!(matches!(error.downcast_ref::<PackagerError>(), Some(PackagerError::ChecksumMismatch(_))), "{error:?}");
+
+    // 旧版格式同样区分帧校验和错误
+    let records = [&1u32.to_le_bytes()[..], b"a", &1u64.to_le_bytes(), b"x"].concat();
+    let mut encoder = zstd::Encoder::new(Vec::new(), 3).unwrap();
+    encoder.include_checksum(true).unwrap();
+    std::io::Write::write_all(&mut encoder, &records).unwrap();
+    let mut legacy = encoder.finish().unwrap();
+    *legacy.last_mut().unwrap() ^= 0x01;
+    let archive = root.join("legacy.bin");
+    fs::write(&archive, legacy).unwrap();
+    let error = packager::unpack_directory(&archive, &root.join("legacy")).unwrap_err();
+    assert!(matches!(error.downcast_ref::<PackagerError>(), Some(PackagerError::ChecksumMismatch(_))), "{error:?}");
+}
+
+#[test]
+fn test_truncated_legacy_archive_and_io_errors() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+
+    // 旧版格式: 路径长度前缀被截断
+    let mut records = Vec::new();
+    records.extend_from_slice(&5u32.to_le_bytes());
+    records.extend_from_slice(b"a.txt");
+    records.extend_from_slice(&2u64.to_le_bytes());
+    records.extend_from_slice(b"hi");
+    records.extend_from_slice(&[7, 0]);
+    let archive = root.join("legacy.bin");
+    fs::write(&archive, zstd::stream::encode_all(records.as_slice(), 3).unwrap()).unwrap();
+    let error = packager::unpack_directory(&archive, &root.join("legacy")).unwrap_err();
+    assert!(matches!(error.downcast_ref::<PackagerError>(), Some(PackagerError::Truncated(_))), "{error:?}");
+
+    // 文件不存在为I/O错误
+    let error = packager::read_manifest(&root.join("missing.sspk")).unwrap_err();
+    match error.downcast_ref::<PackagerError>() {
+        Some(PackagerError::Io(io)) => assert_eq!(io.kind(), std::io::ErrorKind::NotFound),
+        _ => panic!("expected an I/O error, got: {error:?}"),
+    }
+    // 来源为包装的I/O错误本身
+    let source = std::error::Error::source(error.downcast_ref::<PackagerError>().unwrap()).unwrap();
+    assert_eq!(source.downcast_ref::<std::io::Error>().unwrap().kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_truncated_deltas() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let (target, bytes) = sample_delta(root);
+    let delta = root.join("truncated.sspd");
+
+    for len in 0..bytes.len() {
+        fs::write(&delta, &bytes[..len]).unwrap();
+        let case = format!("delta truncated to {len} of {} bytes", bytes.len());
+        let error = expect_delta_rejected(&delta, &target, &case);
+        assert!(matches!(error, PackagerError::Truncated(_)), "{case}: {error}");
+    }
+}
+
+#[test]
+fn test_bit_flipped_deltas() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let (target, bytes) = sample_delta(root);
+    let delta = root.join("flipped.sspd");
+    let footer = bytes.len() - 56;
+    let toc_offset = u64::from_le_bytes(bytes[footer..footer + 8].try_into().unwrap()) as usize;
+
+    for index in 0..bytes.len() {
+        let mut damaged = bytes.clone();
+        damaged[index] ^= 1 << (index % 8);
+        fs::write(&delta, &damaged).unwrap();
+        let case = format!("delta bit {} of byte {index} flipped", index % 8);
+        let error = expect_delta_rejected(&delta, &target, &case);
+
+        let expected = match index {
+            0..8 => matches!(error, PackagerError::BadMagic(_)),
+            8..10 => matches!(error, PackagerError::UnsupportedVersion(_)),
+            10..12 => matches!(error, PackagerError::Truncated(_) | PackagerError::UnsupportedVersion(_)),
+            // 数据帧: 整包摘要或zstd帧校验不符
+            _ if index < toc_offset => matches!(error, PackagerError::ChecksumMismatch(_) | PackagerError::Corrupted(_)),
+            // 操作表: 条目在帧校验和之前解码和校验，可能先被判为损坏或不安全路径
+            _ if index < footer => matches!(
+                error,
+                PackagerError::ChecksumMismatch(_) | PackagerError::Corrupted(_) | PackagerError::UnsafePath(_)
+            ),
+            _ if index < footer + 16 => matches!(error, PackagerError::Corrupted(_) | PackagerError::Truncated(_)),
+            _ if index < footer + 48 => matches!(error, PackagerError::ChecksumMismatch(_)),
+            _ => matches!(error, PackagerError::Truncated(_)),
+        };
+        assert!(expected, "{case}: unexpected classification {error:?}");
+    }
+}
@@ -0,0 +1,132 @@
+// SeeSea Installer - Packager Security Tests
+// 使用恶意安装包样本测试解压路径安全检查
+
+mod common;
+
+use seesea_installer::packager::{self, EntryKind, PackagerError, UnsafePathReason};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// 解压安装包并返回不安全路径错误的原因
+fn unpack_rejection(archive: &Path, output: &Path) -> UnsafePathReason {
+    let error = packager::unpack_directory(archive, output).unwrap_err();
+    match error.downcast_ref::<PackagerError>() {
+        Some(PackagerError::UnsafePath(unsafe_path)) => unsafe_path.reason,
+        _ => panic!("expected PackagerError::UnsafePath, got: {error:?}"),
+    }
+}
+
+#[test]
+fn test_reject_malicious_paths() {
+    let corpus: [(&str, UnsafePathReason); 9] = [
+        ("../../etc/cron.d/x", UnsafePathReason::ParentDir),
+        ("lib/../../escape.txt", UnsafePathReason::ParentDir),
+        ("/usr/bin/x", UnsafePathReason::Absolute),
+        ("\\\\server\\share\\x", UnsafePathReason::Absolute),
+        ("C:/Windows/System32/x.dll", UnsafePathReason::Absolute),
+        ("..\\..\\escape.txt", UnsafePathReason::InvalidComponent),
+        ("lib//double.txt", UnsafePathReason::InvalidComponent),
+        ("./hidden.txt", UnsafePathReason::InvalidComponent),
+        ("", UnsafePathReason::Empty),
+    ];
+
+    for (path, expected) in corpus {
+        let temp_dir = tempdir().unwrap();
+        let archive = temp_dir.path().join("malicious.sspk");
+        let output = temp_dir.path().join("sandbox/output");
+        common::write_archive(&archive, &[(path, b"payload")]);
+
+        assert_eq!(unpack_rejection(&archive, &output), expected, "path: {path:?}");
+        assert!(!temp_dir.path().join("escape.txt").exists());
+        assert!(!temp_dir.path().join("sandbox/escape.txt").exists());
+    }
+}
+
+#[test]
+fn test_reject_malicious_legacy_paths() {
+    let temp_dir = tempdir().unwrap();
+    let archive = temp_dir.path().join("malicious.zst");
+    let output = temp_dir.path().join("sandbox/output");
+    common::write_legacy_archive(&archive, &[("../escape.txt", b"payload")]);
+
+    assert_eq!(unpack_rejection(&archive, &output), UnsafePathReason::ParentDir);
+    assert!(!temp_dir.path().join("sandbox/escape.txt").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_reject_symlink_escape() {
+    let temp_dir = tempdir().unwrap();
+    let archive = temp_dir.path().join("malicious.sspk");
+    let output = temp_dir.path().join("output");
+    let outside = temp_dir.path().join("outside");
+    fs::create_dir_all(&output).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+
+    // 目标目录中已存在指向外部的符号链接
+    std::os::unix::fs::symlink(&outside, output.join("link")).unwrap();
+    common::write_archive(&archive, &[("link/evil.sh", b"payload")]);
+
+    assert_eq!(unpack_rejection(&archive, &output), UnsafePathReason::SymlinkEscape);
+    assert!(!outside.join("evil.sh").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_replace_existing_symlink_target() {
+    let temp_dir = tempdir().unwrap();
+    let archive = temp_dir.path().join("payload.sspk");
+    let output = temp_dir.path().join("output");
+    let outside = temp_dir.path().join("outside.txt");
+    fs::create_dir_all(&output).unwrap();
+    fs::write(&outside, b"original").unwrap();
+
+    // 目标文件位置上的符号链接会被替换，而不是被写穿
+    std::os::unix::fs::symlink(&outside, output.join("config.toml")).unwrap();
+    common::write_archive(&archive, &[("config.toml", b"packaged")]);
+    packager::unpack_directory(&archive, &output).unwrap();
+
+    assert_eq!(fs::read(&outside).unwrap(), b"original");
+    assert_eq!(fs::read(output.join("config.toml")).unwrap(), b"packaged");
+}
+
+#[test]
+fn test_reject_escaping_symlink_entries() {
+    let corpus: [(&str, &[u8]); 5] = [
+        ("etc", b"/etc"),
+        ("lib/sideways", b"nested/../.."),
+        ("lib/up", b"../../outside"),
+        ("lib/drive", b"C:/Windows"),
+        ("lib/deep/up", b"../../../outside"),
+    ];
+
+    for (path, target) in corpus {
+        let temp_dir = tempdir().unwrap();
+        let archive = temp_dir.path().join("malicious.sspk");
+        let output = temp_dir.path().join("output");
+        common::write_typed_archive(&archive, &[(path, EntryKind::Symlink, target)]);
+
+        assert_eq!(unpack_rejection(&archive, &output), UnsafePathReason::SymlinkEscape, "link: {path:?}");
+        assert!(fs::symlink_metadata(output.join(path)).is_err());
+    }
+}
+
+#[test]
+fn test_reject_write_through_archived_symlink() {
+    let temp_dir = tempdir().unwrap();
+    let archive = temp_dir.path().join("malicious.sspk");
+    let output = temp_dir.path().join("output");
+
+    // 先放入一个看似无害的链接，再通过父目录链接写出目标目录
+    common::write_typed_archive(
+        &archive,
+        &[
+            ("lib", EntryKind::Directory, b""),
+            ("lib/link", EntryKind::Symlink, b".."),
+            ("lib/link/link2", EntryKind::Symlink, b"../.."),
+        ],
+    );
+
+    assert_eq!(unpack_rejection(&archive, &output), UnsafePathReason::SymlinkEscape);
+}
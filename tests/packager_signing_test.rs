@@ -0,0 +1,141 @@
+// SeeSea Installer - Packager Signing Tests
+// 测试安装包Ed25519签名和解压前的签名验证
+
+use clap::Parser;
+use seesea_installer::Args;
+use seesea_installer::packager::signing::{self, SIGNATURE_BLOCK_LEN, SigningKey};
+use seesea_installer::packager::{self, PackOptions, SignaturePolicy, UnpackOptions, format};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// 创建源目录并打包，指定密钥时对安装包签名
+fn pack(root: &Path, key: Option<&SigningKey>) -> std::path::PathBuf {
+    let source = root.join("building");
+    fs::create_dir_all(source.join("lib")).unwrap();
+    fs::write(source.join("app.txt"), b"application").unwrap();
+    fs::write(source.join("lib/config.toml"), b"key = \"value\"\n").unwrap();
+
+    let archive = root.join("payload.sspk");
+    let options = PackOptions { signing_key: key.cloned(), ..PackOptions::dev() };
+    packager::pack_directory_with(&source, &archive, &options).unwrap();
+    archive
+}
+
+fn require(key: &SigningKey) -> SignaturePolicy {
+    SignaturePolicy::Require(vec![key.verifying_key()])
+}
+
+#[test]
+fn test_signed_archive_verifies_with_trusted_key() {
+    let temp_dir = tempdir().unwrap();
+    let key = signing::generate_signing_key().unwrap();
+    let archive = pack(temp_dir.path(), Some(&key));
+
+    packager::verify_signature(&archive, &require(&key)).unwrap();
+
+    let output = temp_dir.path().join("output");
+    let options = UnpackOptions { signature: require(&key), ..Default::default() };
+    packager::unpack_directory_with(&archive, &output, &options).unwrap();
+    assert_eq!(fs::read(output.join("app.txt")).unwrap(), b"application");
+
+    // 不验证签名时带签名的安装包照常解压
+    packager::unpack_directory(&archive, &temp_dir.path().join("plain")).unwrap();
+}
+
+#[test]
+fn test_reject_untrusted_key() {
+    let temp_dir = tempdir().unwrap();
+    let key = signing::generate_signing_key().unwrap();
+    let other = signing::generate_signing_key().unwrap();
+    let archive = pack(temp_dir.path(), Some(&key));
+
+    let error = packager::verify_signature(&archive, &require(&other)).unwrap_err();
+    assert!(error.to_string().contains("untrusted key"), "unexpected error: {error}");
+
+    let output = temp_dir.path().join("output");
+    let options = UnpackOptions { signature: require(&other), ..Default::default() };
+    assert!(packager::unpack_directory_with(&archive, &output, &options).is_err());
+    assert!(!output.join("app.txt").exists());
+}
+
+#[test]
+fn test_unsigned_archive_policies() {
+    let temp_dir = tempdir().unwrap();
+    let key = signing::generate_signing_key().unwrap();
+    let archive = pack(temp_dir.path(), None);
+
+    let error = packager::verify_signature(&archive, &require(&key)).unwrap_err();
+    assert!(error.to_string().contains("not signed"), "unexpected error: {error}");
+    let error = packager::verify_signature(&archive, &SignaturePolicy::Require(Vec::new())).unwrap_err();
+    assert!(error.to_string().contains("No trusted public key"), "unexpected error: {error}");
+
+    packager::verify_signature(&archive, &SignaturePolicy::AllowUnsigned(vec![key.verifying_key()])).unwrap();
+    packager::verify_signature(&archive, &SignaturePolicy::Ignore).unwrap();
+}
+
+#[test]
+fn test_reject_tampered_signed_archive() {
+    let temp_dir = tempdir().unwrap();
+    let key = signing::generate_signing_key().unwrap();
+    let archive = pack(temp_dir.path(), Some(&key));
+    let original = fs::read(&archive).unwrap();
+
+    // 篡改数据区
+    let mut bytes = original.clone();
+    bytes[format::HEADER_LEN as usize] ^= 0x01;
+    fs::write(&archive, &bytes).unwrap();
+    assert!(packager::verify_signature(&archive, &require(&key)).is_err());
+
+    // 篡改文件尾中的整包摘要，签名不再匹配
+    let mut bytes = original.clone();
+    let digest_pos = bytes.len() - SIGNATURE_BLOCK_LEN as usize - format::FOOTER_LEN as usize + 16;
+    bytes[digest_pos] ^= 0x01;
+    fs::write(&archive, &bytes).unwrap();
+    let error = packager::verify_signature(&archive, &require(&key)).unwrap_err();
+    assert!(error.to_string().contains("signature is invalid"), "unexpected error: {error}");
+
+    // 即使允许未签名的安装包，无效的签名仍然被拒绝
+    let policy = SignaturePolicy::AllowUnsigned(vec![key.verifying_key()]);
+    assert!(packager::verify_signature(&archive, &policy).is_err());
+}
+
+#[test]
+fn test_reject_stripped_signature() {
+    let temp_dir = tempdir().unwrap();
+    let key = signing::generate_signing_key().unwrap();
+    let archive = pack(temp_dir.path(), Some(&key));
+    let mut bytes = fs::read(&archive).unwrap();
+    bytes.truncate(bytes.len() - SIGNATURE_BLOCK_LEN as usize);
+
+    // 只去掉签名块，文件头仍标记为已签名
+    fs::write(&archive, &bytes).unwrap();
+    assert!(packager::verify_signature(&archive, &SignaturePolicy::Ignore).is_err());
+
+    // 同时清除签名标志，整包摘要不再匹配
+    bytes[10] = 0;
+    fs::write(&archive, &bytes).unwrap();
+    let policy = SignaturePolicy::AllowUnsigned(vec![key.verifying_key()]);
+    assert!(packager::verify_signature(&archive, &policy).is_err());
+}
+
+#[test]
+fn test_signing_key_roundtrip() {
+    let temp_dir = tempdir().unwrap();
+    let path = temp_dir.path().join("signing.key");
+    let key = signing::generate_signing_key().unwrap();
+    signing::save_signing_key(&path, &key).unwrap();
+
+    // 不覆盖已有的密钥文件
+    assert!(signing::save_signing_key(&path, &key).is_err());
+    assert_eq!(signing::load_signing_key(&path).unwrap().to_bytes(), key.to_bytes());
+
+    let public_key = signing::public_key_hex(&key.verifying_key());
+    assert_eq!(signing::parse_public_key(&public_key).unwrap(), key.verifying_key());
+    assert!(signing::parse_public_key("not a key").is_err());
+
+    // 命令行指定的密钥进入打包参数
+    let args = Args::parse_from(["installer", "pack", "--signing-key", path.to_str().unwrap(), "--allow-unsigned"]);
+    assert!(args.allow_unsigned);
+    assert_eq!(args.pack_options().unwrap().signing_key.unwrap().to_bytes(), key.to_bytes());
+}
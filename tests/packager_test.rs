@@ -0,0 +1,476 @@
+// SeeSea Installer - Packager Module Tests
+// 测试打包模块的功能
+
+mod common;
+
+use clap::Parser;
+use seesea_installer::Args;
+use seesea_installer::packager::{self, ArchiveEntry, CompressionMethod, EntryKind, PackOptions, UnpackOptions, checksum, format};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// 创建测试用的源目录
+fn create_source_tree(root: &Path) {
+    fs::create_dir_all(root.join("lib/nested")).unwrap();
+    fs::write(root.join("seesea-1.0.0-py3-none-any.whl"), b"wheel contents").unwrap();
+    fs::write(root.join("lib/config.toml"), b"key = \"value\"\n").unwrap();
+    fs::write(root.join("lib/nested/data.bin"), vec![7u8; 64 * 1024]).unwrap();
+}
+
+#[test]
+fn test_pack_unpack_roundtrip() {
+    let temp_dir = tempdir().unwrap();
+    let source = temp_dir.path().join("building");
+    let archive = temp_dir.path().join("payload.sspk");
+    let output = temp_dir.path().join("output");
+    create_source_tree(&source);
+
+    packager::pack_directory(&source, &archive).unwrap();
+    packager::unpack_directory(&archive, &output).unwrap();
+
+    assert_eq!(fs::read(output.join("seesea-1.0.0-py3-none-any.whl")).unwrap(), b"wheel contents");
+    assert_eq!(fs::read(output.join("lib/config.toml")).unwrap(), b"key = \"value\"\n");
+    assert_eq!(fs::read(output.join("lib/nested/data.bin")).unwrap(), vec![7u8; 64 * 1024]);
+}
+
+#[test]
+fn test_archive_header_and_manifest() {
+    let temp_dir = tempdir().unwrap();
+    let source = temp_dir.path().join("building");
+    let archive = temp_dir.path().join("payload.sspk");
+    create_source_tree(&source);
+
+    packager::pack_directory(&source, &archive).unwrap();
+
+    // 验证文件头
+    let bytes = fs::read(&archive).unwrap();
+    assert_eq!(&bytes[..8], &format::MAGIC);
+    assert_eq!(u16::from_le_bytes([bytes[8], bytes[9]]), format::FORMAT_VERSION);
+
+    // 验证清单（3个文件和2个目录）
+    let manifest = packager::read_manifest(&archive).unwrap();
+    assert_eq!(manifest.len(), 5);
+    assert!(manifest.iter().any(|e| e.path == "lib/nested" && e.kind == EntryKind::Directory));
+    let data = manifest.iter().find(|e| e.path == "lib/nested/data.bin").unwrap();
+    assert_eq!(data.kind, EntryKind::File);
+    assert_eq!(data.size, 64 * 1024);
+    assert!(data.mtime > 0);
+}
+
+#[test]
+fn test_unpack_legacy_archive() {
+    let temp_dir = tempdir().unwrap();
+    let archive = temp_dir.path().join("legacy.zst");
+    let output = temp_dir.path().join("output");
+
+    // 按旧版格式手工构造安装包
+    common::write_legacy_archive(&archive, &[("a.txt", b"alpha"), ("dir/b.txt", b"beta")]);
+
+    packager::unpack_directory(&archive, &output).unwrap();
+
+    assert_eq!(fs::read(output.join("a.txt")).unwrap(), b"alpha");
+    assert_eq!(fs::read(output.join("dir/b.txt")).unwrap(), b"beta");
+}
+
+#[test]
+fn test_reject_non_payload_data() {
+    let temp_dir = tempdir().unwrap();
+    let archive = temp_dir.path().join("random.bin");
+    fs::write(&archive, b"this is definitely not an archive").unwrap();
+
+    let result = packager::unpack_directory(&archive, &temp_dir.path().join("output"));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_detect_truncated_archive() {
+    let temp_dir = tempdir().unwrap();
+    let source = temp_dir.path().join("building");
+    let archive = temp_dir.path().join("payload.sspk");
+    create_source_tree(&source);
+    packager::pack_directory(&source, &archive).unwrap();
+
+    // 截掉安装包末尾
+    let bytes = fs::read(&archive).unwrap();
+    fs::write(&archive, &bytes[..bytes.len() - 16]).unwrap();
+
+    let result = packager::unpack_directory(&archive, &temp_dir.path().join("output"));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_detect_corrupted_file() {
+    let temp_dir = tempdir().unwrap();
+    let archive = temp_dir.path().join("corrupted.sspk");
+    let output = temp_dir.path().join("output");
+
+    // 第二个文件的摘要与内容不符
+    let entries: [(&str, EntryKind, &[u8]); 2] = [
+        ("good.txt", EntryKind::File, b"good"),
+        ("seesea-core.whl", EntryKind::File, b"corrupted wheel"),
+    ];
+    let digests = [checksum::digest_bytes(b"good"), checksum::digest_bytes(b"original wheel")];
+    common::write_raw_archive(&archive, &entries, &digests);
+
+    let error = packager::unpack_directory(&archive, &output).unwrap_err();
+    assert!(error.to_string().contains("seesea-core.whl"), "unexpected error: {error}");
+    assert!(!output.join("seesea-core.whl").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_preserve_unix_metadata() {
+    use std::os::unix::fs::{PermissionsExt, symlink};
+
+    let temp_dir = tempdir().unwrap();
+    let source = temp_dir.path().join("building");
+    let archive = temp_dir.path().join("payload.sspk");
+    let output = temp_dir.path().join("output");
+    create_source_tree(&source);
+
+    // 可执行文件、私有文件、空目录和符号链接
+    fs::create_dir_all(source.join("bin")).unwrap();
+    fs::write(source.join("bin/seesea"), b"#!/bin/sh\necho seesea\n").unwrap();
+    fs::set_permissions(source.join("bin/seesea"), fs::Permissions::from_mode(0o755)).unwrap();
+    fs::set_permissions(source.join("lib/config.toml"), fs::Permissions::from_mode(0o600)).unwrap();
+    fs::create_dir_all(source.join("logs")).unwrap();
+    symlink("lib/nested", source.join("current")).unwrap();
+    symlink("../bin/seesea", source.join("lib/seesea-link")).unwrap();
+    let mtime = filetime::FileTime::from_unix_time(1_600_000_000, 0);
+    filetime::set_file_mtime(source.join("bin/seesea"), mtime).unwrap();
+    filetime::set_file_mtime(source.join("logs"), mtime).unwrap();
+
+    packager::pack_directory(&source, &archive).unwrap();
+    packager::unpack_directory(&archive, &output).unwrap();
+
+    let mode = |path: &str| fs::metadata(output.join(path)).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode("bin/seesea"), 0o755);
+    assert_eq!(mode("lib/config.toml"), 0o600);
+    assert!(output.join("logs").is_dir());
+    assert_eq!(fs::read_link(output.join("current")).unwrap(), Path::new("lib/nested"));
+    assert_eq!(fs::read_link(output.join("lib/seesea-link")).unwrap(), Path::new("../bin/seesea"));
+    assert_eq!(fs::read(output.join("current/data.bin")).unwrap(), vec![7u8; 64 * 1024]);
+
+    let restored = |path: &str| filetime::FileTime::from_last_modification_time(&fs::metadata(output.join(path)).unwrap());
+    assert_eq!(restored("bin/seesea"), mtime);
+    assert_eq!(restored("logs"), mtime);
+}
+
+#[test]
+fn test_unpack_single_entry_and_subtree() {
+    let temp_dir = tempdir().unwrap();
+    let source = temp_dir.path().join("building");
+    let archive = temp_dir.path().join("payload.sspk");
+    create_source_tree(&source);
+    packager::pack_directory(&source, &archive).unwrap();
+
+    // 目录表记录每个条目的数据帧位置
+    let manifest = packager::read_manifest(&archive).unwrap();
+    let wheel = manifest.iter().find(|e| e.path == "seesea-1.0.0-py3-none-any.whl").unwrap();
+    assert_eq!(wheel.size, 14);
+    assert!(wheel.offset >= format::HEADER_LEN && wheel.compressed_size > 0);
+
+    // 破坏其他条目的数据帧，不影响单独解压未受损的条目
+    let data = manifest.iter().find(|e| e.path == "lib/nested/data.bin").unwrap();
+    let mut bytes = fs::read(&archive).unwrap();
+    bytes[data.offset as usize + data.compressed_size as usize / 2] ^= 0xFF;
+    fs::write(&archive, &bytes).unwrap();
+
+    let single = temp_dir.path().join("single");
+    assert_eq!(packager::unpack_paths(&archive, &single, &["lib/config.toml"], &UnpackOptions::default()).unwrap(), 1);
+    assert_eq!(fs::read(single.join("lib/config.toml")).unwrap(), b"key = \"value\"\n");
+    assert!(!single.join("seesea-1.0.0-py3-none-any.whl").exists());
+
+    packager::pack_directory(&source, &archive).unwrap();
+    let subtree = temp_dir.path().join("subtree");
+    assert_eq!(packager::unpack_paths(&archive, &subtree, &["lib/nested/"], &UnpackOptions::default()).unwrap(), 2);
+    assert_eq!(fs::read(subtree.join("lib/nested/data.bin")).unwrap(), vec![7u8; 64 * 1024]);
+    assert!(!subtree.join("lib/config.toml").exists());
+
+    let error = packager::unpack_paths(&archive, &subtree, &["lib/missing"], &UnpackOptions::default()).unwrap_err();
+    assert!(error.to_string().contains("lib/missing"), "unexpected error: {error}");
+}
+
+#[test]
+fn test_repair_damaged_files() {
+    let temp_dir = tempdir().unwrap();
+    let source = temp_dir.path().join("building");
+    let archive = temp_dir.path().join("payload.sspk");
+    let install = temp_dir.path().join("install");
+    create_source_tree(&source);
+    packager::pack_directory(&source, &archive).unwrap();
+    packager::unpack_directory(&archive, &install).unwrap();
+
+    assert!(packager::repair_directory(&archive, &install, &UnpackOptions::default()).unwrap().is_empty());
+
+    // 修改一个文件、删除一个文件，其他文件保持不动
+    fs::write(install.join("lib/config.toml"), b"tampered").unwrap();
+    fs::remove_file(install.join("lib/nested/data.bin")).unwrap();
+    fs::write(install.join("extra.txt"), b"user data").unwrap();
+
+    let mut repaired = packager::repair_directory(&archive, &install, &UnpackOptions::default()).unwrap();
+    repaired.sort();
+    assert_eq!(repaired, ["lib/config.toml", "lib/nested/data.bin"]);
+    assert_eq!(fs::read(install.join("lib/config.toml")).unwrap(), b"key = \"value\"\n");
+    assert_eq!(fs::read(install.join("lib/nested/data.bin")).unwrap(), vec![7u8; 64 * 1024]);
+    assert_eq!(fs::read(install.join("extra.txt")).unwrap(), b"user data");
+}
+
+#[test]
+fn test_reject_oversized_declared_size() {
+    let temp_dir = tempdir().unwrap();
+    let archive = temp_dir.path().join("oversized.sspk");
+    let output = temp_dir.path().join("output");
+
+    // 目录表声明的大小远超实际数据，解压时不能按声明大小分配内存
+    let mut writer = checksum::HashingWriter::new(fs::File::create(&archive).unwrap());
+    format::write_header(&mut writer, 0).unwrap();
+    let frame = zstd::stream::encode_all(&b"only a few bytes"[..], 3).unwrap();
+    let entry = ArchiveEntry {
+        path: "huge.bin".to_string(),
+        kind: EntryKind::File,
+        mode: 0o644,
+        size: 1 << 60,
+        offset: writer.written(),
+        compressed_size: frame.len() as u64,
+        ..Default::default()
+    };
+    writer.write_all(&frame).unwrap();
+    common::write_toc(writer, &[entry]);
+
+    let error = packager::unpack_directory(&archive, &output).unwrap_err();
+    assert!(error.to_string().contains("truncated"), "unexpected error: {error}");
+    assert_eq!(fs::read_dir(&output).unwrap().count(), 0);
+}
+
+#[test]
+fn test_pack_with_options() {
+    let temp_dir = tempdir().unwrap();
+    let source = temp_dir.path().join("building");
+    create_source_tree(&source);
+
+    // 各种压缩参数组合打出的安装包都能正常解压
+    let custom = PackOptions { level: 1, threads: 2, long_window: Some(20), checksum: false, ..PackOptions::default() };
+    for (name, options) in [("dev", PackOptions::dev()), ("release", PackOptions::release()), ("custom", custom)] {
+        let archive = temp_dir.path().join(format!("{name}.sspk"));
+        let output = temp_dir.path().join(name);
+        packager::pack_directory_with(&source, &archive, &options).unwrap();
+        packager::unpack_directory(&archive, &output).unwrap();
+        assert_eq!(fs::read(output.join("lib/nested/data.bin")).unwrap(), vec![7u8; 64 * 1024]);
+    }
+
+    let invalid = PackOptions { long_window: Some(31), ..PackOptions::default() };
+    let error = packager::pack_directory_with(&source, &temp_dir.path().join("invalid.sspk"), &invalid).unwrap_err();
+    assert!(error.to_string().contains("long-distance window"), "unexpected error: {error}");
+}
+
+#[test]
+fn test_store_incompressible_entries() {
+    let temp_dir = tempdir().unwrap();
+    let source = temp_dir.path().join("building");
+    let archive = temp_dir.path().join("payload.sspk");
+    let output = temp_dir.path().join("output");
+
+    // 已压缩格式的扩展名、高熵数据和普通文本
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let random: Vec<u8> = (0..256 * 1024)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect();
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join("seesea-1.0.0-py3-none-any.WHL"), vec![b'a'; 4096]).unwrap();
+    fs::write(source.join("model.bin"), &random).unwrap();
+    fs::write(source.join("readme.txt"), b"SeeSea ".repeat(1024)).unwrap();
+
+    packager::pack_directory(&source, &archive).unwrap();
+
+    let manifest = packager::read_manifest(&archive).unwrap();
+    let method = |path: &str| manifest.iter().find(|e| e.path == path).unwrap().method;
+    assert_eq!(method("seesea-1.0.0-py3-none-any.WHL"), CompressionMethod::Stored);
+    assert_eq!(method("model.bin"), CompressionMethod::Stored);
+    assert_eq!(method("readme.txt"), CompressionMethod::Zstd);
+
+    packager::unpack_directory(&archive, &output).unwrap();
+    assert_eq!(fs::read(output.join("seesea-1.0.0-py3-none-any.WHL")).unwrap(), vec![b'a'; 4096]);
+    assert_eq!(fs::read(output.join("model.bin")).unwrap(), random);
+    assert_eq!(fs::read(output.join("readme.txt")).unwrap(), b"SeeSea ".repeat(1024));
+
+    // 关闭原样存储后所有条目都经过zstd压缩
+    let options = PackOptions { store_extensions: Vec::new(), detect_incompressible: false, ..PackOptions::default() };
+    packager::pack_directory_with(&source, &archive, &options).unwrap();
+    let manifest = packager::read_manifest(&archive).unwrap();
+    assert!(manifest.iter().all(|e| e.method == CompressionMethod::Zstd));
+}
+
+#[test]
+fn test_deduplicate_identical_files() {
+    let temp_dir = tempdir().unwrap();
+    let source = temp_dir.path().join("building");
+    let archive = temp_dir.path().join("payload.sspk");
+    let plain = temp_dir.path().join("plain.sspk");
+
+    // 两个组件携带同一个库文件，另有一个大小相同但内容不同的文件
+    let library: Vec<u8> = (0..128 * 1024u32).map(|i| (i * 7 % 253) as u8).collect();
+    let mut different = library.clone();
+    different[0] ^= 0xFF;
+    fs::create_dir_all(source.join("core/lib")).unwrap();
+    fs::create_dir_all(source.join("plugin/lib")).unwrap();
+    fs::write(source.join("core/lib/libshared.so"), &library).unwrap();
+    fs::write(source.join("plugin/lib/libshared.so"), &library).unwrap();
+    fs::write(source.join("plugin/lib/libother.so"), &different).unwrap();
+
+    packager::pack_directory(&source, &archive).unwrap();
+    let options = PackOptions { deduplicate: false, ..PackOptions::default() };
+    packager::pack_directory_with(&source, &plain, &options).unwrap();
+
+    let manifest = packager::read_manifest(&archive).unwrap();
+    let find = |path: &str| manifest.iter().find(|e| e.path == path).unwrap();
+    assert_eq!(find("core/lib/libshared.so").offset, find("plugin/lib/libshared.so").offset);
+    assert_ne!(find("core/lib/libshared.so").offset, find("plugin/lib/libother.so").offset);
+    assert!(fs::metadata(&archive).unwrap().len() < fs::metadata(&plain).unwrap().len());
+
+    // 默认每个路径各自写出一份，启用硬链接时共享数据的文件链接到一起
+    let copies = temp_dir.path().join("copies");
+    let linked = temp_dir.path().join("linked");
+    packager::unpack_directory(&archive, &copies).unwrap();
+    packager::unpack_directory_with(&archive, &linked, &UnpackOptions { hardlinks: true, ..Default::default() }).unwrap();
+    for output in [&copies, &linked] {
+        assert_eq!(fs::read(output.join("core/lib/libshared.so")).unwrap(), library);
+        assert_eq!(fs::read(output.join("plugin/lib/libshared.so")).unwrap(), library);
+        assert_eq!(fs::read(output.join("plugin/lib/libother.so")).unwrap(), different);
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let inode = |path: &Path| fs::metadata(path).unwrap().ino();
+        assert_ne!(inode(&copies.join("core/lib/libshared.so")), inode(&copies.join("plugin/lib/libshared.so")));
+        assert_eq!(inode(&linked.join("core/lib/libshared.so")), inode(&linked.join("plugin/lib/libshared.so")));
+        assert_ne!(inode(&linked.join("core/lib/libshared.so")), inode(&linked.join("plugin/lib/libother.so")));
+    }
+}
+
+#[test]
+fn test_cli_pack_options() {
+    // 单独指定的参数覆盖预设
+    let args = Args::try_parse_from(["seesea-installer", "pack", "--preset", "dev", "--level", "5", "--no-checksum"]).unwrap();
+    let options = args.pack_options().unwrap();
+    assert_eq!(options.level, 5);
+    assert_eq!(options.threads, PackOptions::dev().threads);
+    assert!(!options.checksum);
+
+    let args = Args::try_parse_from(["seesea-installer", "pack", "--preset", "release"]).unwrap();
+    assert_eq!(args.pack_options().unwrap(), PackOptions::release());
+
+    let args = Args::try_parse_from(["seesea-installer", "pack", "--level", "99"]).unwrap();
+    assert!(args.pack_options().is_err());
+}
+
+#[test]
+fn test_deterministic_packing() {
+    let temp_dir = tempdir().unwrap();
+    let first = temp_dir.path().join("first");
+    let second = temp_dir.path().join("second");
+
+    // 相同内容的两棵目录树：创建顺序、修改时间和权限不同
+    create_source_tree(&first);
+    fs::create_dir_all(second.join("lib/nested")).unwrap();
+    fs::write(second.join("lib/nested/data.bin"), vec![7u8; 64 * 1024]).unwrap();
+    fs::write(second.join("lib/config.toml"), b"key = \"value\"\n").unwrap();
+    fs::write(second.join("seesea-1.0.0-py3-none-any.whl"), b"wheel contents").unwrap();
+    let old = filetime::FileTime::from_unix_time(1_000_000, 0);
+    filetime::set_file_mtime(second.join("lib/config.toml"), old).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(second.join("lib/config.toml"), fs::Permissions::from_mode(0o600)).unwrap();
+    }
+
+    // 不同的线程数也生成相同的字节
+    let pack = |source: &Path, threads: u32| {
+        let archive = temp_dir.path().join("payload.sspk");
+        let options = PackOptions { threads, deterministic: true, source_date_epoch: Some(1_700_000_000), ..PackOptions::dev() };
+        packager::pack_directory_with(source, &archive, &options).unwrap();
+        checksum::digest_bytes(&fs::read(&archive).unwrap())
+    };
+    assert_eq!(pack(&first, 4), pack(&second, 1));
+
+    let archive = temp_dir.path().join("payload.sspk");
+    let manifest = packager::read_manifest(&archive).unwrap();
+    let paths: Vec<&str> = manifest.iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(paths, ["lib", "lib/config.toml", "lib/nested", "lib/nested/data.bin", "seesea-1.0.0-py3-none-any.whl"]);
+    assert!(manifest.iter().all(|e| e.mtime == 1_700_000_000));
+    let mode = |path: &str| manifest.iter().find(|e| e.path == path).unwrap().mode;
+    assert_eq!(mode("lib"), 0o755);
+    assert_eq!(mode("lib/config.toml"), 0o644);
+
+    // 时间戳在打包开始时确定一次，指定的时间戳优先于环境变量，非可重现模式不读取环境变量
+    let options = PackOptions { deterministic: true, source_date_epoch: Some(1_700_000_000), ..PackOptions::dev() };
+    assert_eq!(options.with_resolved_epoch().unwrap().source_date_epoch, Some(1_700_000_000));
+    assert!(PackOptions { deterministic: true, ..PackOptions::dev() }.with_resolved_epoch().unwrap().source_date_epoch.is_some());
+    assert_eq!(PackOptions::dev().with_resolved_epoch().unwrap().source_date_epoch, None);
+
+    // 命令行开关启用可重现模式
+    let args = Args::try_parse_from(["seesea-installer", "pack", "--preset", "release", "--deterministic"]).unwrap();
+    assert!(args.pack_options().unwrap().deterministic);
+}
+
+#[test]
+fn test_compress_decompress_file() {
+    let temp_dir = tempdir().unwrap();
+    let input = temp_dir.path().join("model.bin");
+    let compressed = temp_dir.path().join("model.bin.zst");
+    let output = temp_dir.path().join("model.out");
+    let content: Vec<u8> = (0..4 * 1024 * 1024u32).map(|i| (i % 251) as u8).collect();
+    fs::write(&input, &content).unwrap();
+
+    packager::compress_file(&input, &compressed).unwrap();
+    packager::decompress_file(&compressed, &output).unwrap();
+
+    assert!(fs::metadata(&compressed).unwrap().len() < content.len() as u64);
+    assert_eq!(fs::read(&output).unwrap(), content);
+}
+
+#[test]
+fn test_ignore_rules() {
+    let temp_dir = tempdir().unwrap();
+    let source = temp_dir.path().join("building");
+    let archive = temp_dir.path().join("payload.sspk");
+
+    fs::create_dir_all(source.join("__pycache__")).unwrap();
+    fs::create_dir_all(source.join("lib/__pycache__")).unwrap();
+    fs::create_dir_all(source.join("data")).unwrap();
+    fs::write(source.join(".installerignore"), "# build artifacts\n__pycache__/\n*.pyc\n.DS_Store\n*~\n!keep.pyc\n").unwrap();
+    fs::write(source.join("app.py"), b"print('seesea')").unwrap();
+    fs::write(source.join("__pycache__/app.cpython-312.pyc"), b"bytecode").unwrap();
+    fs::write(source.join("lib/__pycache__/mod.cpython-312.pyc"), b"bytecode").unwrap();
+    fs::write(source.join("lib/mod.pyc"), b"bytecode").unwrap();
+    fs::write(source.join("keep.pyc"), b"bytecode").unwrap();
+    fs::write(source.join(".DS_Store"), b"finder").unwrap();
+    fs::write(source.join("notes.txt~"), b"backup").unwrap();
+    fs::write(source.join("data/debug.log"), b"log").unwrap();
+
+    // 打包参数中的排除规则在 .installerignore 之后生效，包含规则覆盖两者
+    let options = PackOptions { exclude: vec!["data/*.log".into()], include: vec!["lib/mod.pyc".into()], ..PackOptions::dev() };
+    let summary = packager::pack_directory_with(&source, &archive, &options).unwrap();
+    assert_eq!(summary.skipped, [".DS_Store", "__pycache__/", "data/debug.log", "lib/__pycache__/", "notes.txt~"]);
+    assert_eq!(summary.entries, 5);
+
+    let manifest = packager::read_manifest(&archive).unwrap();
+    let paths: Vec<&str> = manifest.iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(paths, ["app.py", "data", "keep.pyc", "lib", "lib/mod.pyc"]);
+
+    // 不读取规则文件时只应用打包参数中的规则，规则文件本身仍不打包
+    let args = Args::try_parse_from(["seesea-installer", "pack", "--no-ignore-file", "--exclude", "__pycache__/"]).unwrap();
+    let summary = packager::pack_directory_with(&source, &archive, &args.pack_options().unwrap()).unwrap();
+    assert_eq!(summary.skipped, ["__pycache__/", "lib/__pycache__/"]);
+    let manifest = packager::read_manifest(&archive).unwrap();
+    assert!(manifest.iter().any(|e| e.path == ".DS_Store"));
+    assert!(!manifest.iter().any(|e| e.path == ".installerignore"));
+}
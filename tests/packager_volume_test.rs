@@ -0,0 +1,119 @@
+// SeeSea Installer - Packager Volume Tests
+// 测试按大小拆分安装包为分卷，以及分卷的拼接和缺失、错序、截断检测
+
+mod common;
+
+use seesea_installer::cli::parse_size;
+use seesea_installer::packager::volume::{self, MIN_VOLUME_SIZE};
+use seesea_installer::packager::{self, PackOptions};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// 创建源目录并按最小分卷大小拆分打包，返回安装包路径
+fn pack_split(root: &Path) -> std::path::PathBuf {
+    let source = root.join("building");
+    fs::create_dir_all(source.join("lib")).unwrap();
+    fs::write(source.join("model.bin"), common::random_bytes(200_000, 0x9E37_79B9_7F4A_7C15)).unwrap();
+    fs::write(source.join("lib/small.txt"), b"small file").unwrap();
+
+    let archive = root.join("payload.sspk");
+    let options = PackOptions { volume_size: Some(MIN_VOLUME_SIZE), ..PackOptions::dev() };
+    let summary = packager::pack_directory_with(&source, &archive, &options).unwrap();
+    assert_eq!(summary.volumes.len(), 4);
+    assert_eq!(summary.volumes[0], volume::volume_path(&archive, 1));
+    archive
+}
+
+#[test]
+fn test_split_and_reassemble() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let archive = pack_split(root);
+
+    // 每个分卷都不超过指定大小，不生成未拆分的安装包文件
+    assert!(!archive.exists());
+    for index in 1..=4 {
+        let len = fs::metadata(volume::volume_path(&archive, index)).unwrap().len();
+        assert!(len <= MIN_VOLUME_SIZE, "volume {index} is {len} bytes");
+    }
+
+    // 可以用去掉序号的路径或任意一个分卷打开
+    assert_eq!(packager::read_manifest(&archive).unwrap().len(), 3);
+    packager::verify_archive(&volume::volume_path(&archive, 3), &Default::default()).unwrap();
+    packager::unpack_directory(&archive, &root.join("output")).unwrap();
+    assert_eq!(fs::read(root.join("output/model.bin")).unwrap(), fs::read(root.join("building/model.bin")).unwrap());
+    assert_eq!(fs::read(root.join("output/lib/small.txt")).unwrap(), b"small file");
+
+    // 重新打包为更少的分卷时删除多余的旧分卷
+    let options = PackOptions { volume_size: Some(MIN_VOLUME_SIZE * 2), ..PackOptions::dev() };
+    let summary = packager::pack_directory_with(&root.join("building"), &archive, &options).unwrap();
+    assert_eq!(summary.volumes.len(), 2);
+    assert!(!volume::volume_path(&archive, 3).exists());
+    packager::unpack_directory(&archive, &root.join("output2")).unwrap();
+}
+
+#[test]
+fn test_detect_missing_and_out_of_order_volumes() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let archive = pack_split(root);
+    let second = volume::volume_path(&archive, 2);
+    let third = volume::volume_path(&archive, 3);
+    let output = root.join("output");
+
+    let original = fs::read(&second).unwrap();
+    fs::remove_file(&second).unwrap();
+    let error = packager::unpack_directory(&archive, &output).unwrap_err();
+    assert!(error.to_string().contains("Missing volume 2 of 4"), "unexpected error: {error}");
+
+    // 第二和第三个分卷互换了文件名
+    fs::rename(&third, &second).unwrap();
+    fs::write(&third, &original).unwrap();
+    let error = packager::unpack_directory(&archive, &output).unwrap_err();
+    assert!(error.to_string().contains("out of order"), "unexpected error: {error}");
+    fs::rename(&second, root.join("tmp")).unwrap();
+    fs::rename(&third, &second).unwrap();
+    fs::rename(root.join("tmp"), &third).unwrap();
+
+    // 截断的分卷
+    fs::write(&second, &original[..original.len() - 1]).unwrap();
+    let error = packager::unpack_directory(&archive, &output).unwrap_err();
+    assert!(error.to_string().contains("truncated"), "unexpected error: {error}");
+
+    // 混入其他分卷集的分卷
+    fs::write(root.join("building/model.bin"), common::random_bytes(200_000, 7)).unwrap();
+    let other = root.join("other.sspk");
+    let options = PackOptions { volume_size: Some(MIN_VOLUME_SIZE), ..PackOptions::dev() };
+    packager::pack_directory_with(&root.join("building"), &other, &options).unwrap();
+    fs::copy(volume::volume_path(&other, 2), &second).unwrap();
+    let error = packager::unpack_directory(&archive, &output).unwrap_err();
+    assert!(error.to_string().contains("different split archive"), "unexpected error: {error}");
+
+    fs::write(&second, &original).unwrap();
+    packager::unpack_directory(&archive, &output).unwrap();
+}
+
+#[test]
+fn test_volume_size_limits() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("building")).unwrap();
+    fs::write(root.join("building/app.txt"), b"application").unwrap();
+
+    let options = PackOptions { volume_size: Some(1024), ..PackOptions::dev() };
+    assert!(packager::pack_directory_with(&root.join("building"), &root.join("p.sspk"), &options).is_err());
+
+    // 自解压安装程序不能拆分
+    let options = PackOptions { volume_size: Some(MIN_VOLUME_SIZE), ..PackOptions::dev() };
+    let stub = root.join("stub");
+    fs::write(&stub, b"stub").unwrap();
+    let error = packager::bundle_installer(&stub, &root.join("building"), None, &root.join("setup"), &options).unwrap_err();
+    assert!(error.to_string().contains("cannot be split"), "unexpected error: {error}");
+
+    assert_eq!(parse_size("4GB"), Ok(4_000_000_000));
+    assert_eq!(parse_size("2G"), Ok(2 << 30));
+    assert_eq!(parse_size("700MiB"), Ok(700 << 20));
+    assert_eq!(parse_size("65536"), Ok(65536));
+    assert!(parse_size("4 parsecs").is_err());
+}
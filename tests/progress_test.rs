@@ -0,0 +1,98 @@
+// SeeSea Installer - Progress Tests
+// 测试打包、解压、单文件压缩解压的进度回调和进度条文本
+
+use seesea_installer::packager::{self, PackOptions, Progress, ProgressHandler, UnpackOptions};
+use seesea_installer::progress_bar::render_line;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::tempdir;
+
+/// 记录的进度事件: 已处理字节数、总字节数、当前条目
+type Events = Arc<Mutex<Vec<(u64, Option<u64>, Option<String>)>>>;
+
+/// 创建把每个进度事件记录下来的回调
+fn recorder() -> (ProgressHandler, Events) {
+    let events: Events = Arc::default();
+    let sink = Arc::clone(&events);
+    let handler = ProgressHandler::new(move |progress: &Progress| {
+        sink.lock().unwrap().push((progress.processed, progress.total, progress.entry.map(str::to_string)));
+    });
+    (handler, events)
+}
+
+/// 进度单调递增并在最后达到总量，返回报告过的条目
+fn assert_completes(events: &Events, total: u64) -> Vec<String> {
+    let events = events.lock().unwrap();
+    assert!(!events.is_empty());
+    assert!(events.windows(2).all(|pair| pair[0].0 <= pair[1].0));
+    assert!(events.iter().all(|(_, event_total, _)| *event_total == Some(total)));
+    assert_eq!(events.last().unwrap().0, total);
+    let mut entries: Vec<String> = events.iter().filter_map(|(_, _, entry)| entry.clone()).collect();
+    entries.dedup();
+    entries
+}
+
+#[test]
+fn test_pack_and_unpack_progress() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let source = root.join("building");
+    fs::create_dir_all(source.join("lib")).unwrap();
+    fs::write(source.join("a.bin"), vec![1u8; 300_000]).unwrap();
+    fs::write(source.join("lib/b.bin"), vec![2u8; 200_000]).unwrap();
+    // 与 a.bin 内容相同，去重后不读取数据也要计入进度
+    fs::write(source.join("lib/c.bin"), vec![1u8; 300_000]).unwrap();
+    let archive = root.join("payload.sspk");
+
+    let (handler, events) = recorder();
+    let options = PackOptions { progress: handler, ..PackOptions::dev() };
+    let summary = packager::pack_directory_with(&source, &archive, &options).unwrap();
+    assert_eq!(summary.deduplicated, 1);
+    assert_eq!(assert_completes(&events, 800_000), ["a.bin", "lib", "lib/b.bin", "lib/c.bin"]);
+
+    let (handler, events) = recorder();
+    let options = UnpackOptions { hardlinks: true, progress: handler, ..Default::default() };
+    packager::unpack_directory_with(&archive, &root.join("output"), &options).unwrap();
+    assert_eq!(assert_completes(&events, 800_000), ["a.bin", "lib", "lib/b.bin", "lib/c.bin"]);
+
+    // 只解压部分条目时总量只包含被选中的条目
+    let (handler, events) = recorder();
+    let options = UnpackOptions { progress: handler, ..Default::default() };
+    packager::unpack_paths(&archive, &root.join("partial"), &["lib/b.bin"], &options).unwrap();
+    assert_eq!(assert_completes(&events, 200_000), ["lib/b.bin"]);
+}
+
+#[test]
+fn test_compress_file_progress() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let input = root.join("data.txt");
+    fs::write(&input, "progress ".repeat(100_000)).unwrap();
+
+    let (handler, events) = recorder();
+    let options = PackOptions { progress: handler, ..PackOptions::dev() };
+    packager::compress_file_with(&input, &root.join("data.zst"), &options).unwrap();
+    assert_completes(&events, 900_000);
+
+    // 解压按读取的压缩数据计算进度
+    let (handler, events) = recorder();
+    let options = UnpackOptions { progress: handler, ..Default::default() };
+    packager::decompress_file_with(&root.join("data.zst"), &root.join("data.out"), &options).unwrap();
+    assert_completes(&events, fs::metadata(root.join("data.zst")).unwrap().len());
+    assert_eq!(fs::read(root.join("data.out")).unwrap(), fs::read(&input).unwrap());
+}
+
+#[test]
+fn test_render_line() {
+    let line = render_line("Packing", 512 * 1024 * 1024, Some(1024 * 1024 * 1024), Duration::from_secs(4), Some("lib/model.bin"));
+    assert!(line.starts_with("Packing [############............]  50%"), "{line}");
+    assert!(line.contains("512.0 MiB / 1.0 GiB 128.0 MiB/s ETA 0:04 lib/model.bin"), "{line}");
+
+    // 总量未知时不显示百分比和剩余时间，过长的路径只保留结尾
+    let entry = format!("{}/tail.txt", "very/long/directory".repeat(4));
+    let line = render_line("Unpacking", 1000, None, Duration::from_secs(1), Some(&entry));
+    assert!(line.starts_with("Unpacking 1000 B 1000 B/s ..."), "{line}");
+    assert!(line.ends_with("directory/tail.txt"), "{line}");
+    assert!(!line.contains("ETA"));
+}
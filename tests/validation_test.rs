@@ -0,0 +1,226 @@
+// SeeSea Installer - Config Validation Tests
+// 测试收集全部配置问题、问题在配置文件中的行列位置以及 validate 命令
+
+use clap::Parser;
+use seesea_installer::layering::{self, ConfigOverrides};
+use seesea_installer::validation::{self, Severity, ValidationReport};
+use seesea_installer::{Args, commands, config};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// 包含多处问题的配置，行号在断言中使用
+const BROKEN_CONFIG: &str = r#"[project]
+name = "broken"
+version = "1.2"
+colour = "blue"
+
+[install_options]
+default_dir = "/opt/broken"
+
+[platform.linx]
+default_dir = "/usr/local/broken"
+
+[[components]]
+name = "core"
+files = ["bin/app", "missing/lib.so"]
+depends_on = ["ui"]
+
+[[components]]
+name = "ui"
+depends_on = ["core"]
+
+[[dependencies]]
+name = "python"
+version = ">=3.8.0"
+depends_on = ["libssl"]
+platforms = ["linus"]
+"#;
+
+/// 写入配置文件并验证
+fn validate_file(root: &Path, contents: &str) -> ValidationReport {
+    let path = root.join("install.toml");
+    fs::write(&path, contents).unwrap();
+    let layers = layering::load_layers(&path, &ConfigOverrides::default()).unwrap();
+    validation::validate_layers(&layers, Some(root))
+}
+
+/// 查找指定键的问题
+fn find<'a>(report: &'a ValidationReport, key: &str) -> &'a validation::Diagnostic {
+    report
+        .diagnostics
+        .iter()
+        .find(|d| d.key == key)
+        .unwrap_or_else(|| panic!("no diagnostic for {key}: {:#?}", report.diagnostics))
+}
+
+#[test]
+fn test_collects_all_problems_with_locations() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("bin")).unwrap();
+    fs::write(root.join("bin/app"), "app").unwrap();
+    let report = validate_file(root, BROKEN_CONFIG);
+
+    // 未知的键是警告，未知的覆盖平台是错误
+    let unknown = find(&report, "project.colour");
+    assert_eq!(unknown.severity, Severity::Warning);
+    assert_eq!((unknown.location.as_ref().unwrap().line, unknown.location.as_ref().unwrap().column), (Some(4), Some(1)));
+    let platform = find(&report, "platform.linx");
+    assert_eq!(platform.severity, Severity::Error);
+    assert_eq!(platform.location.as_ref().unwrap().line, Some(9));
+
+    // 版本号格式错误
+    let version = find(&report, "project.version");
+    assert_eq!(version.severity, Severity::Warning);
+    assert_eq!(version.location.as_ref().unwrap().line, Some(3));
+
+    // 循环依赖和不存在的依赖
+    let cycle = find(&report, "components[0].depends_on");
+    assert!(cycle.message.contains("core -> ui -> core"), "unexpected message: {}", cycle.message);
+    assert_eq!(cycle.location.as_ref().unwrap().line, Some(15));
+    let missing = find(&report, "dependencies[0].depends_on[0]");
+    assert!(missing.message.contains("libssl"), "unexpected message: {}", missing.message);
+    let location = missing.location.as_ref().unwrap();
+    assert_eq!((location.line, location.column), (Some(24), Some(15)));
+
+    // 依赖的未知平台
+    let platform = find(&report, "dependencies[0].platforms[0]");
+    assert_eq!(platform.severity, Severity::Error);
+    assert_eq!(platform.location.as_ref().unwrap().line, Some(25));
+
+    // 只报告不存在的组件文件
+    let file = find(&report, "components[0].files[1]");
+    assert_eq!(file.severity, Severity::Warning);
+    assert!(!report.diagnostics.iter().any(|d| d.key == "components[0].files[0]"));
+
+    // 带比较运算符的依赖版本有效
+    assert!(!report.diagnostics.iter().any(|d| d.key == "dependencies[0].version"));
+
+    assert_eq!(report.error_count(), 4, "unexpected diagnostics: {:#?}", report.diagnostics);
+    assert_eq!(report.warning_count(), 3, "unexpected diagnostics: {:#?}", report.diagnostics);
+    assert!(report.has_errors());
+    assert!(file.render().starts_with(&format!("{}:14:", root.join("install.toml").display())), "unexpected rendering: {}", file.render());
+}
+
+#[test]
+fn test_locations_follow_included_files() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    fs::write(
+        root.join("base.toml"),
+        "[project]\nname = \"included\"\nversion = \"1.0.0\"\n\n[install_options]\ndefault_dir = \"/opt/included\"\n\n[[commands]]\nname = \"start\"\nprogram = \"\"\ntimeout = 0\n",
+    )
+    .unwrap();
+    let report = validate_file(root, "include = [\"base.toml\"]\n\n[install_options]\nsilent = \"yes\"\n");
+
+    // 类型错误时不再检查配置内容
+    assert_eq!(report.error_count(), 1, "unexpected diagnostics: {:#?}", report.diagnostics);
+    let silent = find(&report, "install_options.silent");
+    let location = silent.location.as_ref().unwrap();
+    assert_eq!((location.source.as_str(), location.line), (root.join("install.toml").display().to_string().as_str(), Some(4)));
+
+    // 同一命令的多个错误都被报告，位置在被包含的文件中
+    let report = validate_file(root, "include = [\"base.toml\"]\n");
+    let program = find(&report, "commands[0].program");
+    let timeout = find(&report, "commands[0].timeout");
+    assert_eq!(program.location.as_ref().unwrap().source, root.join("base.toml").display().to_string());
+    assert_eq!(program.location.as_ref().unwrap().line, Some(10));
+    assert_eq!(timeout.location.as_ref().unwrap().line, Some(11));
+}
+
+#[test]
+fn test_loading_reports_every_error() {
+    let error = config::parse_config(
+        r#"
+[project]
+name = ""
+version = "1.0.0"
+
+[install_options]
+default_dir = "/opt/app"
+
+[[components]]
+name = "core"
+depends_on = ["ui"]
+"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(error.contains("Project name cannot be empty"), "unexpected error: {error}");
+    assert!(error.contains("components[0].depends_on[0]: Component core depends on non-existent component: ui"), "unexpected error: {error}");
+
+    // 警告不影响加载
+    let config = config::parse_config("[project]\nname = \"app\"\nversion = \"1.0\"\nunknown = 1\n\n[install_options]\ndefault_dir = \"/opt/app\"\n");
+    assert!(config.is_ok(), "unexpected error: {config:?}");
+}
+
+#[test]
+fn test_loading_keeps_added_checks_as_warnings() {
+    // 依赖关系和平台名称检查随 validate 命令加入，加载时只是警告，以前能加载的配置仍能加载
+    let contents = r#"
+[project]
+name = "app"
+version = "1.0.0"
+
+[install_options]
+default_dir = "/opt/app"
+
+[platform.linx]
+default_dir = "/usr/local/app"
+
+[[components]]
+name = "core"
+depends_on = ["ui"]
+platforms = ["linus"]
+
+[[components]]
+name = "ui"
+depends_on = ["core"]
+
+[[dependencies]]
+name = "a"
+version = "1.0.0"
+depends_on = ["b", "libssl"]
+
+[[dependencies]]
+name = "b"
+version = "1.0.0"
+depends_on = ["a"]
+"#;
+    let config = config::parse_config(contents);
+    assert!(config.is_ok(), "unexpected error: {config:?}");
+
+    // validate 命令把同样的问题报告为错误
+    let temp_dir = tempdir().unwrap();
+    let report = validate_file(temp_dir.path(), contents);
+    for key in ["platform.linx", "components[0].depends_on", "components[0].platforms[0]", "dependencies[0].depends_on", "dependencies[0].depends_on[1]"] {
+        assert_eq!(find(&report, key).severity, Severity::Error, "{key}");
+    }
+    assert_eq!(report.error_count(), 5, "unexpected diagnostics: {:#?}", report.diagnostics);
+}
+
+#[test]
+fn test_validate_command() {
+    let temp_dir = tempdir().unwrap();
+    let root = temp_dir.path();
+    let path = root.join("install.toml");
+    fs::create_dir_all(root.join("bin")).unwrap();
+    fs::write(root.join("bin/app"), "app").unwrap();
+    let run = |contents: &str| {
+        fs::write(&path, contents).unwrap();
+        let args = Args::try_parse_from(["seesea-installer", "validate", "--config", &path.display().to_string()]).unwrap();
+        let mut out = Vec::new();
+        let result = commands::run_packager_command(&args, &mut out).expect("not a packager command");
+        (result, String::from_utf8(out).unwrap())
+    };
+
+    let (result, output) = run(BROKEN_CONFIG);
+    assert!(result.is_err());
+    assert!(output.contains(&format!("{}:24:15: error: dependencies[0].depends_on[0]", path.display())), "unexpected output: {output}");
+    assert!(output.contains("4 error(s), 3 warning(s)"), "unexpected output: {output}");
+
+    let (result, output) = run("[project]\nname = \"app\"\nversion = \"1.0.0\"\n\n[install_options]\ndefault_dir = \"/opt/app\"\n");
+    assert!(result.is_ok(), "unexpected error: {result:?}");
+    assert!(output.contains("0 error(s), 0 warning(s)"), "unexpected output: {output}");
+}